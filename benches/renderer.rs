@@ -0,0 +1,249 @@
+//! Benchmarks of the steps of a frame on a WARP device, so that they run the
+//! same on any machine, including CI runners without a GPU.
+//!
+//! ```sh
+//! cargo bench --features bench
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+
+use egui::{
+    Color32, ColorImage, ImageData, Pos2, RawInput, Rect, TextureId,
+    TextureOptions, TexturesDelta, epaint::ImageDelta, vec2,
+};
+use egui_directx10::{Renderer, RendererOutput, bench};
+
+use windows::{
+    Win32::{
+        Foundation::HMODULE,
+        Graphics::{Direct3D10::*, Dxgi::Common::*},
+    },
+    core::BOOL,
+};
+
+const FRAME_SIZE: (u32, u32) = (1280, 720);
+
+fn create_warp_device() -> ID3D10Device {
+    let mut device = None;
+    unsafe {
+        D3D10CreateDevice(
+            None,
+            D3D10_DRIVER_TYPE_WARP,
+            HMODULE::default(),
+            0,
+            D3D10_SDK_VERSION,
+            Some(&mut device),
+        )
+    }
+    .expect("Failed to create a WARP device");
+    device.unwrap()
+}
+
+fn create_render_target(device: &ID3D10Device) -> ID3D10RenderTargetView {
+    let desc = D3D10_TEXTURE2D_DESC {
+        Width: FRAME_SIZE.0,
+        Height: FRAME_SIZE.1,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D10_USAGE_DEFAULT,
+        BindFlags: D3D10_BIND_RENDER_TARGET.0 as _,
+        ..Default::default()
+    };
+    let mut render_target = None;
+    unsafe {
+        let texture = device
+            .CreateTexture2D(&desc, None)
+            .expect("Failed to create the render target");
+        device
+            .CreateRenderTargetView(&texture, None, Some(&mut render_target))
+            .expect("Failed to create the render target view");
+    }
+    render_target.unwrap()
+}
+
+/// Wait until the GPU, here the WARP threads, finished the commands issued
+/// so far.
+fn wait_for_gpu(device: &ID3D10Device, query: &ID3D10Query) {
+    unsafe {
+        device.Flush();
+        query.End();
+        let mut done = BOOL(0);
+        // `GetData` returns `S_FALSE` and leaves `done` unchanged while the
+        // commands are running.
+        while query
+            .GetData(
+                Some(&mut done as *mut BOOL as _),
+                size_of::<BOOL>() as _,
+                0,
+            )
+            .is_ok()
+            && !done.as_bool()
+        {}
+    }
+}
+
+/// Run a few passes of the `egui` demo, so that its windows are laid out,
+/// and return the output of the last one with the textures of all of them.
+fn demo_output(egui_ctx: &egui::Context) -> RendererOutput {
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    let raw_input = RawInput {
+        screen_rect: Some(Rect::from_min_size(
+            Pos2::ZERO,
+            vec2(FRAME_SIZE.0 as f32, FRAME_SIZE.1 as f32),
+        )),
+        ..RawInput::default()
+    };
+    let mut textures_delta = TexturesDelta::default();
+    let mut output = None;
+    for _ in 0..3 {
+        let full_output = egui_ctx.run(raw_input.clone(), |ctx| demo.ui(ctx));
+        let (renderer_output, _, _) = egui_directx10::split_output(full_output);
+        textures_delta.append(renderer_output.textures_delta.clone());
+        output = Some(renderer_output);
+    }
+    RendererOutput {
+        textures_delta,
+        ..output.unwrap()
+    }
+}
+
+fn clone_output(output: &RendererOutput) -> RendererOutput {
+    RendererOutput {
+        textures_delta: TexturesDelta::default(),
+        shapes: output.shapes.clone(),
+        pixels_per_point: output.pixels_per_point,
+    }
+}
+
+fn meshes(c: &mut Criterion) {
+    let device = create_warp_device();
+    let egui_ctx = egui::Context::default();
+    let output = demo_output(&egui_ctx);
+    let primitives =
+        egui_ctx.tessellate(output.shapes, output.pixels_per_point);
+
+    c.bench_function("convert_meshes", |b| {
+        b.iter_batched(
+            || primitives.clone(),
+            |primitives| {
+                bench::convert_meshes(
+                    primitives,
+                    FRAME_SIZE,
+                    output.pixels_per_point,
+                )
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    let meshes =
+        bench::convert_meshes(primitives, FRAME_SIZE, output.pixels_per_point);
+    c.bench_function("upload_meshes/immutable", |b| {
+        b.iter(|| black_box(bench::upload_meshes(&device, &meshes).unwrap()));
+    });
+    let mut buffers = bench::DynamicBuffers::default();
+    c.bench_function("upload_meshes/dynamic", |b| {
+        b.iter(|| {
+            bench::upload_meshes_dynamic(&device, &mut buffers, &meshes)
+                .unwrap()
+        });
+    });
+}
+
+fn textures(c: &mut Criterion) {
+    let device = create_warp_device();
+    let mut renderer = Renderer::new(&device).unwrap();
+    let image = |size: usize| {
+        ImageData::from(ColorImage::filled([size, size], Color32::GRAY))
+    };
+    let options = TextureOptions::default();
+
+    // Whole updates create a new texture, as for the font atlas and images.
+    for size in [256, 2048] {
+        c.bench_function(&format!("update_textures/whole/{size}"), |b| {
+            b.iter(|| {
+                let delta = TexturesDelta {
+                    set: vec![(
+                        TextureId::Managed(1),
+                        ImageDelta::full(image(size), options),
+                    )],
+                    free: vec![],
+                };
+                renderer.update_textures(&device, delta).unwrap();
+            });
+        });
+    }
+
+    // Partial updates overwrite a region, as when glyphs are added to the
+    // font atlas.
+    let atlas = TexturesDelta {
+        set: vec![(
+            TextureId::Managed(1),
+            ImageDelta::full(image(2048), options),
+        )],
+        free: vec![],
+    };
+    renderer.update_textures(&device, atlas).unwrap();
+    for size in [16, 256] {
+        c.bench_function(&format!("update_textures/partial/{size}"), |b| {
+            b.iter(|| {
+                let delta = TexturesDelta {
+                    set: vec![(
+                        TextureId::Managed(1),
+                        ImageDelta::partial([0, 0], image(size), options),
+                    )],
+                    free: vec![],
+                };
+                renderer.update_textures(&device, delta).unwrap();
+            });
+        });
+    }
+}
+
+fn frames(c: &mut Criterion) {
+    let device = create_warp_device();
+    let render_target = create_render_target(&device);
+    let mut renderer = Renderer::new(&device).unwrap();
+    let mut query = None;
+    unsafe {
+        device.CreateQuery(
+            &D3D10_QUERY_DESC {
+                Query: D3D10_QUERY_EVENT,
+                MiscFlags: 0,
+            },
+            Some(&mut query),
+        )
+    }
+    .unwrap();
+    let query = query.unwrap();
+    let egui_ctx = egui::Context::default();
+    let mut output = demo_output(&egui_ctx);
+    renderer
+        .update_textures(&device, std::mem::take(&mut output.textures_delta))
+        .unwrap();
+
+    c.bench_function("render/demo", |b| {
+        b.iter(|| {
+            renderer
+                .render(
+                    &device,
+                    &render_target,
+                    &egui_ctx,
+                    clone_output(&output),
+                )
+                .unwrap();
+            // Include the rasterization by WARP, which runs on the CPU.
+            wait_for_gpu(&device, &query);
+        });
+    });
+}
+
+criterion_group!(benches, meshes, textures, frames);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! A template for an overlay DLL. Build it with
+//! `cargo build --example overlay_dll --features ffi`, load it into the
+//! host and drive it with the functions declared in
+//! `include/egui_directx10.h`.
+
+use std::ffi::c_void;
+
+use windows::{Win32::Foundation::HINSTANCE, core::BOOL};
+
+const DLL_PROCESS_ATTACH: u32 = 1;
+
+#[unsafe(no_mangle)]
+extern "system" fn DllMain(_: HINSTANCE, reason: u32, _: *mut c_void) -> BOOL {
+    if reason == DLL_PROCESS_ATTACH {
+        let mut clicks = 0;
+        egui_directx10::ffi::set_ui(move |ctx| {
+            egui::Window::new("Overlay").show(ctx, |ui| {
+                ui.label("Hello from Rust!");
+                if ui.button(format!("Clicked {clicks} times")).clicked() {
+                    clicks += 1;
+                }
+            });
+        });
+    }
+    true.into()
+}
@@ -0,0 +1,70 @@
+use std::{cell::RefCell, time::Duration};
+
+use egui_directx10::win32::{self, State};
+
+use windows::{
+    Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+        Graphics::Direct3D10::ID3D10Device,
+        UI::WindowsAndMessaging::*,
+    },
+    core::w,
+};
+
+thread_local! {
+    static STATE: RefCell<Option<State>> = const { RefCell::new(None) };
+}
+
+extern "system" fn wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_DESTROY {
+        unsafe { PostQuitMessage(0) };
+        return LRESULT(0);
+    }
+    // Messages sent while the state is borrowed, such as `WM_SIZE` during a
+    // DPI change, go straight to `DefWindowProcW`.
+    let result = STATE.with(|state| {
+        let mut state = state.try_borrow_mut().ok()?;
+        state.as_mut()?.handle_message(hwnd, msg, wparam, lparam)
+    });
+    result
+        .unwrap_or_else(|| unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) })
+}
+
+fn create_device() -> ID3D10Device {
+    let (device, _) = egui_directx10::create_device(&Default::default())
+        .expect("Failed to create device");
+    device
+}
+
+fn main() {
+    win32::register_window_class(w!("egui-directx10"), Some(wnd_proc))
+        .expect("Failed to register window class");
+    let hwnd =
+        win32::create_window(w!("egui-directx10"), w!("egui demo"), 1280, 720)
+            .expect("Failed to create window");
+    let state =
+        State::new(hwnd, &create_device()).expect("Failed to set up egui");
+    STATE.with(|cell| *cell.borrow_mut() = Some(state));
+
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    win32::run_message_loop(|| {
+        STATE.with(|cell| {
+            let mut cell = cell.borrow_mut();
+            let Some(state) = cell.as_mut() else {
+                return Duration::MAX;
+            };
+            state
+                .run_ui(hwnd, |ctx| demo.ui(ctx))
+                .expect("Failed to render");
+            if state.close_requested() {
+                unsafe { DestroyWindow(hwnd) }.unwrap();
+            }
+            state.repaint_delay()
+        })
+    });
+}
@@ -0,0 +1,61 @@
+use windows::Win32::Graphics::Direct3D10::ID3D10Device;
+
+use winit::{
+    application::ApplicationHandler,
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    window::{Window, WindowId},
+};
+
+#[derive(Default)]
+struct App {
+    window: Option<(Window, egui_directx10::winit::State)>,
+    demo: egui_demo_lib::DemoWindows,
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        let window = event_loop
+            .create_window(Window::default_attributes())
+            .expect("Failed to create window");
+        let state =
+            egui_directx10::winit::State::new(&window, &create_device())
+                .expect("Failed to set up egui");
+        self.window = Some((window, state));
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some((window, state)) = &mut self.window else {
+            return;
+        };
+        let response = state.on_window_event(window, &event).unwrap();
+        if response.repaint {
+            window.request_redraw();
+        }
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested => state
+                .run_ui(window, |ctx| self.demo.ui(ctx))
+                .expect("Failed to render"),
+            _ => {},
+        }
+    }
+}
+
+fn create_device() -> ID3D10Device {
+    let (device, _) = egui_directx10::create_device(&Default::default())
+        .expect("Failed to create device");
+    device
+}
+
+fn main() {
+    EventLoop::new()
+        .unwrap()
+        .run_app(&mut App::default())
+        .unwrap();
+}
@@ -0,0 +1,80 @@
+//! Feed arbitrary sequences of texture deltas, including malformed ones that
+//! `egui` itself would never send, into the texture pool of the renderer.
+//!
+//! ```sh
+//! cargo +nightly fuzz run texture_deltas
+//! ```
+
+#![no_main]
+
+use std::mem;
+
+use arbitrary::Arbitrary;
+use egui::{
+    Color32, ColorImage, TextureId, TextureOptions, TexturesDelta, Vec2,
+    epaint::ImageDelta,
+};
+use egui_directx10::fuzzing::TexturePoolFuzzer;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    /// Set texture `id` to an image of `size`, or the region of it at `pos`,
+    /// with `pixels` pixels, which need not match the size.
+    Set {
+        id: u8,
+        pos: Option<(u8, u8)>,
+        size: (u8, u8),
+        pixels: u16,
+    },
+    /// Free texture `id`.
+    Free { id: u8 },
+    /// Apply the delta built so far and evict the textures.
+    Evict,
+}
+
+fuzz_target!(|frames: Vec<Vec<Op>>| {
+    let mut fuzzer = TexturePoolFuzzer::new();
+    for ops in frames {
+        let mut delta = TexturesDelta::default();
+        for op in ops {
+            match op {
+                Op::Set {
+                    id,
+                    pos,
+                    size: (width, height),
+                    pixels,
+                } => {
+                    let image = ColorImage {
+                        size: [width as usize, height as usize],
+                        source_size: Vec2::new(width as f32, height as f32),
+                        pixels: vec![Color32::WHITE; pixels as usize],
+                    };
+                    delta.set.push((
+                        texture_id(id),
+                        ImageDelta {
+                            image: image.into(),
+                            options: TextureOptions::default(),
+                            pos: pos.map(|(x, y)| [x as usize, y as usize]),
+                        },
+                    ));
+                },
+                Op::Free { id } => delta.free.push(texture_id(id)),
+                Op::Evict => {
+                    fuzzer.update(mem::take(&mut delta));
+                    fuzzer.evict();
+                },
+            }
+        }
+        fuzzer.update(delta);
+    }
+});
+
+/// Map the low half of the ids to managed textures, and the high half to
+/// user textures, which deltas must not touch.
+fn texture_id(id: u8) -> TextureId {
+    match id {
+        0..128 => TextureId::Managed(id as u64),
+        _ => TextureId::User((id - 128) as u64),
+    }
+}
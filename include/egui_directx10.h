@@ -0,0 +1,79 @@
+/*
+ * C interface of the `ffi` feature of `egui-directx10`.
+ *
+ * All functions return an HRESULT. The overlay functions (`egui_dx10_init`
+ * to `egui_dx10_shutdown`) must be called on the thread that presents the
+ * swap chain. See `src/ffi.rs` for details.
+ */
+
+#ifndef EGUI_DIRECTX10_H
+#define EGUI_DIRECTX10_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A renderer and `egui` context, see `egui_dx10_renderer_create`. */
+typedef struct EguiDx10Renderer EguiDx10Renderer;
+
+typedef struct EguiDx10Key {
+    /* An `egui::Key` name such as "Enter", "A" or "F1", in UTF-8. */
+    const char *name;
+    bool pressed;
+} EguiDx10Key;
+
+typedef struct EguiDx10Input {
+    /* Pointer position relative to the back buffer, in pixels. */
+    float pointer_x;
+    float pointer_y;
+    bool pointer_inside;
+    /* Bit 0: primary, bit 1: secondary, bit 2: middle. */
+    uint32_t buttons;
+    /* Scroll distance since the previous frame, in lines. */
+    float scroll_x;
+    float scroll_y;
+    /* Bit 0: Alt, bit 1: Ctrl, bit 2: Shift. */
+    uint32_t modifiers;
+    /* Text typed since the previous frame in UTF-8, or NULL. */
+    const char *text;
+    const EguiDx10Key *keys;
+    size_t key_count;
+    /* Zero for 1.0. */
+    float pixels_per_point;
+} EguiDx10Input;
+
+/* `device` is an `ID3D10Device*`. */
+int32_t egui_dx10_renderer_create(void *device, EguiDx10Renderer **renderer);
+int32_t egui_dx10_renderer_destroy(EguiDx10Renderer *renderer);
+/* `shader_resource_view` is an `ID3D10ShaderResourceView*`; the UI refers to
+ * it as `egui::TextureId::User(*texture_id)`. Only exported with the
+ * `user-textures` feature. */
+int32_t egui_dx10_renderer_register_texture(EguiDx10Renderer *renderer,
+                                            void *shader_resource_view,
+                                            uint64_t *texture_id);
+/* Returns S_FALSE if the texture was not registered. Only exported with the
+ * `user-textures` feature. */
+int32_t egui_dx10_renderer_unregister_texture(EguiDx10Renderer *renderer,
+                                              uint64_t texture_id);
+/* `render_target` is an `ID3D10RenderTargetView*`. `input` may be NULL. */
+int32_t egui_dx10_renderer_render(EguiDx10Renderer *renderer,
+                                  void *render_target,
+                                  const EguiDx10Input *input);
+
+/* `swap_chain` is an `IDXGISwapChain*` created on a Direct3D10 device. */
+int32_t egui_dx10_init(void *swap_chain);
+/* Call from the `Present` hook before the original. `input` may be NULL. */
+int32_t egui_dx10_render(const EguiDx10Input *input);
+/* Call from the `ResizeBuffers` hook before the original. */
+int32_t egui_dx10_resize(void);
+int32_t egui_dx10_shutdown(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
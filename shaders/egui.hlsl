@@ -1,3 +1,24 @@
+// Optional features are enabled with preprocessor switches. The precompiled
+// blobs in this directory are built without any of them; variants are
+// compiled at runtime (see `src/shader.rs`).
+//
+// + DITHER_ORDERED: add a 4x4 ordered dither to the output color.
+// + DITHER_INTERLEAVED_GRADIENT_NOISE: add interleaved gradient noise to the
+//   output color, matching the dithering of `egui-wgpu`.
+// + COLOR_MATRIX: transform the output color with a 3x3 matrix in linear
+//   space.
+// + COLOR_LUT: transform the output color with a 3D lookup table.
+// + COLOR_ADJUSTMENT: scale the brightness and adjust the gamma of the output
+//   color.
+// + GAMMA_LUT: map each channel of the output color through a 1D lookup
+//   table.
+// + TEXT_GAMMA: raise the coverage sampled from the font atlas to a power.
+// + SUBPIXEL_TEXT: output per-channel coverage of the font atlas to
+//   SV_TARGET1 for dual-source blending.
+// + COLOR_SPACES: convert sampled texels from the color space of the texture
+//   to sRGB, and the output color from sRGB to the color space of the render
+//   target.
+
 void vs_egui(
     in const float2 i_pos  : POSITION,
     in const float2 i_uv   : TEXCOORD,
@@ -13,9 +34,244 @@ void vs_egui(
 Texture2D<float4> g_texture: register(t0);
 SamplerState      g_sampler: register(s0);
 
+cbuffer egui_constants: register(b0) {
+    // 1 / (2^n - 1) for a render target with n bits per color channel.
+    float  g_dither_scale;
+    // Color spaces of the current texture and of the render target, one of
+    // the COLOR_SPACE_* constants below.
+    uint   g_texture_color_space;
+    uint   g_target_color_space;
+    // Nonzero if the current texture is the font atlas and text is drawn with
+    // per-channel coverage.
+    uint   g_subpixel_text;
+    // Rows of the color transform matrix.
+    float4 g_color_matrix[3];
+    // Brightness factor and reciprocal gamma of the color adjustment.
+    float2 g_color_adjustment;
+    // Exponent of the coverage of the font atlas, 1.0 for other textures.
+    float  g_text_gamma;
+};
+
+Texture3D<float4> g_color_lut: register(t1);
+Texture1D<float4> g_gamma_lut: register(t2);
+// Samples the lookup tables with linear filtering, clamped to the edges.
+SamplerState      g_lut_sampler: register(s1);
+
+float3 linear_from_gamma(float3 rgb) {
+    return rgb <= 0.04045 ? rgb / 12.92 : pow((rgb + 0.055) / 1.055, 2.4);
+}
+
+float3 gamma_from_linear(float3 rgb) {
+    return rgb <= 0.0031308
+        ? rgb * 12.92
+        : 1.055 * pow(rgb, 1.0 / 2.4) - 0.055;
+}
+
+static const uint COLOR_SPACE_SRGB   = 0;
+static const uint COLOR_SPACE_REC709 = 1;
+static const uint COLOR_SPACE_LINEAR = 2;
+
+#if defined(COLOR_SPACES)
+// Rec.709 video is displayed with the BT.1886 EOTF, a pure 2.4 power curve.
+// All color spaces share primaries, so only the transfer function differs.
+float3 linear_from_color_space(float3 rgb, uint color_space) {
+    if (color_space == COLOR_SPACE_LINEAR) {
+        return saturate(rgb);
+    }
+    return color_space == COLOR_SPACE_REC709
+        ? pow(saturate(rgb), 2.4)
+        : linear_from_gamma(saturate(rgb));
+}
+
+float3 color_space_from_linear(float3 rgb, uint color_space) {
+    if (color_space == COLOR_SPACE_LINEAR) {
+        return saturate(rgb);
+    }
+    return color_space == COLOR_SPACE_REC709
+        ? pow(saturate(rgb), 1.0 / 2.4)
+        : gamma_from_linear(saturate(rgb));
+}
+
+// Converts a premultiplied color between color spaces.
+float4 convert_color_space(float4 color, uint from, uint to) {
+    if (from == to || color.a <= 0.0) {
+        return color;
+    }
+    float3 rgb = color.rgb / color.a;
+    rgb = color_space_from_linear(linear_from_color_space(rgb, from), to);
+    return float4(rgb * color.a, color.a);
+}
+#endif
+
+#if defined(COLOR_MATRIX) || defined(COLOR_LUT)
+float4 color_transform(float4 color) {
+    if (color.a <= 0.0) {
+        return color;
+    }
+    float3 rgb = saturate(color.rgb / color.a);
+#if defined(COLOR_MATRIX)
+    rgb = linear_from_gamma(rgb);
+    rgb = float3(
+        dot(g_color_matrix[0].xyz, rgb),
+        dot(g_color_matrix[1].xyz, rgb),
+        dot(g_color_matrix[2].xyz, rgb));
+    rgb = gamma_from_linear(saturate(rgb));
+#else
+    uint width, height, depth;
+    g_color_lut.GetDimensions(width, height, depth);
+    float3 size = float3(width, height, depth);
+    rgb = g_color_lut.Sample(
+        g_lut_sampler, rgb * (size - 1.0) / size + 0.5 / size).rgb;
+#endif
+    return float4(rgb * color.a, color.a);
+}
+#define COLOR_TRANSFORM
+#endif
+
+#if defined(COLOR_ADJUSTMENT)
+float4 color_adjustment(float4 color) {
+    if (color.a <= 0.0) {
+        return color;
+    }
+    float3 rgb = saturate(color.rgb / color.a);
+    rgb = saturate(pow(rgb, g_color_adjustment.y) * g_color_adjustment.x);
+    return float4(rgb * color.a, color.a);
+}
+#endif
+
+#if defined(GAMMA_LUT)
+float4 gamma_lut(float4 color) {
+    if (color.a <= 0.0) {
+        return color;
+    }
+    float3 rgb = saturate(color.rgb / color.a);
+    uint width;
+    g_gamma_lut.GetDimensions(width);
+    float3 u = rgb * (width - 1.0) / width + 0.5 / width;
+    rgb = float3(
+        g_gamma_lut.Sample(g_lut_sampler, u.r).r,
+        g_gamma_lut.Sample(g_lut_sampler, u.g).g,
+        g_gamma_lut.Sample(g_lut_sampler, u.b).b);
+    return float4(rgb * color.a, color.a);
+}
+#endif
+
+#if defined(DITHER_ORDERED)
+static const float BAYER_4X4[16] = {
+     0.0,  8.0,  2.0, 10.0,
+    12.0,  4.0, 14.0,  6.0,
+     3.0, 11.0,  1.0,  9.0,
+    15.0,  7.0, 13.0,  5.0,
+};
+
+// Returns a value in (-0.5, 0.5), so flat colors are never changed.
+float dither_noise(float2 pos) {
+    uint2 p = uint2(pos) & 3;
+    return (BAYER_4X4[p.y * 4 + p.x] + 0.5) / 16.0 - 0.5;
+}
+#define DITHERING
+#elif defined(DITHER_INTERLEAVED_GRADIENT_NOISE)
+// From https://www.iryoku.com/next-generation-post-processing-in-call-of-duty-advanced-warfare.
+// Scaled down slightly to ensure flat colors are never changed.
+float dither_noise(float2 pos) {
+    float f = 0.06711056 * pos.x + 0.00583715 * pos.y;
+    return (frac(52.9829189 * frac(f)) - 0.5) * 0.95;
+}
+#define DITHERING
+#endif
+
+// Applies the color transform, adjustment and gamma lookup table, and
+// converts a premultiplied color to the color space of the render target.
+float4 output_color(float4 color) {
+#if defined(COLOR_TRANSFORM)
+    color = color_transform(color);
+#endif
+#if defined(COLOR_ADJUSTMENT)
+    color = color_adjustment(color);
+#endif
+#if defined(GAMMA_LUT)
+    color = gamma_lut(color);
+#endif
+#if defined(COLOR_SPACES)
+    color = convert_color_space(
+        color, COLOR_SPACE_SRGB, g_target_color_space);
+#endif
+    return color;
+}
+
 float4 ps_egui(
+    in const float4 i_pos  : SV_POSITION,
+    in const float2 i_uv   : TEXCOORD,
+    in const float4 i_color: COLOR
+#if defined(SUBPIXEL_TEXT)
+    , out float4 o_blend: SV_TARGET1
+#endif
+    ): SV_TARGET {
+#if defined(COLOR_SPACES)
+    float4 texel = convert_color_space(
+        g_texture.Sample(g_sampler, i_uv),
+        g_texture_color_space,
+        COLOR_SPACE_SRGB);
+#else
+    float4 texel = g_texture.Sample(g_sampler, i_uv);
+#endif
+#if defined(TEXT_GAMMA)
+    // The font atlas is premultiplied white, so all channels are coverage.
+    if (g_text_gamma != 1.0) {
+        texel = pow(texel, g_text_gamma);
+    }
+#endif
+    float4 color = output_color(i_color * texel);
+#if defined(SUBPIXEL_TEXT)
+    o_blend = color.aaaa;
+    if (g_subpixel_text != 0) {
+        // Sample the coverage of the red and blue subpixels of an RGB stripe
+        // panel a third of a pixel to the left and right.
+        float2 step = ddx(i_uv) / 3.0;
+        float3 coverage = float3(
+            g_texture.Sample(g_sampler, i_uv - step).a,
+            texel.a,
+            g_texture.Sample(g_sampler, i_uv + step).a);
+#if defined(TEXT_GAMMA)
+        coverage.xz = pow(coverage.xz, g_text_gamma);
+#endif
+        float3 alpha = i_color.a * coverage;
+        float3 straight = i_color.a > 0.0 ? i_color.rgb / i_color.a : 0.0;
+        float3 rgb = output_color(float4(straight, 1.0)).rgb;
+        color = float4(rgb * alpha, alpha.g);
+        o_blend = float4(alpha, alpha.g);
+    }
+#endif
+#if defined(DITHERING)
+    color.rgb += dither_noise(i_pos.xy) * g_dither_scale;
+#endif
+    return color;
+}
+
+// Separable Gaussian blur used for the backdrop of translucent panels (see
+// `src/backdrop.rs`). Each pass samples `g_texture` around the texel at
+// `g_blur_uv_offset + uv * g_blur_uv_scale`, `g_blur_step` apart.
+cbuffer blur_constants: register(b3) {
+    float2 g_blur_step;
+    float2 g_blur_uv_offset;
+    float2 g_blur_uv_scale;
+};
+
+static const float BLUR_WEIGHTS[5] = {
+    0.2270270270, 0.1945945946, 0.1216216216, 0.0540540541, 0.0162162162,
+};
+
+float4 ps_blur(
     in const float4 i_pos  : SV_POSITION,
     in const float2 i_uv   : TEXCOORD,
     in const float4 i_color: COLOR): SV_TARGET {
-    return i_color * g_texture.Sample(g_sampler, i_uv);
+    float2 uv = g_blur_uv_offset + i_uv * g_blur_uv_scale;
+    float3 sum = g_texture.Sample(g_sampler, uv).rgb * BLUR_WEIGHTS[0];
+    [unroll] for (int i = 1; i < 5; i++) {
+        sum += g_texture.Sample(g_sampler, uv + g_blur_step * i).rgb
+            * BLUR_WEIGHTS[i];
+        sum += g_texture.Sample(g_sampler, uv - g_blur_step * i).rgb
+            * BLUR_WEIGHTS[i];
+    }
+    return float4(sum, 1.0);
 }
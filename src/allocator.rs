@@ -0,0 +1,48 @@
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+/// Creates and destroys the GPU textures holding the images managed by
+/// `egui`, such as the font atlas and images loaded by `egui_extras`.
+///
+/// Engines with their own VRAM budgeting, pooling or placement strategies can
+/// route the allocations of the renderer through them by passing an
+/// implementation to [`Renderer::with_texture_allocator`](crate::Renderer::with_texture_allocator).
+/// User textures registered with
+/// [`Renderer::register_user_texture`](crate::Renderer::register_user_texture)
+/// are owned by the application and never go through the allocator.
+pub trait TextureAllocator {
+    /// Create a texture described by `desc` and initialized with
+    /// `initial_data`.
+    ///
+    /// The returned texture must match `desc` in size, format, usage, bind
+    /// flags and CPU access flags; a texture recycled from a pool must be
+    /// overwritten with `initial_data`.
+    fn allocate(
+        &mut self,
+        device: &ID3D10Device,
+        desc: &D3D10_TEXTURE2D_DESC,
+        initial_data: &D3D10_SUBRESOURCE_DATA,
+    ) -> Result<ID3D10Texture2D>;
+
+    /// Take back a texture returned by [`TextureAllocator::allocate`] that
+    /// is no longer used by the renderer. All views of it have been released
+    /// at this point. The default implementation releases it.
+    fn free(&mut self, texture: ID3D10Texture2D) {
+        drop(texture);
+    }
+}
+
+/// The [`TextureAllocator`] used by default, which creates every texture
+/// with `ID3D10Device::CreateTexture2D`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultTextureAllocator;
+
+impl TextureAllocator for DefaultTextureAllocator {
+    fn allocate(
+        &mut self,
+        device: &ID3D10Device,
+        desc: &D3D10_TEXTURE2D_DESC,
+        initial_data: &D3D10_SUBRESOURCE_DATA,
+    ) -> Result<ID3D10Texture2D> {
+        unsafe { device.CreateTexture2D(desc, Some(initial_data)) }
+    }
+}
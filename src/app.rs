@@ -0,0 +1,423 @@
+//! An `eframe`-style application framework on top of
+//! [`winit::State`](crate::winit::State).
+//!
+//! Applications structured around `eframe::App` can switch to Direct3D10 by
+//! implementing [`App`] instead and starting with [`run_native`]. The trait
+//! mirrors the parts of `eframe` that do not depend on its own backends:
+//! [`App::update`], persistence through [`App::save`] and [`Storage`], and
+//! [`App::on_exit`].
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use ::winit::{
+    application::ApplicationHandler,
+    dpi::LogicalSize,
+    event::{StartCause, WindowEvent},
+    event_loop::ActiveEventLoop,
+    window::{Window, WindowAttributes, WindowId},
+};
+
+use windows::{
+    Win32::{Foundation::E_FAIL, Graphics::Direct3D10::ID3D10Device},
+    core::{Error, Result},
+};
+
+use crate::{
+    DeviceOptions, RendererOptions, create_device,
+    run::{run_event_loop, schedule_repaint},
+    winit::State,
+};
+
+/// An application run by [`run_native`], like `eframe::App`.
+pub trait App {
+    /// Build the UI of a frame. Called whenever the window needs to be
+    /// redrawn.
+    fn update(&mut self, ctx: &egui::Context, frame: &mut Frame);
+
+    /// Save the state of the application, periodically (see
+    /// [`App::auto_save_interval`]) and before exiting. Only called if
+    /// [`NativeOptions::persistence`] is enabled.
+    fn save(&mut self, _storage: &mut dyn Storage) {}
+
+    /// Called once when the application exits, after the last call to
+    /// [`App::save`].
+    fn on_exit(&mut self) {}
+
+    /// How often [`App::save`] is called while the application runs.
+    fn auto_save_interval(&self) -> Duration {
+        Duration::from_secs(30)
+    }
+
+    /// The color the window is cleared to before drawing the UI, in gamma
+    /// space.
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        visuals.panel_fill.to_normalized_gamma_f32()
+    }
+}
+
+/// Persistent key-value storage for [`App::save`], like `eframe::Storage`.
+pub trait Storage {
+    /// Get the value stored under `key`.
+    fn get_string(&self, key: &str) -> Option<String>;
+    /// Store `value` under `key`.
+    fn set_string(&mut self, key: &str, value: String);
+    /// Write pending changes to disk.
+    fn flush(&mut self);
+}
+
+/// A [`Storage`] backed by a text file in `%APPDATA%\<app name>`.
+pub struct FileStorage {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+    dirty: bool,
+}
+
+impl FileStorage {
+    /// Open the storage of the application named `app_name`, reading any
+    /// values saved by a previous run. Returns `None` if `%APPDATA%` is not
+    /// set.
+    pub fn from_app_name(app_name: &str) -> Option<Self> {
+        let dir_name = app_name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == ' ' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        let path = PathBuf::from(env::var_os("APPDATA")?)
+            .join(dir_name)
+            .join("storage.txt");
+        let entries = fs::read_to_string(&path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| line.split_once('\t'))
+                    .map(|(key, value)| (unescape(key), unescape(value)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            path,
+            entries,
+            dirty: false,
+        })
+    }
+}
+
+impl Storage for FileStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.entries.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        if self.entries.get(key) != Some(&value) {
+            self.entries.insert(key.to_owned(), value);
+            self.dirty = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let contents = self
+            .entries
+            .iter()
+            .map(|(key, value)| format!("{}\t{}\n", escape(key), escape(value)))
+            .collect::<String>();
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&self.path, contents));
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(err) => {
+                log::error!("failed to write {}: {err}", self.path.display())
+            },
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some(c) => result.push(c),
+            None => {},
+        }
+    }
+    result
+}
+
+/// Access to the environment of an [`App`], like `eframe::Frame`.
+pub struct Frame {
+    device: ID3D10Device,
+    storage: Option<Box<dyn Storage>>,
+}
+
+impl Frame {
+    /// The Direct3D10 device the UI is rendered with.
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+
+    /// The storage of the application, if persistence is enabled.
+    pub fn storage(&self) -> Option<&dyn Storage> {
+        self.storage.as_deref()
+    }
+
+    /// The storage of the application, if persistence is enabled.
+    pub fn storage_mut(&mut self) -> Option<&mut (dyn Storage + 'static)> {
+        self.storage.as_deref_mut()
+    }
+}
+
+/// Data available while creating an [`App`] in [`run_native`], like
+/// `eframe::CreationContext`.
+pub struct CreationContext<'a> {
+    /// The `egui` context of the window, for example to install fonts or
+    /// set the style.
+    pub egui_ctx: &'a egui::Context,
+    /// The Direct3D10 device the UI is rendered with.
+    pub device: &'a ID3D10Device,
+    /// The storage of the application, if persistence is enabled, holding
+    /// the values saved by the previous run.
+    pub storage: Option<&'a dyn Storage>,
+}
+
+/// Options for [`run_native`], like `eframe::NativeOptions`.
+#[derive(Clone, Debug)]
+pub struct NativeOptions {
+    /// The initial size of the client area of the window, in points. Uses
+    /// the default of `winit` if `None`.
+    pub inner_size: Option<egui::Vec2>,
+    /// Options of the device, see [`create_device`].
+    pub device: DeviceOptions,
+    /// Options of the [`Renderer`](crate::Renderer).
+    pub renderer: RendererOptions,
+    /// Store the state saved by [`App::save`] in a [`FileStorage`] named
+    /// after the application. Enabled by default.
+    pub persistence: bool,
+}
+
+impl Default for NativeOptions {
+    fn default() -> Self {
+        Self {
+            inner_size: None,
+            device: DeviceOptions::default(),
+            renderer: RendererOptions::default(),
+            persistence: true,
+        }
+    }
+}
+
+/// Run an [`App`] in a window titled `app_name` until it is closed, like
+/// `eframe::run_native`.
+///
+/// `app_creator` is called once the window, device and `egui` context
+/// exist. As with [`run_simple`](crate::run_simple), this must be called on
+/// the main thread, and only once per process.
+///
+/// ```ignore
+/// egui_directx10::run_native(
+///     "My App",
+///     egui_directx10::NativeOptions::default(),
+///     |cc| Box::new(MyApp::new(cc)),
+/// )?;
+/// ```
+pub fn run_native(
+    app_name: &str,
+    options: NativeOptions,
+    app_creator: impl FnOnce(&CreationContext<'_>) -> Box<dyn App>,
+) -> Result<()> {
+    let mut window_attributes =
+        WindowAttributes::default().with_title(app_name);
+    if let Some(size) = options.inner_size {
+        window_attributes =
+            window_attributes.with_inner_size(LogicalSize::new(size.x, size.y));
+    }
+    let storage = options
+        .persistence
+        .then(|| FileStorage::from_app_name(app_name))
+        .flatten()
+        .map(|storage| Box::new(storage) as Box<dyn Storage>);
+    let mut runner = NativeRunner {
+        window_attributes,
+        device_options: options.device,
+        renderer_options: options.renderer,
+        app_creator: Some(app_creator),
+        storage,
+        running: None,
+        error: None,
+    };
+    run_event_loop(&mut runner)?;
+    runner.error.map_or(Ok(()), Err)
+}
+
+struct Running {
+    window: Window,
+    state: State,
+    app: Box<dyn App>,
+    frame: Frame,
+    last_save: Instant,
+}
+
+impl Running {
+    fn save(&mut self) {
+        if let Some(storage) = &mut self.frame.storage {
+            self.app.save(storage.as_mut());
+            storage.flush();
+        }
+        self.last_save = Instant::now();
+    }
+
+    fn redraw(&mut self, event_loop: &ActiveEventLoop) -> Result<()> {
+        let clear_color =
+            self.app.clear_color(&self.state.egui_ctx().style().visuals);
+        self.state.set_clear_color(clear_color);
+        let Self {
+            window,
+            state,
+            app,
+            frame,
+            ..
+        } = self;
+        state.run_ui(window, |ctx| app.update(ctx, frame))?;
+        if state.close_requested() {
+            event_loop.exit();
+            return Ok(());
+        }
+        if self.last_save.elapsed() >= self.app.auto_save_interval() {
+            self.save();
+        }
+        schedule_repaint(event_loop, &self.window, &self.state);
+        Ok(())
+    }
+}
+
+struct NativeRunner<F> {
+    window_attributes: WindowAttributes,
+    device_options: DeviceOptions,
+    renderer_options: RendererOptions,
+    app_creator: Option<F>,
+    storage: Option<Box<dyn Storage>>,
+    running: Option<Running>,
+    error: Option<Error>,
+}
+
+impl<F: FnOnce(&CreationContext<'_>) -> Box<dyn App>> NativeRunner<F> {
+    fn start(&mut self, event_loop: &ActiveEventLoop) -> Result<Running> {
+        let window = event_loop.create_window(self.window_attributes.clone());
+        let window = window.map_err(|err| {
+            log::error!("failed to create the window: {err}");
+            Error::from_hresult(E_FAIL)
+        })?;
+        let (device, _) = create_device(&self.device_options)?;
+        let state = State::with_options(
+            &window,
+            &device,
+            self.renderer_options.clone(),
+        )?;
+        let app_creator = self.app_creator.take().unwrap();
+        let app = app_creator(&CreationContext {
+            egui_ctx: state.egui_ctx(),
+            device: &device,
+            storage: self.storage.as_deref(),
+        });
+        Ok(Running {
+            window,
+            state,
+            app,
+            frame: Frame {
+                device,
+                storage: self.storage.take(),
+            },
+            last_save: Instant::now(),
+        })
+    }
+
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: Error) {
+        self.error = Some(error);
+        event_loop.exit();
+    }
+}
+
+impl<F: FnOnce(&CreationContext<'_>) -> Box<dyn App>> ApplicationHandler
+    for NativeRunner<F>
+{
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.running.is_some() || self.app_creator.is_none() {
+            return;
+        }
+        match self.start(event_loop) {
+            Ok(running) => self.running = Some(running),
+            Err(err) => self.fail(event_loop, err),
+        }
+    }
+
+    fn new_events(&mut self, _: &ActiveEventLoop, cause: StartCause) {
+        if let StartCause::ResumeTimeReached { .. } = cause
+            && let Some(running) = &self.running
+        {
+            running.window.request_redraw();
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(running) = &mut self.running else {
+            return;
+        };
+        let result = (|| {
+            let response =
+                running.state.on_window_event(&running.window, &event)?;
+            if response.repaint {
+                running.window.request_redraw();
+            }
+            match event {
+                WindowEvent::CloseRequested => event_loop.exit(),
+                WindowEvent::RedrawRequested => running.redraw(event_loop)?,
+                _ => {},
+            }
+            Ok(())
+        })();
+        if let Err(err) = result {
+            self.fail(event_loop, err);
+        }
+    }
+
+    fn exiting(&mut self, _: &ActiveEventLoop) {
+        if let Some(mut running) = self.running.take() {
+            running.save();
+            running.app.on_exit();
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use windows::{
+    Win32::Graphics::{
+        Direct3D10::*,
+        Dxgi::{Common::*, IDXGISwapChain},
+    },
+    core::Result,
+};
+
+use crate::{Renderer, RendererOutput};
+
+/// The back buffer of a swap chain and a render target view of it, cached
+/// by [`Renderer::render_to_swapchain`].
+pub(crate) struct BackBuffer {
+    texture: ID3D10Texture2D,
+    render_target: ID3D10RenderTargetView,
+}
+
+impl BackBuffer {
+    fn new(device: &ID3D10Device, texture: ID3D10Texture2D) -> Result<Self> {
+        let mut desc = D3D10_TEXTURE2D_DESC::default();
+        unsafe { texture.GetDesc(&mut desc) };
+        // Typeless back buffers need an explicitly typed view.
+        let format = match desc.Format {
+            DXGI_FORMAT_R8G8B8A8_TYPELESS => DXGI_FORMAT_R8G8B8A8_UNORM,
+            DXGI_FORMAT_B8G8R8A8_TYPELESS => DXGI_FORMAT_B8G8R8A8_UNORM,
+            DXGI_FORMAT_R10G10B10A2_TYPELESS => DXGI_FORMAT_R10G10B10A2_UNORM,
+            format => format,
+        };
+        let view_desc = D3D10_RENDER_TARGET_VIEW_DESC {
+            Format: format,
+            ViewDimension: D3D10_RTV_DIMENSION_TEXTURE2D,
+            ..Default::default()
+        };
+        let mut render_target = None;
+        unsafe {
+            device.CreateRenderTargetView(
+                &texture,
+                Some(&view_desc),
+                Some(&mut render_target),
+            )
+        }?;
+        Ok(Self {
+            texture,
+            render_target: render_target.unwrap(),
+        })
+    }
+}
+
+impl Renderer {
+    /// Render `egui_output` onto the current back buffer of `swap_chain`,
+    /// like [`Renderer::render`], which saves fetching the back buffer and
+    /// creating a render target view of it every frame, as `Present` hooks
+    /// otherwise do.
+    ///
+    /// The view is cached and recreated when the swap chain hands out
+    /// another back buffer, such as after a resize. It keeps the back buffer
+    /// alive, so call [`Renderer::release_back_buffer`] before
+    /// `IDXGISwapChain::ResizeBuffers`, which fails while references to the
+    /// buffers exist. The swap chain must have been created on the device
+    /// of the renderer.
+    pub fn render_to_swapchain(
+        &mut self,
+        swap_chain: &IDXGISwapChain,
+        egui_ctx: &egui::Context,
+        egui_output: RendererOutput,
+    ) -> Result<()> {
+        let texture = unsafe { swap_chain.GetBuffer::<ID3D10Texture2D>(0) }?;
+        if self
+            .back_buffer
+            .as_ref()
+            .is_none_or(|back_buffer| back_buffer.texture != texture)
+        {
+            self.back_buffer = None;
+            self.back_buffer = Some(BackBuffer::new(&self.device, texture)?);
+        }
+        let render_target =
+            self.back_buffer.as_ref().unwrap().render_target.clone();
+        let device = self.device.clone();
+        self.render(&device, &render_target, egui_ctx, egui_output)
+    }
+
+    /// Release the back buffer cached by [`Renderer::render_to_swapchain`].
+    /// Call this before `IDXGISwapChain::ResizeBuffers`; the view is
+    /// recreated on the next call.
+    pub fn release_back_buffer(&mut self) {
+        self.back_buffer = None;
+    }
+}
@@ -0,0 +1,234 @@
+use std::mem;
+
+use egui::TextureId;
+
+use windows::{
+    Win32::Graphics::{Direct3D::*, Direct3D10::*},
+    core::Result,
+};
+
+use crate::{
+    BackdropBlur, ColorSpace, Renderer, post_process::RenderTexture, shader,
+    snapshot::Snapshot, texture::TexturePool,
+};
+
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct BlurConstants {
+    step: [f32; 2],
+    uv_offset: [f32; 2],
+    uv_scale: [f32; 2],
+    _padding: [f32; 2],
+}
+
+/// The state of [`RendererOptions::backdrop_blur`](crate::RendererOptions::backdrop_blur).
+pub(crate) struct Backdrop {
+    options: BackdropBlur,
+    texture_id: TextureId,
+    pixel_shader: ID3D10PixelShader,
+    constant_buffer: ID3D10Buffer,
+    snapshot: Option<Snapshot>,
+    /// The horizontally blurred and the fully blurred backdrop. The latter
+    /// is registered as `texture_id`.
+    targets: (RenderTexture, RenderTexture),
+}
+
+impl Backdrop {
+    pub fn new(
+        device: &ID3D10Device,
+        texture_pool: &mut TexturePool,
+        options: BackdropBlur,
+    ) -> Result<Self> {
+        let pixel_shader = shader::create_blur_pixel_shader(device)?;
+        let mut constant_buffer = None;
+        unsafe {
+            device.CreateBuffer(
+                &D3D10_BUFFER_DESC {
+                    ByteWidth: mem::size_of::<BlurConstants>() as _,
+                    Usage: D3D10_USAGE_DEFAULT,
+                    BindFlags: D3D10_BIND_CONSTANT_BUFFER.0 as _,
+                    ..D3D10_BUFFER_DESC::default()
+                },
+                None,
+                Some(&mut constant_buffer),
+            )
+        }?;
+        // The targets are resized to the frame on first use.
+        let targets = (
+            RenderTexture::new(device, (1, 1))?,
+            RenderTexture::new(device, (1, 1))?,
+        );
+        let texture_id = texture_pool
+            .register_user_texture(targets.1.srv.clone(), ColorSpace::Srgb);
+        Ok(Self {
+            options,
+            texture_id,
+            pixel_shader,
+            constant_buffer: constant_buffer.unwrap(),
+            snapshot: None,
+            targets,
+        })
+    }
+}
+
+impl Renderer {
+    /// The texture holding the blurred backdrop of the last frame, or `None`
+    /// if [`RendererOptions::backdrop_blur`](crate::RendererOptions::backdrop_blur)
+    /// is disabled.
+    ///
+    /// The texture covers the region of the render target the frame is
+    /// drawn to, so a panel at `rect` samples the part of it behind itself
+    /// with UVs of `rect` divided by the size of
+    /// [`egui::Context::screen_rect`]. It is captured before the UI is drawn,
+    /// so it never contains the UI itself.
+    ///
+    /// ```ignore
+    /// let screen = ctx.screen_rect();
+    /// let uv = egui::Rect::from_min_max(
+    ///     (rect.min.to_vec2() / screen.size()).to_pos2(),
+    ///     (rect.max.to_vec2() / screen.size()).to_pos2(),
+    /// );
+    /// painter.image(texture_id, rect, uv, egui::Color32::WHITE);
+    /// ```
+    pub fn backdrop_texture_id(&self) -> Option<TextureId> {
+        self.backdrop.as_ref().map(|backdrop| backdrop.texture_id)
+    }
+
+    /// Release the copy and the blurred targets of the backdrop until the
+    /// next frame. See [`Renderer::trim`].
+    pub(crate) fn trim_backdrop(&mut self) -> Result<()> {
+        let Some(backdrop) = &mut self.backdrop else {
+            return Ok(());
+        };
+        backdrop.snapshot = None;
+        backdrop.targets = (
+            RenderTexture::new(&self.device, (1, 1))?,
+            RenderTexture::new(&self.device, (1, 1))?,
+        );
+        self.texture_pool.textures_mut().replace_user_texture(
+            backdrop.texture_id,
+            backdrop.targets.1.srv.clone(),
+        );
+        Ok(())
+    }
+
+    /// Capture the region of `render_target` and blur it into the backdrop
+    /// texture. Overrides the pipeline state; call before `setup`.
+    pub(crate) fn capture_backdrop(
+        &mut self,
+        ctx: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+    ) -> Result<()> {
+        let Some(backdrop) = &mut self.backdrop else {
+            return Ok(());
+        };
+        let snapshot =
+            Snapshot::take(&mut backdrop.snapshot, ctx, render_target)?;
+        let (snapshot, source_size) = (snapshot.srv.clone(), snapshot.size());
+        let downscale = backdrop.options.downscale.max(1);
+        let blurred_size = (
+            frame_size.0.div_ceil(downscale).max(1),
+            frame_size.1.div_ceil(downscale).max(1),
+        );
+        if backdrop.targets.0.size != blurred_size {
+            backdrop.targets = (
+                RenderTexture::new(&self.device, blurred_size)?,
+                RenderTexture::new(&self.device, blurred_size)?,
+            );
+            self.texture_pool.textures_mut().replace_user_texture(
+                backdrop.texture_id,
+                backdrop.targets.1.srv.clone(),
+            );
+        }
+
+        let backdrop = self.backdrop.as_ref().unwrap();
+        let spread = backdrop.options.spread;
+        let source_size = (source_size.0 as f32, source_size.1 as f32);
+        // The horizontal pass also downscales the frame region of the
+        // snapshot.
+        self.blur_pass(
+            ctx,
+            backdrop,
+            &backdrop.targets.0,
+            &snapshot,
+            BlurConstants {
+                step: [spread * downscale as f32 / source_size.0, 0.],
+                uv_offset: [
+                    frame_origin.0 as f32 / source_size.0,
+                    frame_origin.1 as f32 / source_size.1,
+                ],
+                uv_scale: [
+                    frame_size.0 as f32 / source_size.0,
+                    frame_size.1 as f32 / source_size.1,
+                ],
+                _padding: [0.; 2],
+            },
+        )?;
+        self.blur_pass(
+            ctx,
+            backdrop,
+            &backdrop.targets.1,
+            &backdrop.targets.0.srv,
+            BlurConstants {
+                step: [0., spread / blurred_size.1 as f32],
+                uv_offset: [0.; 2],
+                uv_scale: [1.; 2],
+                _padding: [0.; 2],
+            },
+        )?;
+        unsafe { ctx.PSSetShaderResources(0, Some(&[None])) };
+        Ok(())
+    }
+
+    fn blur_pass(
+        &self,
+        ctx: &ID3D10Device,
+        backdrop: &Backdrop,
+        target: &RenderTexture,
+        source: &ID3D10ShaderResourceView,
+        constants: BlurConstants,
+    ) -> Result<()> {
+        unsafe {
+            ctx.UpdateSubresource(
+                &backdrop.constant_buffer,
+                0,
+                None,
+                bytemuck::bytes_of(&constants).as_ptr() as _,
+                0,
+                0,
+            );
+            ctx.IASetPrimitiveTopology(D3D10_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
+            ctx.IASetInputLayout(&self.input_layout);
+            ctx.VSSetShader(&self.vertex_shader);
+            ctx.PSSetShader(&backdrop.pixel_shader);
+            ctx.PSSetConstantBuffers(
+                3,
+                Some(&[Some(backdrop.constant_buffer.clone())]),
+            );
+            ctx.PSSetSamplers(
+                0,
+                Some(&[Some(self.sampler_state_clamp.clone())]),
+            );
+            ctx.RSSetState(&self.rasterizer_state);
+            ctx.RSSetViewports(Some(&[D3D10_VIEWPORT {
+                TopLeftX: 0,
+                TopLeftY: 0,
+                Width: target.size.0,
+                Height: target.size.1,
+                MinDepth: 0.,
+                MaxDepth: 1.,
+            }]));
+            // Unbind the source of the previous pass before rendering to it.
+            ctx.PSSetShaderResources(0, Some(&[None]));
+            ctx.OMSetRenderTargets(
+                Some(&[Some(target.render_target.clone())]),
+                None,
+            );
+            ctx.OMSetBlendState(&self.blend_state_opaque, &[0.; 4], u32::MAX);
+            ctx.PSSetShaderResources(0, Some(&[Some(source.clone())]));
+        }
+        self.draw_quad(ctx, (0, 0), target.size, [1.; 4])
+    }
+}
@@ -0,0 +1,509 @@
+use std::{ffi::c_void, mem, ptr};
+
+use egui::Color32;
+
+use windows::{
+    Win32::{
+        Foundation::{E_INVALIDARG, RECT},
+        Graphics::{Direct3D10::*, Dxgi::Common::*},
+    },
+    core::{Error, Result},
+};
+
+use crate::{TextureAllocator, VertexData};
+
+/// The part of a vertex and an index buffer a mesh is drawn from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DrawRange {
+    pub index_count: u32,
+    /// The first index of the mesh in the index buffer.
+    pub start_index: u32,
+    /// The first vertex of the mesh in the vertex buffer, which is added to
+    /// its indices.
+    pub base_vertex: i32,
+}
+
+/// The device calls the renderer makes to upload meshes and textures and to
+/// draw them.
+///
+/// The renderer only ever uses [`ID3D10Device`], which implements this by
+/// delegating to Direct3D10. The trait exists so that the state machines
+/// built on top of it, such as the `TexturePool`, and the drawing of meshes
+/// can be driven without a GPU: by `recording::RecordingBackend` in unit
+/// tests, and by the backend of the `fuzzing` feature.
+pub(crate) trait Backend {
+    type Buffer;
+    type Texture;
+    type ShaderResourceView: Clone;
+
+    fn create_vertex_buffer(&self, data: &[VertexData])
+    -> Result<Self::Buffer>;
+
+    fn create_index_buffer(&self, data: &[u32]) -> Result<Self::Buffer>;
+
+    /// Create a `D3D10_USAGE_DYNAMIC` buffer of `size` bytes, bound as
+    /// `bind_flag`, to be written with [`Backend::write_buffer`].
+    fn create_dynamic_buffer(
+        &self,
+        size: usize,
+        bind_flag: D3D10_BIND_FLAG,
+    ) -> Result<Self::Buffer>;
+
+    /// Map a buffer created by [`Backend::create_dynamic_buffer`] with
+    /// `map_type`, and write `slices` one after the other from byte `offset`
+    /// on. Returns `E_INVALIDARG` if they do not fit in the buffer.
+    fn write_buffer<'a>(
+        &self,
+        buffer: &Self::Buffer,
+        map_type: D3D10_MAP,
+        offset: usize,
+        slices: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<()>;
+
+    /// Create a dynamic RGBA8 texture holding `pixels`, with `width` pixels
+    /// per row, through `allocator`, and a view of it.
+    fn create_texture(
+        &self,
+        allocator: &mut dyn TextureAllocator,
+        width: usize,
+        height: usize,
+        pixels: &[Color32],
+    ) -> Result<(Self::Texture, Self::ShaderResourceView)>;
+
+    /// Return a texture created by [`Backend::create_texture`] to
+    /// `allocator`.
+    fn free_texture(
+        &self,
+        allocator: &mut dyn TextureAllocator,
+        texture: Self::Texture,
+    );
+
+    /// Overwrite `region` of `texture` with tightly packed RGBA8 `data`.
+    fn update_texture(
+        &self,
+        texture: &Self::Texture,
+        region: D3D10_BOX,
+        data: &[u8],
+    );
+
+    /// Draw the indices of `range` as triangles, clipped to `scissor_rect`.
+    /// If `srv` is `None`, the previously bound texture is kept.
+    fn draw_indexed(
+        &self,
+        vertex_buffer: &Self::Buffer,
+        index_buffer: &Self::Buffer,
+        range: DrawRange,
+        scissor_rect: RECT,
+        srv: Option<&Self::ShaderResourceView>,
+    );
+}
+
+impl Backend for ID3D10Device {
+    type Buffer = ID3D10Buffer;
+    type Texture = ID3D10Texture2D;
+    type ShaderResourceView = ID3D10ShaderResourceView;
+
+    fn create_vertex_buffer(
+        &self,
+        data: &[VertexData],
+    ) -> Result<ID3D10Buffer> {
+        create_immutable_buffer(self, data, D3D10_BIND_VERTEX_BUFFER)
+    }
+
+    fn create_index_buffer(&self, data: &[u32]) -> Result<ID3D10Buffer> {
+        create_immutable_buffer(self, data, D3D10_BIND_INDEX_BUFFER)
+    }
+
+    fn create_dynamic_buffer(
+        &self,
+        size: usize,
+        bind_flag: D3D10_BIND_FLAG,
+    ) -> Result<ID3D10Buffer> {
+        let mut buffer = None;
+        unsafe {
+            self.CreateBuffer(
+                &D3D10_BUFFER_DESC {
+                    ByteWidth: size as _,
+                    Usage: D3D10_USAGE_DYNAMIC,
+                    BindFlags: bind_flag.0 as _,
+                    CPUAccessFlags: D3D10_CPU_ACCESS_WRITE.0 as _,
+                    ..D3D10_BUFFER_DESC::default()
+                },
+                None,
+                Some(&mut buffer),
+            )
+        }?;
+        Ok(buffer.unwrap())
+    }
+
+    fn write_buffer<'a>(
+        &self,
+        buffer: &ID3D10Buffer,
+        map_type: D3D10_MAP,
+        offset: usize,
+        slices: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<()> {
+        let mut desc = D3D10_BUFFER_DESC::default();
+        unsafe { buffer.GetDesc(&mut desc) };
+        let mut data: *mut c_void = ptr::null_mut();
+        unsafe { buffer.Map(map_type, 0, &mut data) }?;
+        let mut end = offset;
+        for slice in slices {
+            if end + slice.len() > desc.ByteWidth as usize {
+                unsafe { buffer.Unmap() };
+                log::error!(
+                    "cannot write {} bytes at {end} to a buffer of {} bytes.",
+                    slice.len(),
+                    desc.ByteWidth,
+                );
+                return Err(Error::from_hresult(E_INVALIDARG));
+            }
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    slice.as_ptr(),
+                    data.cast::<u8>().add(end),
+                    slice.len(),
+                );
+            }
+            end += slice.len();
+        }
+        unsafe { buffer.Unmap() };
+        Ok(())
+    }
+
+    fn create_texture(
+        &self,
+        allocator: &mut dyn TextureAllocator,
+        width: usize,
+        height: usize,
+        pixels: &[Color32],
+    ) -> Result<(ID3D10Texture2D, ID3D10ShaderResourceView)> {
+        let desc = D3D10_TEXTURE2D_DESC {
+            Width: width as _,
+            Height: height as _,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D10_USAGE_DYNAMIC,
+            BindFlags: D3D10_BIND_SHADER_RESOURCE.0 as _,
+            CPUAccessFlags: D3D10_CPU_ACCESS_WRITE.0 as _,
+            ..Default::default()
+        };
+
+        let subresource_data = D3D10_SUBRESOURCE_DATA {
+            pSysMem: bytemuck::cast_slice::<_, u8>(pixels).as_ptr() as _,
+            SysMemPitch: (width * mem::size_of::<Color32>()) as u32,
+            SysMemSlicePitch: 0,
+        };
+
+        let tex = allocator.allocate(self, &desc, &subresource_data)?;
+
+        let mut srv = None;
+        unsafe { self.CreateShaderResourceView(&tex, None, Some(&mut srv)) }?;
+        Ok((tex, srv.unwrap()))
+    }
+
+    fn free_texture(
+        &self,
+        allocator: &mut dyn TextureAllocator,
+        texture: ID3D10Texture2D,
+    ) {
+        allocator.free(texture);
+    }
+
+    fn update_texture(
+        &self,
+        texture: &ID3D10Texture2D,
+        region: D3D10_BOX,
+        data: &[u8],
+    ) {
+        let row_pitch = (region.right - region.left) * 4;
+        unsafe {
+            self.UpdateSubresource(
+                texture,
+                0,
+                Some(&region),
+                data.as_ptr() as _,
+                row_pitch,
+                0,
+            );
+        }
+    }
+
+    fn draw_indexed(
+        &self,
+        vertex_buffer: &ID3D10Buffer,
+        index_buffer: &ID3D10Buffer,
+        range: DrawRange,
+        scissor_rect: RECT,
+        srv: Option<&ID3D10ShaderResourceView>,
+    ) {
+        unsafe {
+            self.IASetVertexBuffers(
+                0,
+                1,
+                Some(&Some(vertex_buffer.clone())),
+                Some(&(mem::size_of::<VertexData>() as _)),
+                Some(&0),
+            );
+            self.IASetIndexBuffer(index_buffer, DXGI_FORMAT_R32_UINT, 0);
+            self.RSSetScissorRects(Some(&[scissor_rect]));
+            if let Some(srv) = srv {
+                self.PSSetShaderResources(0, Some(&[Some(srv.clone())]));
+            }
+            self.DrawIndexed(
+                range.index_count,
+                range.start_index,
+                range.base_vertex,
+            );
+        }
+    }
+}
+
+fn create_immutable_buffer<T: bytemuck::Pod>(
+    device: &ID3D10Device,
+    data: &[T],
+    bind_flag: D3D10_BIND_FLAG,
+) -> Result<ID3D10Buffer> {
+    let mut buffer = None;
+    unsafe {
+        device.CreateBuffer(
+            &D3D10_BUFFER_DESC {
+                ByteWidth: mem::size_of_val(data) as _,
+                Usage: D3D10_USAGE_IMMUTABLE,
+                BindFlags: bind_flag.0 as _,
+                ..D3D10_BUFFER_DESC::default()
+            },
+            Some(&D3D10_SUBRESOURCE_DATA {
+                pSysMem: bytemuck::cast_slice::<_, u8>(data).as_ptr() as _,
+                ..D3D10_SUBRESOURCE_DATA::default()
+            }),
+            Some(&mut buffer),
+        )
+    }?;
+    Ok(buffer.unwrap())
+}
+
+/// A backend for unit tests.
+#[cfg(test)]
+pub(crate) mod recording {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use super::*;
+
+    /// A call made to a [`RecordingBackend`].
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub(crate) enum Call {
+        CreateVertexBuffer(usize),
+        CreateIndexBuffer(usize),
+        CreateDynamicBuffer {
+            size: usize,
+            bind_flag: D3D10_BIND_FLAG,
+        },
+        WriteBuffer {
+            map_type: D3D10_MAP,
+            offset: usize,
+            size: usize,
+        },
+        CreateTexture {
+            texture: usize,
+            size: [usize; 2],
+        },
+        FreeTexture(usize),
+        UpdateTexture {
+            texture: usize,
+            region: D3D10_BOX,
+        },
+        DrawIndexed {
+            range: DrawRange,
+            scissor_rect: RECT,
+            srv: Option<usize>,
+        },
+    }
+
+    /// A [`Backend`] that records the calls made to it, for unit tests of
+    /// the code driving it. Buffers hold their length, in elements for
+    /// immutable buffers and in bytes for dynamic ones, and textures and
+    /// their views a number counting up from 0. Clones share the recorded
+    /// calls.
+    #[derive(Clone, Default)]
+    pub(crate) struct RecordingBackend {
+        calls: Rc<RefCell<Vec<Call>>>,
+        texture_count: Rc<Cell<usize>>,
+    }
+
+    impl RecordingBackend {
+        /// The calls made since the last call.
+        pub fn take_calls(&self) -> Vec<Call> {
+            mem::take(&mut self.calls.borrow_mut())
+        }
+
+        fn record(&self, call: Call) {
+            self.calls.borrow_mut().push(call);
+        }
+    }
+
+    impl Backend for RecordingBackend {
+        type Buffer = usize;
+        type Texture = usize;
+        type ShaderResourceView = usize;
+
+        fn create_vertex_buffer(&self, data: &[VertexData]) -> Result<usize> {
+            self.record(Call::CreateVertexBuffer(data.len()));
+            Ok(data.len())
+        }
+
+        fn create_index_buffer(&self, data: &[u32]) -> Result<usize> {
+            self.record(Call::CreateIndexBuffer(data.len()));
+            Ok(data.len())
+        }
+
+        fn create_dynamic_buffer(
+            &self,
+            size: usize,
+            bind_flag: D3D10_BIND_FLAG,
+        ) -> Result<usize> {
+            self.record(Call::CreateDynamicBuffer { size, bind_flag });
+            Ok(size)
+        }
+
+        fn write_buffer<'a>(
+            &self,
+            &buffer: &usize,
+            map_type: D3D10_MAP,
+            offset: usize,
+            slices: impl IntoIterator<Item = &'a [u8]>,
+        ) -> Result<()> {
+            let size = slices.into_iter().map(<[u8]>::len).sum();
+            assert!(offset + size <= buffer, "buffer write out of bounds");
+            self.record(Call::WriteBuffer {
+                map_type,
+                offset,
+                size,
+            });
+            Ok(())
+        }
+
+        fn create_texture(
+            &self,
+            _allocator: &mut dyn TextureAllocator,
+            width: usize,
+            height: usize,
+            pixels: &[Color32],
+        ) -> Result<(usize, usize)> {
+            assert_eq!(pixels.len(), width * height, "texture data size");
+            let texture = self.texture_count.get();
+            self.texture_count.set(texture + 1);
+            self.record(Call::CreateTexture {
+                texture,
+                size: [width, height],
+            });
+            Ok((texture, texture))
+        }
+
+        fn free_texture(
+            &self,
+            _allocator: &mut dyn TextureAllocator,
+            texture: usize,
+        ) {
+            self.record(Call::FreeTexture(texture));
+        }
+
+        fn update_texture(
+            &self,
+            &texture: &usize,
+            region: D3D10_BOX,
+            data: &[u8],
+        ) {
+            let pixels =
+                (region.right - region.left) * (region.bottom - region.top);
+            assert_eq!(
+                data.len(),
+                pixels as usize * 4,
+                "texture update data size"
+            );
+            self.record(Call::UpdateTexture { texture, region });
+        }
+
+        fn draw_indexed(
+            &self,
+            _vertex_buffer: &usize,
+            _index_buffer: &usize,
+            range: DrawRange,
+            scissor_rect: RECT,
+            srv: Option<&usize>,
+        ) {
+            self.record(Call::DrawIndexed {
+                range,
+                scissor_rect,
+                srv: srv.copied(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{recording::*, *};
+    use crate::{
+        ColorSpace, DefaultTextureAllocator, MeshData, Renderer,
+        texture::TexturePool,
+    };
+
+    #[test]
+    fn meshes_are_drawn_with_their_texture_and_clip_rect() {
+        let backend = RecordingBackend::default();
+        let mut pool = TexturePool::new(
+            backend.clone(),
+            Box::new(DefaultTextureAllocator),
+        );
+        let texture_id = pool.register_user_texture(5, ColorSpace::Srgb);
+        let mut mesh = MeshData {
+            vtx: Vec::new(),
+            idx: Vec::new(),
+            tex: texture_id,
+            clip_rect: egui::Rect::from_min_max(
+                egui::pos2(1., 2.),
+                egui::pos2(30., 40.),
+            ),
+            callback: None,
+        };
+        let range = DrawRange {
+            index_count: 6,
+            start_index: 12,
+            base_vertex: 4,
+        };
+        Renderer::draw_mesh(&backend, &pool, (&0, &0), range, &mesh);
+        // Meshes with missing textures keep the previous one.
+        mesh.tex = egui::TextureId::Managed(9);
+        Renderer::draw_mesh(&backend, &pool, (&0, &0), range, &mesh);
+        let scissor_rect = RECT {
+            left: 1,
+            top: 2,
+            right: 30,
+            bottom: 40,
+        };
+        assert_eq!(
+            backend.take_calls(),
+            [
+                Call::DrawIndexed {
+                    range,
+                    scissor_rect,
+                    srv: Some(5),
+                },
+                Call::DrawIndexed {
+                    range,
+                    scissor_rect,
+                    srv: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Internal steps of [`Renderer::render`](crate::Renderer::render), exposed
+//! for the benchmarks in `benches/`. Not part of the stable API.
+
+use egui::{ClippedPrimitive, epaint::Primitive};
+
+use windows::{
+    Win32::Graphics::{Direct3D10::*, Dxgi::Common::DXGI_FORMAT_UNKNOWN},
+    core::Result,
+};
+
+use crate::{FrameInfo, MeshData, backend::Backend, geometry::GeometryBuffers};
+
+/// Meshes converted to the vertex format of the renderer, ready to upload.
+pub struct ConvertedMeshes(Vec<MeshData>);
+
+impl ConvertedMeshes {
+    /// The number of vertices of all meshes.
+    pub fn vertex_count(&self) -> usize {
+        self.0.iter().map(|mesh| mesh.vtx.len()).sum()
+    }
+}
+
+/// Convert the meshes of `primitives`, tessellated at `pixels_per_point`,
+/// for a frame of `frame_size` pixels. Paint callbacks are skipped.
+pub fn convert_meshes(
+    primitives: Vec<ClippedPrimitive>,
+    frame_size: (u32, u32),
+    pixels_per_point: f32,
+) -> ConvertedMeshes {
+    let frame = FrameInfo {
+        origin: (0, 0),
+        size: frame_size,
+        format: DXGI_FORMAT_UNKNOWN,
+        pixels_per_point,
+        zoom_factor: 1.,
+        snap_to_pixels: false,
+    };
+    ConvertedMeshes(
+        primitives
+            .into_iter()
+            .filter_map(|primitive| match primitive.primitive {
+                Primitive::Mesh(mesh) => {
+                    frame.convert_mesh(mesh, primitive.clip_rect)
+                },
+                Primitive::Callback(_) => None,
+            })
+            .collect(),
+    )
+}
+
+/// Create an immutable vertex and index buffer for each of `meshes` on
+/// `device`, for comparison with [`upload_meshes_dynamic`].
+pub fn upload_meshes(
+    device: &ID3D10Device,
+    meshes: &ConvertedMeshes,
+) -> Result<Vec<(ID3D10Buffer, ID3D10Buffer)>> {
+    meshes
+        .0
+        .iter()
+        .map(|mesh| {
+            Ok((
+                device.create_vertex_buffer(&mesh.vtx)?,
+                device.create_index_buffer(&mesh.idx)?,
+            ))
+        })
+        .collect()
+}
+
+/// The vertex and index buffers the renderer keeps across frames.
+#[derive(Default)]
+pub struct DynamicBuffers(GeometryBuffers);
+
+/// Write `meshes` to `buffers`, as every frame does before drawing them.
+pub fn upload_meshes_dynamic(
+    device: &ID3D10Device,
+    buffers: &mut DynamicBuffers,
+    meshes: &ConvertedMeshes,
+) -> Result<()> {
+    buffers.0.upload(device, &meshes.0)?;
+    Ok(())
+}
@@ -0,0 +1,341 @@
+use windows::{
+    Win32::{Foundation::E_INVALIDARG, Graphics::Direct3D10::*},
+    core::{Error, Result},
+};
+
+use crate::{
+    AlphaOutput, BackdropBlur, BlendMode, ColorAdjustment, ColorSpace,
+    ColorTransform, DefaultTextureAllocator, DepthRange, Dithering,
+    DynamicRenderScale, FrameCallback, FrameLimits, FrameWatchdog, RenderScale,
+    Renderer, RendererOptions, ScaleFilter, SharedResources, SharedTexturePool,
+    TextureAllocator, TimeSlicing,
+};
+
+/// Chainable construction of a [`Renderer`], created with
+/// [`Renderer::builder`].
+///
+/// Every method sets the [`RendererOptions`] field of the same name, so see
+/// there for what they do. Options not set keep their default, so
+/// `Renderer::builder(device).build()` is the same as [`Renderer::new`].
+///
+/// ```no_run
+/// # fn f(device: &windows::Win32::Graphics::Direct3D10::ID3D10Device) -> windows::core::Result<()> {
+/// use egui_directx10::{ColorSpace, Dithering, Renderer};
+///
+/// let renderer = Renderer::builder(device)
+///     .target_color_space(ColorSpace::Linear)
+///     .dithering(Some(Dithering::default()))
+///     .validate_pipeline(cfg!(debug_assertions))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RendererBuilder {
+    device: ID3D10Device,
+    options: RendererOptions,
+    texture_allocator: Box<dyn TextureAllocator>,
+    shared_resources: Option<SharedResources>,
+    texture_pool: Option<SharedTexturePool>,
+}
+
+impl Renderer {
+    /// Start building a [`Renderer`] on `device`, see [`RendererBuilder`].
+    pub fn builder(device: &ID3D10Device) -> RendererBuilder {
+        RendererBuilder {
+            device: device.clone(),
+            options: RendererOptions::default(),
+            texture_allocator: Box::new(DefaultTextureAllocator),
+            shared_resources: None,
+            texture_pool: None,
+        }
+    }
+}
+
+impl RendererBuilder {
+    /// Create the [`Renderer`]. Errors are handled as in
+    /// [`Renderer::with_options`]; additionally, `E_INVALIDARG` is returned
+    /// if the [`SharedResources`] or the [`SharedTexturePool`] were created
+    /// on another device.
+    pub fn build(self) -> Result<Renderer> {
+        let texture_pool = match self.texture_pool {
+            Some(pool) if *pool.device() != self.device => {
+                log::error!(
+                    "the texture pool of a renderer belongs to another device"
+                );
+                return Err(Error::from_hresult(E_INVALIDARG));
+            },
+            Some(pool) => pool,
+            None => SharedTexturePool::with_texture_allocator(
+                &self.device,
+                self.texture_allocator,
+            ),
+        };
+        let shared = match self.shared_resources {
+            Some(shared) if shared.device != self.device => {
+                log::error!(
+                    "the shared resources of a renderer belong to another device"
+                );
+                return Err(Error::from_hresult(E_INVALIDARG));
+            },
+            Some(shared) => shared,
+            None => SharedResources::new(&self.device)?,
+        };
+        Renderer::from_shared_resources(&shared, self.options, texture_pool)
+    }
+
+    /// Replace all options at once, for options loaded from a file or
+    /// shared between renderers. Methods called afterwards override them.
+    pub fn options(mut self, options: RendererOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Create and destroy the textures managed by `egui` through
+    /// `texture_allocator`, as [`Renderer::with_texture_allocator`] does.
+    pub fn texture_allocator(
+        mut self,
+        texture_allocator: Box<dyn TextureAllocator>,
+    ) -> Self {
+        self.texture_allocator = texture_allocator;
+        self
+    }
+
+    /// Use the resources of `shared` instead of creating new ones, as
+    /// [`Renderer::with_shared_resources`] does.
+    pub fn shared_resources(mut self, shared: &SharedResources) -> Self {
+        self.shared_resources = Some(shared.clone());
+        self
+    }
+
+    /// Draw the textures of `texture_pool`, such as the one of another
+    /// renderer returned by [`Renderer::texture_pool`], instead of creating
+    /// a new pool. The allocator of the pool is used, ignoring
+    /// [`RendererBuilder::texture_allocator`].
+    pub fn texture_pool(mut self, texture_pool: &SharedTexturePool) -> Self {
+        self.texture_pool = Some(texture_pool.clone());
+        self
+    }
+
+    /// See [`RendererOptions::dithering`].
+    pub fn dithering(mut self, dithering: Option<Dithering>) -> Self {
+        self.options.dithering = dithering;
+        self
+    }
+
+    /// See [`RendererOptions::color_transform`].
+    pub fn color_transform(
+        mut self,
+        color_transform: Option<ColorTransform>,
+    ) -> Self {
+        self.options.color_transform = color_transform;
+        self
+    }
+
+    /// See [`RendererOptions::color_adjustment`].
+    pub fn color_adjustment(
+        mut self,
+        color_adjustment: Option<ColorAdjustment>,
+    ) -> Self {
+        self.options.color_adjustment = color_adjustment;
+        self
+    }
+
+    /// See [`RendererOptions::gamma_lut`].
+    pub fn gamma_lut(
+        mut self,
+        gamma_lut: Option<ID3D10ShaderResourceView>,
+    ) -> Self {
+        self.options.gamma_lut = gamma_lut;
+        self
+    }
+
+    /// See [`RendererOptions::text_gamma`].
+    pub fn text_gamma(mut self, text_gamma: Option<f32>) -> Self {
+        self.options.text_gamma = text_gamma;
+        self
+    }
+
+    /// See [`RendererOptions::target_color_space`].
+    pub fn target_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.options.target_color_space = color_space;
+        self
+    }
+
+    /// See [`RendererOptions::srgb_intermediate`].
+    pub fn srgb_intermediate(mut self, srgb_intermediate: bool) -> Self {
+        self.options.srgb_intermediate = srgb_intermediate;
+        self
+    }
+
+    /// See [`RendererOptions::linear_blending`].
+    pub fn linear_blending(mut self, linear_blending: bool) -> Self {
+        self.options.linear_blending = linear_blending;
+        self
+    }
+
+    /// See [`RendererOptions::pixels_per_point`].
+    pub fn pixels_per_point(mut self, pixels_per_point: Option<f32>) -> Self {
+        self.options.pixels_per_point = pixels_per_point;
+        self
+    }
+
+    /// See [`RendererOptions::ignore_zoom_factor`].
+    pub fn ignore_zoom_factor(mut self, ignore_zoom_factor: bool) -> Self {
+        self.options.ignore_zoom_factor = ignore_zoom_factor;
+        self
+    }
+
+    /// See [`RendererOptions::snap_to_pixels`].
+    pub fn snap_to_pixels(mut self, snap_to_pixels: bool) -> Self {
+        self.options.snap_to_pixels = snap_to_pixels;
+        self
+    }
+
+    /// See [`RendererOptions::track_dirty_rect`].
+    pub fn track_dirty_rect(mut self, track_dirty_rect: bool) -> Self {
+        self.options.track_dirty_rect = track_dirty_rect;
+        self
+    }
+
+    /// See [`RendererOptions::damage_tracking`].
+    pub fn damage_tracking(mut self, damage_tracking: bool) -> Self {
+        self.options.damage_tracking = damage_tracking;
+        self
+    }
+
+    /// See [`RendererOptions::render_scale`].
+    pub fn render_scale(mut self, render_scale: Option<RenderScale>) -> Self {
+        self.options.render_scale = render_scale;
+        self
+    }
+
+    /// See [`RendererOptions::dynamic_render_scale`].
+    pub fn dynamic_render_scale(
+        mut self,
+        dynamic_render_scale: Option<DynamicRenderScale>,
+    ) -> Self {
+        self.options.dynamic_render_scale = dynamic_render_scale;
+        self
+    }
+
+    /// See [`RendererOptions::subpixel_text`].
+    pub fn subpixel_text(mut self, subpixel_text: bool) -> Self {
+        self.options.subpixel_text = subpixel_text;
+        self
+    }
+
+    /// See [`RendererOptions::alpha_output`].
+    pub fn alpha_output(mut self, alpha_output: AlphaOutput) -> Self {
+        self.options.alpha_output = alpha_output;
+        self
+    }
+
+    /// See [`RendererOptions::chroma_key`].
+    pub fn chroma_key(mut self, chroma_key: Option<[f32; 3]>) -> Self {
+        self.options.chroma_key = chroma_key;
+        self
+    }
+
+    /// See [`RendererOptions::disable_unused_stages`].
+    pub fn disable_unused_stages(
+        mut self,
+        disable_unused_stages: bool,
+    ) -> Self {
+        self.options.disable_unused_stages = disable_unused_stages;
+        self
+    }
+
+    /// See [`RendererOptions::preserve_device_state`].
+    pub fn preserve_device_state(
+        mut self,
+        preserve_device_state: bool,
+    ) -> Self {
+        self.options.preserve_device_state = preserve_device_state;
+        self
+    }
+
+    /// See [`RendererOptions::multithread_protected`].
+    pub fn multithread_protected(
+        mut self,
+        multithread_protected: bool,
+    ) -> Self {
+        self.options.multithread_protected = multithread_protected;
+        self
+    }
+
+    /// See [`RendererOptions::validate_pipeline`].
+    pub fn validate_pipeline(mut self, validate_pipeline: bool) -> Self {
+        self.options.validate_pipeline = validate_pipeline;
+        self
+    }
+
+    /// See [`RendererOptions::prewarm`].
+    pub fn prewarm(mut self, prewarm: bool) -> Self {
+        self.options.prewarm = prewarm;
+        self
+    }
+
+    /// See [`RendererOptions::depth_range`].
+    pub fn depth_range(mut self, depth_range: DepthRange) -> Self {
+        self.options.depth_range = depth_range;
+        self
+    }
+
+    /// See [`RendererOptions::user_texture_filter`].
+    pub fn user_texture_filter(
+        mut self,
+        user_texture_filter: ScaleFilter,
+    ) -> Self {
+        self.options.user_texture_filter = user_texture_filter;
+        self
+    }
+
+    /// See [`RendererOptions::blend_mode`].
+    pub fn blend_mode(mut self, blend_mode: BlendMode) -> Self {
+        self.options.blend_mode = blend_mode;
+        self
+    }
+
+    /// See [`RendererOptions::software_cursor`].
+    #[cfg(feature = "software-cursor")]
+    pub fn software_cursor(mut self, software_cursor: bool) -> Self {
+        self.options.software_cursor = software_cursor;
+        self
+    }
+
+    /// See [`RendererOptions::backdrop_blur`].
+    pub fn backdrop_blur(
+        mut self,
+        backdrop_blur: Option<BackdropBlur>,
+    ) -> Self {
+        self.options.backdrop_blur = backdrop_blur;
+        self
+    }
+
+    /// See [`RendererOptions::frame_watchdog`].
+    pub fn frame_watchdog(
+        mut self,
+        frame_watchdog: Option<FrameWatchdog>,
+    ) -> Self {
+        self.options.frame_watchdog = frame_watchdog;
+        self
+    }
+
+    /// See [`RendererOptions::frame_limits`].
+    pub fn frame_limits(mut self, frame_limits: Option<FrameLimits>) -> Self {
+        self.options.frame_limits = frame_limits;
+        self
+    }
+
+    /// See [`RendererOptions::time_slicing`].
+    pub fn time_slicing(mut self, time_slicing: Option<TimeSlicing>) -> Self {
+        self.options.time_slicing = time_slicing;
+        self
+    }
+
+    /// See [`RendererOptions::on_frame`].
+    pub fn on_frame(mut self, on_frame: Option<FrameCallback>) -> Self {
+        self.options.on_frame = on_frame;
+        self
+    }
+}
@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use windows::{
+    Win32::{Foundation::E_FAIL, Graphics::Direct3D10::*},
+    core::{Error, Result},
+};
+
+use crate::{Renderer, RendererOptions};
+
+/// An `egui` context together with a [`Renderer`] drawing it, for
+/// applications that own their window, input and swap chain but do not
+/// want to pass the output of `egui` to the renderer by hand.
+///
+/// Gather the [`egui::RawInput`] of the frame, for example with
+/// `Win32Input` or `egui-winit`, then build the UI between
+/// [`EguiD3D10::begin_frame`] and [`EguiD3D10::end_frame_and_render`]:
+///
+/// ```ignore
+/// let ctx = egui.begin_frame(raw_input);
+/// egui::CentralPanel::default().show(ctx, |ui| ui.label("Hello"));
+/// let platform_output = egui.end_frame_and_render(&render_target)?;
+/// ```
+///
+/// The platform output, such as the cursor and clipboard commands, is
+/// returned to the application. For a whole application with its window
+/// and event loop, see `run_native` and the `win32` module instead.
+pub struct EguiD3D10 {
+    device: ID3D10Device,
+    egui_ctx: egui::Context,
+    renderer: Renderer,
+    in_frame: bool,
+    repaint_delay: Duration,
+}
+
+impl EguiD3D10 {
+    /// Create a new `egui` context and a [`Renderer`] on `device`.
+    pub fn new(device: &ID3D10Device) -> Result<Self> {
+        Self::with_options(device, RendererOptions::default())
+    }
+
+    /// Like [`EguiD3D10::new`], creating the [`Renderer`] with
+    /// [`Renderer::with_options`].
+    pub fn with_options(
+        device: &ID3D10Device,
+        options: RendererOptions,
+    ) -> Result<Self> {
+        let renderer = Renderer::with_options(device, options)?;
+        Ok(Self::with_renderer(device, renderer))
+    }
+
+    /// Draw a new `egui` context with `renderer`, which must have been
+    /// created on `device`, for example with [`Renderer::builder`].
+    pub fn with_renderer(device: &ID3D10Device, renderer: Renderer) -> Self {
+        Self {
+            device: device.clone(),
+            egui_ctx: egui::Context::default(),
+            renderer,
+            in_frame: false,
+            repaint_delay: Duration::ZERO,
+        }
+    }
+
+    /// The `egui` context, for example to set its style.
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// The [`Renderer`], for example to register user textures.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// The device the UI is rendered with.
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+
+    /// How long `egui` asked to wait before the next frame, as of the last
+    /// call to [`EguiD3D10::end_frame_and_render`]. [`Duration::ZERO`] means
+    /// as soon as possible, and [`Duration::MAX`] means only on the next
+    /// input event.
+    pub fn repaint_delay(&self) -> Duration {
+        self.repaint_delay
+    }
+
+    /// Start a frame of `egui` with `raw_input`, and return the context to
+    /// build the UI with.
+    pub fn begin_frame(&mut self, raw_input: egui::RawInput) -> &egui::Context {
+        self.egui_ctx.begin_pass(raw_input);
+        self.in_frame = true;
+        &self.egui_ctx
+    }
+
+    /// End the frame started with [`EguiD3D10::begin_frame`] and render the
+    /// UI to `render_target` with [`Renderer::render`]. Returns the platform
+    /// output of the frame for the application to handle.
+    ///
+    /// Returns `E_FAIL` if no frame was started.
+    pub fn end_frame_and_render(
+        &mut self,
+        render_target: &ID3D10RenderTargetView,
+    ) -> Result<egui::PlatformOutput> {
+        if !self.in_frame {
+            log::error!("ended a frame of egui that was not started");
+            return Err(Error::from_hresult(E_FAIL));
+        }
+        self.in_frame = false;
+        let full_output = self.egui_ctx.end_pass();
+        let (renderer_output, platform_output, viewport_output) =
+            crate::split_output(full_output);
+        if let Some(viewport) = viewport_output.get(&egui::ViewportId::ROOT) {
+            self.repaint_delay = viewport.repaint_delay;
+        }
+        #[cfg(feature = "software-cursor")]
+        self.renderer.set_cursor_icon(platform_output.cursor_icon);
+        self.renderer.render(
+            &self.device,
+            render_target,
+            &self.egui_ctx,
+            renderer_output,
+        )?;
+        Ok(platform_output)
+    }
+
+    /// Run `run_ui` in a frame of `egui` with `raw_input` and render it,
+    /// like [`EguiD3D10::begin_frame`] followed by
+    /// [`EguiD3D10::end_frame_and_render`].
+    pub fn run(
+        &mut self,
+        raw_input: egui::RawInput,
+        render_target: &ID3D10RenderTargetView,
+        run_ui: impl FnOnce(&egui::Context),
+    ) -> Result<egui::PlatformOutput> {
+        run_ui(self.begin_frame(raw_input));
+        self.end_frame_and_render(render_target)
+    }
+}
@@ -0,0 +1,129 @@
+use std::{any::Any, sync::Arc};
+
+use egui::{PaintCallback, PaintCallbackInfo};
+
+use windows::Win32::{Foundation::RECT, Graphics::Direct3D10::*};
+
+use crate::{DepthRange, FrameInfo, MeshData};
+
+/// The function of a [`CallbackFn`].
+type Paint = dyn Fn(PaintCallbackInfo, &ID3D10Device) + Send + Sync;
+
+/// The callback of an [`egui::PaintCallback`] drawing with Direct3D10, like
+/// `egui_glow::CallbackFn` for OpenGL, to draw your own 3D content inside
+/// an `egui` panel:
+///
+/// ```no_run
+/// # fn f(ui: &mut egui::Ui, rect: egui::Rect) {
+/// let callback = egui_directx10::CallbackFn::new(|info, device| {
+///     // Bind your pipeline and draw with `device`.
+/// });
+/// ui.painter().add(egui::PaintCallback {
+///     rect,
+///     callback: std::sync::Arc::new(callback),
+/// });
+/// # }
+/// ```
+///
+/// The callback is called while the frame is drawn, with the viewport set
+/// to the rect of the callback and the scissor rect to its clip rect. It
+/// may change any state of the device, which the renderer sets up again
+/// afterwards. Callbacks of other types are skipped with a warning.
+pub struct CallbackFn {
+    f: Box<Paint>,
+}
+
+impl CallbackFn {
+    /// Wrap `callback`, which is called with the position of the callback
+    /// and the device to draw with.
+    pub fn new(
+        callback: impl Fn(PaintCallbackInfo, &ID3D10Device) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            f: Box::new(callback),
+        }
+    }
+}
+
+/// A paint callback of a frame, with what is needed to position it.
+pub(crate) struct Callback {
+    callback: Arc<dyn Any + Send + Sync>,
+    /// The rect of the callback, in points.
+    viewport: egui::Rect,
+    /// The clip rect of the callback, in points.
+    clip_rect: egui::Rect,
+    pixels_per_point: f32,
+    origin: (i32, i32),
+    size: (u32, u32),
+}
+
+impl Callback {
+    /// Call the callback with the viewport and scissor rect set for it.
+    /// `clip_rect` is the clip rect in pixels of the render target, which
+    /// damage tracking may have shrunk.
+    pub fn call(
+        &self,
+        ctx: &ID3D10Device,
+        clip_rect: egui::Rect,
+        depth_range: DepthRange,
+    ) {
+        let Some(callback) = self.callback.downcast_ref::<CallbackFn>() else {
+            log::warn!(
+                "paint callbacks must be egui_directx10::CallbackFn. this \
+                 request will be ignored."
+            );
+            return;
+        };
+        let info = PaintCallbackInfo {
+            viewport: self.viewport,
+            clip_rect: self.clip_rect,
+            pixels_per_point: self.pixels_per_point,
+            screen_size_px: [self.size.0, self.size.1],
+        };
+        let viewport = info.viewport_in_pixels();
+        unsafe {
+            ctx.RSSetViewports(Some(&[D3D10_VIEWPORT {
+                TopLeftX: self.origin.0 + viewport.left_px,
+                TopLeftY: self.origin.1 + viewport.top_px,
+                Width: viewport.width_px.max(0) as _,
+                Height: viewport.height_px.max(0) as _,
+                MinDepth: depth_range.min_depth,
+                MaxDepth: depth_range.max_depth,
+            }]));
+            ctx.RSSetScissorRects(Some(&[RECT {
+                left: clip_rect.left() as _,
+                top: clip_rect.top() as _,
+                right: clip_rect.right() as _,
+                bottom: clip_rect.bottom() as _,
+            }]));
+        }
+        (callback.f)(info, ctx);
+    }
+}
+
+impl FrameInfo {
+    /// Position a paint callback in the frame, or `None` if it is empty.
+    pub(crate) fn convert_callback(
+        &self,
+        callback: PaintCallback,
+        clip_rect: egui::Rect,
+    ) -> Option<MeshData> {
+        if !callback.rect.is_positive() {
+            return None;
+        }
+        Some(MeshData {
+            vtx: Vec::new(),
+            idx: Vec::new(),
+            tex: egui::TextureId::default(),
+            clip_rect: self.clip_rect_in_pixels(clip_rect),
+            callback: Some(Callback {
+                callback: callback.callback,
+                viewport: callback.rect,
+                clip_rect,
+                pixels_per_point: self.pixels_per_point * self.zoom_factor,
+                origin: self.origin,
+                size: self.size,
+            }),
+        })
+    }
+}
@@ -0,0 +1,254 @@
+use windows::{
+    Win32::{
+        Foundation::E_INVALIDARG,
+        Graphics::{Direct3D10::*, Dxgi::Common::*},
+    },
+    core::{Error, Interface, Result},
+};
+
+use crate::Renderer;
+
+impl Renderer {
+    /// Also write every rendered frame to `capture_texture`, for streaming
+    /// and recording tools that need the UI without a second render pass,
+    /// or stop with `None`.
+    ///
+    /// After the UI is drawn, the rendered region of the render target is
+    /// copied to the same position in the first subresource of
+    /// `capture_texture`. The copy is of the render target, so it includes
+    /// whatever was drawn below the UI; render to a cleared target first for
+    /// the UI alone. The parts of the region outside of either texture are
+    /// not copied.
+    ///
+    /// Multisampled render targets are resolved as a whole instead, which
+    /// needs a texture of the size of the render target; rendering returns
+    /// `E_INVALIDARG` for other sizes.
+    ///
+    /// The texture must have a single sample, or this returns
+    /// `E_INVALIDARG`. It must also have the format of the render target or
+    /// one of the same typeless family, or rendering returns `E_INVALIDARG`.
+    pub fn set_capture_texture(
+        &mut self,
+        capture_texture: Option<ID3D10Texture2D>,
+    ) -> Result<()> {
+        if let Some(texture) = &capture_texture {
+            let mut desc = D3D10_TEXTURE2D_DESC::default();
+            unsafe { texture.GetDesc(&mut desc) };
+            if desc.SampleDesc.Count > 1 {
+                log::error!("capture textures cannot be multisampled.");
+                return Err(Error::from_hresult(E_INVALIDARG));
+            }
+        }
+        self.capture_texture = capture_texture;
+        Ok(())
+    }
+
+    /// Copy the region of `render_target` starting at `frame_origin` with
+    /// size `frame_size` to the texture set with
+    /// [`Renderer::set_capture_texture`], if any.
+    pub(crate) fn copy_to_capture(
+        &self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+    ) -> Result<()> {
+        let Some(capture_texture) = &self.capture_texture else {
+            return Ok(());
+        };
+        let source = unsafe { render_target.GetResource() }?
+            .cast::<ID3D10Texture2D>()?;
+        let mut desc = D3D10_TEXTURE2D_DESC::default();
+        unsafe { source.GetDesc(&mut desc) };
+        let mut capture_desc = D3D10_TEXTURE2D_DESC::default();
+        unsafe { capture_texture.GetDesc(&mut capture_desc) };
+        if typeless_family(capture_desc.Format) != typeless_family(desc.Format)
+        {
+            log::error!(
+                "the capture texture has format {:?}, which cannot be copied \
+                 from the render target of format {:?}.",
+                capture_desc.Format,
+                desc.Format,
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        let size = Self::get_render_target_size(render_target)?;
+        if desc.SampleDesc.Count > 1
+            && (capture_desc.Width, capture_desc.Height) != size
+        {
+            log::error!(
+                "the capture texture is {}x{}, but multisampled render \
+                 targets are resolved as a whole, to a texture of their size \
+                 of {}x{}.",
+                capture_desc.Width,
+                capture_desc.Height,
+                size.0,
+                size.1,
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        let (mip_slice, array_slice) =
+            Self::get_render_target_slice(render_target);
+        let subresource = mip_slice + array_slice * desc.MipLevels;
+        unsafe {
+            if desc.SampleDesc.Count > 1 {
+                device_context.ResolveSubresource(
+                    capture_texture,
+                    0,
+                    &source,
+                    subresource,
+                    Self::get_render_target_format(render_target),
+                );
+            } else if let Some(region) = copy_region(
+                frame_origin,
+                frame_size,
+                size,
+                (capture_desc.Width, capture_desc.Height),
+            ) {
+                device_context.CopySubresourceRegion(
+                    capture_texture,
+                    0,
+                    region.left,
+                    region.top,
+                    0,
+                    &source,
+                    subresource,
+                    Some(&region),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The part of the region at `origin` with `size` that lies within both
+/// the source of `source_size` and the destination of `destination_size`,
+/// or `None` if there is none.
+fn copy_region(
+    origin: (i32, i32),
+    size: (u32, u32),
+    source_size: (u32, u32),
+    destination_size: (u32, u32),
+) -> Option<D3D10_BOX> {
+    let clamp = |start: i32, length: u32, source: u32, destination: u32| {
+        let end =
+            (start as i64 + length as i64).min(source.min(destination) as i64);
+        let start = (start as i64).max(0);
+        (start < end).then_some((start as u32, end as u32))
+    };
+    let (left, right) =
+        clamp(origin.0, size.0, source_size.0, destination_size.0)?;
+    let (top, bottom) =
+        clamp(origin.1, size.1, source_size.1, destination_size.1)?;
+    Some(D3D10_BOX {
+        left,
+        top,
+        front: 0,
+        right,
+        bottom,
+        back: 1,
+    })
+}
+
+/// The typeless format of the family of `format`, which textures can be
+/// copied between, or `format` itself if it is not part of one.
+fn typeless_family(format: DXGI_FORMAT) -> DXGI_FORMAT {
+    match format {
+        DXGI_FORMAT_R8G8B8A8_UNORM
+        | DXGI_FORMAT_R8G8B8A8_UNORM_SRGB
+        | DXGI_FORMAT_R8G8B8A8_UINT
+        | DXGI_FORMAT_R8G8B8A8_SNORM
+        | DXGI_FORMAT_R8G8B8A8_SINT => DXGI_FORMAT_R8G8B8A8_TYPELESS,
+        DXGI_FORMAT_B8G8R8A8_UNORM | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => {
+            DXGI_FORMAT_B8G8R8A8_TYPELESS
+        },
+        DXGI_FORMAT_B8G8R8X8_UNORM | DXGI_FORMAT_B8G8R8X8_UNORM_SRGB => {
+            DXGI_FORMAT_B8G8R8X8_TYPELESS
+        },
+        DXGI_FORMAT_R10G10B10A2_UNORM | DXGI_FORMAT_R10G10B10A2_UINT => {
+            DXGI_FORMAT_R10G10B10A2_TYPELESS
+        },
+        DXGI_FORMAT_R16G16B16A16_FLOAT
+        | DXGI_FORMAT_R16G16B16A16_UNORM
+        | DXGI_FORMAT_R16G16B16A16_UINT
+        | DXGI_FORMAT_R16G16B16A16_SNORM
+        | DXGI_FORMAT_R16G16B16A16_SINT => DXGI_FORMAT_R16G16B16A16_TYPELESS,
+        DXGI_FORMAT_R32G32B32A32_FLOAT
+        | DXGI_FORMAT_R32G32B32A32_UINT
+        | DXGI_FORMAT_R32G32B32A32_SINT => DXGI_FORMAT_R32G32B32A32_TYPELESS,
+        format => format,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(left: u32, top: u32, right: u32, bottom: u32) -> D3D10_BOX {
+        D3D10_BOX {
+            left,
+            top,
+            front: 0,
+            right,
+            bottom,
+            back: 1,
+        }
+    }
+
+    #[test]
+    fn region_inside_both_textures_is_copied_whole() {
+        assert_eq!(
+            copy_region((10, 20), (30, 40), (100, 100), (200, 200)),
+            Some(region(10, 20, 40, 60))
+        );
+    }
+
+    #[test]
+    fn negative_origins_shrink_the_region() {
+        assert_eq!(
+            copy_region((-10, -5), (30, 40), (100, 100), (100, 100)),
+            Some(region(0, 0, 20, 35))
+        );
+    }
+
+    #[test]
+    fn region_is_clamped_to_the_smaller_texture() {
+        assert_eq!(
+            copy_region((50, 50), (100, 100), (120, 80), (90, 200)),
+            Some(region(50, 50, 90, 80))
+        );
+    }
+
+    #[test]
+    fn regions_outside_of_the_textures_are_skipped() {
+        assert_eq!(
+            copy_region((-40, 0), (30, 10), (100, 100), (100, 100)),
+            None
+        );
+        assert_eq!(
+            copy_region((100, 0), (30, 10), (100, 100), (100, 100)),
+            None
+        );
+        assert_eq!(copy_region((0, 0), (0, 10), (100, 100), (100, 100)), None);
+    }
+
+    #[test]
+    fn formats_of_a_family_are_compatible() {
+        assert_eq!(
+            typeless_family(DXGI_FORMAT_R8G8B8A8_UNORM_SRGB),
+            typeless_family(DXGI_FORMAT_R8G8B8A8_TYPELESS)
+        );
+        assert_eq!(
+            typeless_family(DXGI_FORMAT_R8G8B8A8_UNORM),
+            typeless_family(DXGI_FORMAT_R8G8B8A8_UNORM_SRGB)
+        );
+        assert_ne!(
+            typeless_family(DXGI_FORMAT_R8G8B8A8_UNORM),
+            typeless_family(DXGI_FORMAT_B8G8R8A8_UNORM)
+        );
+        assert_ne!(
+            typeless_family(DXGI_FORMAT_R10G10B10A2_UNORM),
+            typeless_family(DXGI_FORMAT_R16G16B16A16_FLOAT)
+        );
+    }
+}
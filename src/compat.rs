@@ -0,0 +1,82 @@
+//! The parts of the `egui` API that differ between the releases selected by
+//! the `egui-0_*` features. The rest of the crate goes through these instead
+//! of naming the differing items directly.
+
+use std::borrow::Cow;
+
+use egui::{Color32, ImageData};
+
+/// The map of [`egui::ViewportOutput`]s in [`egui::FullOutput`], which is
+/// ordered since `egui` 0.33.
+#[cfg(feature = "egui-0_33")]
+pub type ViewportOutputs = egui::OrderedViewportIdMap<egui::ViewportOutput>;
+
+/// The map of [`egui::ViewportOutput`]s in [`egui::FullOutput`], which is
+/// ordered since `egui` 0.33.
+#[cfg(not(feature = "egui-0_33"))]
+pub type ViewportOutputs = egui::ViewportIdMap<egui::ViewportOutput>;
+
+/// An image of `size` with `pixels`. Before `egui` 0.32,
+/// `ColorImage::new` filled the image with a single color instead.
+pub(crate) fn color_image(
+    size: [usize; 2],
+    pixels: Vec<Color32>,
+) -> egui::ColorImage {
+    #[cfg(any(feature = "egui-0_32", feature = "egui-0_33"))]
+    let image = egui::ColorImage::new(size, pixels);
+    #[cfg(not(any(feature = "egui-0_32", feature = "egui-0_33")))]
+    let image = egui::ColorImage { size, pixels };
+    image
+}
+
+/// The whole area of the viewport of `egui_ctx`, in points, which `egui`
+/// 0.33 renamed from the screen rect.
+#[cfg(feature = "headless")]
+pub(crate) fn viewport_rect(egui_ctx: &egui::Context) -> egui::Rect {
+    #[cfg(feature = "egui-0_33")]
+    let rect = egui_ctx.viewport_rect();
+    #[cfg(not(feature = "egui-0_33"))]
+    let rect = egui_ctx.screen_rect();
+    rect
+}
+
+/// The pixels of `image`, converting the coverage of the font atlas to
+/// white with alpha before `egui` 0.32, which stores the atlas as a color
+/// image itself.
+pub(crate) fn image_pixels(image: &ImageData) -> Cow<'_, [Color32]> {
+    match image {
+        ImageData::Color(image) => Cow::Borrowed(&image.pixels),
+        #[cfg(not(any(feature = "egui-0_32", feature = "egui-0_33")))]
+        ImageData::Font(image) => {
+            Cow::Owned(image.srgba_pixels(None).collect())
+        },
+    }
+}
+
+/// The number of pixels `image` holds, which malformed deltas may not match
+/// its size.
+pub(crate) fn image_pixel_count(image: &ImageData) -> usize {
+    match image {
+        ImageData::Color(image) => image.pixels.len(),
+        #[cfg(not(any(feature = "egui-0_32", feature = "egui-0_33")))]
+        ImageData::Font(image) => image.pixels.len(),
+    }
+}
+
+/// Call `on_end_pass` at the end of every pass of `egui_ctx`, registering it
+/// only once however often this is called. `egui` 0.33 replaced these
+/// callbacks with plugins, which it registers once by type.
+#[cfg(not(feature = "egui-0_33"))]
+pub(crate) fn on_end_pass_once(
+    egui_ctx: &egui::Context,
+    debug_name: &'static str,
+    on_end_pass: fn(&egui::Context),
+) {
+    let id = egui::Id::new(debug_name);
+    let registered = egui_ctx.data_mut(|data| {
+        std::mem::replace(data.get_temp_mut_or_default::<bool>(id), true)
+    });
+    if !registered {
+        egui_ctx.on_end_pass(debug_name, std::sync::Arc::new(on_end_pass));
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    mem, ptr, slice,
+};
+
+use egui::{
+    Color32, ColorImage, CursorIcon, Pos2, Rect, Shape, TextureId, Vec2,
+    epaint::ClippedShape,
+};
+
+use windows::{
+    Win32::{Graphics::Gdi::*, UI::WindowsAndMessaging::*},
+    core::{PCWSTR, Result},
+};
+
+use crate::{compat, system_cursor::system_cursor, texture::TexturePool};
+
+/// Draws the `egui` cursor as a textured quad on top of the UI, using the
+/// images of the system cursors.
+pub struct SoftwareCursor {
+    icon: CursorIcon,
+    /// Images keyed by the resource id of the system cursor, as several
+    /// icons share the same cursor.
+    images: HashMap<usize, CursorImage>,
+}
+
+struct CursorImage {
+    texture_id: TextureId,
+    /// Size in physical pixels.
+    size: Vec2,
+    /// Hotspot in physical pixels, relative to the top left corner.
+    hotspot: Vec2,
+}
+
+impl SoftwareCursor {
+    pub fn new() -> Self {
+        Self {
+            icon: CursorIcon::Default,
+            images: HashMap::new(),
+        }
+    }
+
+    pub fn set_icon(&mut self, icon: CursorIcon) {
+        self.icon = icon;
+    }
+
+    /// Append the cursor to `shapes`, at the latest pointer position known to
+    /// `egui_ctx`. Nothing is drawn if the pointer is outside the window or
+    /// the icon is [`CursorIcon::None`].
+    pub fn paint(
+        &mut self,
+        texture_pool: &mut TexturePool,
+        egui_ctx: &egui::Context,
+        points_per_pixel: f32,
+        shapes: &mut Vec<ClippedShape>,
+    ) -> Result<()> {
+        let Some(pos) = egui_ctx.input(|i| {
+            i.pointer
+                .has_pointer()
+                .then(|| i.pointer.latest_pos())
+                .flatten()
+        }) else {
+            return Ok(());
+        };
+        let Some(name) = system_cursor(self.icon) else {
+            return Ok(());
+        };
+        let image = match self.images.entry(name.0 as usize) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(load_cursor_image(texture_pool, name)?)
+            },
+        };
+
+        let min = pos - image.hotspot * points_per_pixel;
+        shapes.push(ClippedShape {
+            clip_rect: Rect::EVERYTHING,
+            shape: Shape::image(
+                image.texture_id,
+                Rect::from_min_size(min, image.size * points_per_pixel),
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                Color32::WHITE,
+            ),
+        });
+        Ok(())
+    }
+}
+
+fn load_cursor_image(
+    texture_pool: &mut TexturePool,
+    name: PCWSTR,
+) -> Result<CursorImage> {
+    let (image, hotspot) = unsafe { rasterize_cursor(name) }?;
+    let size = Vec2::new(image.width() as f32, image.height() as f32);
+    let texture_id = texture_pool.register_internal_image(image.into())?;
+    Ok(CursorImage {
+        texture_id,
+        size,
+        hotspot,
+    })
+}
+
+/// Draw a system cursor onto black and onto white, and recover its
+/// premultiplied colors and coverage from the difference. This works for
+/// both alpha-blended cursors and legacy cursors with an AND mask.
+unsafe fn rasterize_cursor(name: PCWSTR) -> Result<(ColorImage, Vec2)> {
+    unsafe {
+        let cursor = LoadCursorW(None, name)?;
+        let mut info = ICONINFO::default();
+        GetIconInfo(cursor.into(), &mut info)?;
+        if !info.hbmMask.is_invalid() {
+            let _ = DeleteObject(info.hbmMask.into());
+        }
+        if !info.hbmColor.is_invalid() {
+            let _ = DeleteObject(info.hbmColor.into());
+        }
+        let hotspot = Vec2::new(info.xHotspot as f32, info.yHotspot as f32);
+
+        let width = GetSystemMetrics(SM_CXCURSOR).max(1) as usize;
+        let height = GetSystemMetrics(SM_CYCURSOR).max(1) as usize;
+        let on_black = draw_cursor(cursor, width, height, 0x00)?;
+        let on_white = draw_cursor(cursor, width, height, 0xff)?;
+
+        let pixels = on_black
+            .chunks_exact(4)
+            .zip(on_white.chunks_exact(4))
+            .map(|(black, white)| {
+                // Inverting pixels end up brighter on black than on white;
+                // draw them opaque with the color they show on black.
+                let alpha = 255 - white[1].saturating_sub(black[1]);
+                Color32::from_rgba_premultiplied(
+                    black[2].min(alpha),
+                    black[1].min(alpha),
+                    black[0].min(alpha),
+                    alpha,
+                )
+            })
+            .collect();
+        Ok((compat::color_image([width, height], pixels), hotspot))
+    }
+}
+
+/// Draw `cursor` onto a 32-bit BGRA bitmap filled with `background` and
+/// return its pixels.
+unsafe fn draw_cursor(
+    cursor: HCURSOR,
+    width: usize,
+    height: usize,
+    background: u8,
+) -> Result<Vec<u8>> {
+    let info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: mem::size_of::<BITMAPINFOHEADER>() as _,
+            biWidth: width as _,
+            // negative height for a top-down bitmap
+            biHeight: -(height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    unsafe {
+        let dc = CreateCompatibleDC(None);
+        let mut bits = ptr::null_mut();
+        let bitmap = match CreateDIBSection(
+            Some(dc),
+            &info,
+            DIB_RGB_COLORS,
+            &mut bits,
+            None,
+            0,
+        ) {
+            Ok(bitmap) => bitmap,
+            Err(err) => {
+                let _ = DeleteDC(dc);
+                return Err(err);
+            },
+        };
+        let old_bitmap = SelectObject(dc, bitmap.into());
+        let pixels =
+            slice::from_raw_parts_mut(bits as *mut u8, width * height * 4);
+        pixels.fill(background);
+        let result = DrawIconEx(
+            dc,
+            0,
+            0,
+            cursor.into(),
+            width as _,
+            height as _,
+            0,
+            None,
+            DI_NORMAL,
+        );
+        let _ = GdiFlush();
+        let pixels = pixels.to_vec();
+        SelectObject(dc, old_bitmap);
+        let _ = DeleteObject(bitmap.into());
+        let _ = DeleteDC(dc);
+        result.map(|_| pixels)
+    }
+}
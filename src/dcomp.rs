@@ -0,0 +1,77 @@
+use crate::{Renderer, RendererOutput};
+
+use windows::{
+    Win32::{
+        Foundation::{POINT, RECT},
+        Graphics::{
+            Direct3D10::*, DirectComposition::IDCompositionSurface,
+            Dxgi::IDXGISurface,
+        },
+    },
+    core::{Interface, Result},
+};
+
+impl Renderer {
+    /// Render the output of `egui` into a DirectComposition surface.
+    ///
+    /// This wraps [`IDCompositionSurface::BeginDraw`] and
+    /// [`IDCompositionSurface::EndDraw`] around the rendering. `size` is the
+    /// size of the surface in pixels, which is redrawn as a whole every time.
+    /// Surfaces may be allocated from a shared atlas, so the UI is drawn at
+    /// the offset returned by `BeginDraw`, and the region is cleared to
+    /// transparent black beforehand without touching the rest of the atlas.
+    ///
+    /// The surface should be created with `DXGI_ALPHA_MODE_PREMULTIPLIED` and
+    /// a non-sRGB format such as `DXGI_FORMAT_B8G8R8A8_UNORM`, so the output
+    /// of `egui` composes correctly with the content below it. The
+    /// DirectComposition device must be created from the same Direct3D10
+    /// device as this [`Renderer`], which requires a Direct3D10.1 device.
+    ///
+    /// Pipeline state and errors are handled as in [`Renderer::render`].
+    /// `EndDraw` is called even if rendering fails.
+    pub fn render_to_composition_surface(
+        &mut self,
+        device_context: &ID3D10Device,
+        surface: &IDCompositionSurface,
+        size: (u32, u32),
+        egui_ctx: &egui::Context,
+        egui_output: RendererOutput,
+    ) -> Result<()> {
+        let update_rect = RECT {
+            left: 0,
+            top: 0,
+            right: size.0 as _,
+            bottom: size.1 as _,
+        };
+        let mut offset = POINT::default();
+        let dxgi_surface = unsafe {
+            surface.BeginDraw::<IDXGISurface>(Some(&update_rect), &mut offset)
+        }?;
+        let result = dxgi_surface
+            .cast::<ID3D10Texture2D>()
+            .and_then(|texture| {
+                let mut render_target = None;
+                unsafe {
+                    self.device.CreateRenderTargetView(
+                        &texture,
+                        None,
+                        Some(&mut render_target),
+                    )
+                }?;
+                Ok(render_target.unwrap())
+            })
+            .and_then(|render_target| {
+                self.render_region(
+                    device_context,
+                    &render_target,
+                    (offset.x, offset.y),
+                    size,
+                    Some([0.; 4]),
+                    egui_ctx,
+                    egui_output,
+                )
+            });
+        unsafe { surface.EndDraw() }?;
+        result
+    }
+}
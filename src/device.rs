@@ -0,0 +1,172 @@
+use std::{marker::PhantomData, ptr};
+
+use windows::{
+    Win32::{Foundation::HMODULE, Graphics::Direct3D10::*},
+    core::{HRESULT, Interface, Result},
+};
+
+// Returned when the debug layer is requested but not installed.
+const DXGI_ERROR_SDK_COMPONENT_MISSING: HRESULT = HRESULT(0x887A002D_u32 as _);
+
+/// Options of [`create_device`].
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DeviceOptions {
+    /// Enable the Direct3D10 debug layer, which reports API misuse to the
+    /// debugger output. If the SDK layers are not installed, the device is
+    /// created without it and a warning is logged. Disabled by default.
+    pub debug: bool,
+
+    /// Fall back to the WARP software rasterizer if no hardware device can be
+    /// created, as on CI machines, some virtual machines and machines with
+    /// broken drivers. Enabled by default.
+    pub warp_fallback: bool,
+
+    /// Enable the thread safety layer of the device, so that it can be used
+    /// from several threads, for example to create textures on a worker
+    /// thread while the UI renders. See [`set_multithread_protected`].
+    /// Disabled by default.
+    pub multithread_protected: bool,
+}
+
+impl Default for DeviceOptions {
+    fn default() -> Self {
+        Self {
+            debug: false,
+            warp_fallback: true,
+            multithread_protected: false,
+        }
+    }
+}
+
+/// The kind of device created by [`create_device`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DriverType {
+    /// A device on the default hardware adapter.
+    Hardware,
+    /// A device on the WARP software rasterizer.
+    Warp,
+}
+
+/// Create a Direct3D10 device on the default hardware adapter, falling back
+/// to WARP if that fails and [`DeviceOptions::warp_fallback`] is enabled.
+/// Returns the device and the driver it was created on.
+///
+/// If all attempts fail, the error of the last one is returned.
+pub fn create_device(
+    options: &DeviceOptions,
+) -> Result<(ID3D10Device, DriverType)> {
+    let mut result =
+        create_device_with_debug(D3D10_DRIVER_TYPE_HARDWARE, options.debug)
+            .map(|device| (device, DriverType::Hardware));
+    if let Err(err) = &result
+        && options.warp_fallback
+    {
+        log::warn!("failed to create a hardware device, using WARP: {err}");
+        result =
+            create_device_with_debug(D3D10_DRIVER_TYPE_WARP, options.debug)
+                .map(|device| (device, DriverType::Warp));
+    }
+    if let Ok((device, _)) = &result
+        && options.multithread_protected
+    {
+        set_multithread_protected(device, true)?;
+    }
+    result
+}
+
+/// Enable or disable the thread safety layer of `device`, through which
+/// Direct3D10 serializes calls made from several threads. Returns whether
+/// it was enabled before.
+///
+/// Individual calls are then safe, but a sequence of calls such as a render
+/// pass can still be interleaved with calls from other threads; hold a
+/// [`DeviceLock`] around it, which the [`Renderer`](crate::Renderer) does
+/// itself with
+/// [`RendererOptions::multithread_protected`](crate::RendererOptions::multithread_protected).
+pub fn set_multithread_protected(
+    device: &ID3D10Device,
+    protected: bool,
+) -> Result<bool> {
+    let multithread = device.cast::<ID3D10Multithread>()?;
+    Ok(unsafe { multithread.SetMultithreadProtected(protected) }.as_bool())
+}
+
+/// Whether the thread safety layer of `device` is enabled, see
+/// [`set_multithread_protected`].
+pub fn is_multithread_protected(device: &ID3D10Device) -> bool {
+    device.cast::<ID3D10Multithread>().is_ok_and(|multithread| {
+        unsafe { multithread.GetMultithreadProtected() }.as_bool()
+    })
+}
+
+/// Exclusive use of a device by the current thread, for the lifetime of
+/// this guard. Other threads calling into the device block until it is
+/// dropped. Only effective if the thread safety layer of the device is
+/// enabled, see [`set_multithread_protected`].
+///
+/// The lock is reentrant, and must be dropped on the thread that took it.
+pub struct DeviceLock {
+    multithread: ID3D10Multithread,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl DeviceLock {
+    /// Take the lock of `device`, waiting for other threads to release it.
+    pub fn new(device: &ID3D10Device) -> Result<Self> {
+        let multithread = device.cast::<ID3D10Multithread>()?;
+        unsafe { multithread.Enter() };
+        Ok(Self {
+            multithread,
+            _not_send: PhantomData,
+        })
+    }
+}
+
+impl Drop for DeviceLock {
+    fn drop(&mut self) {
+        unsafe { self.multithread.Leave() };
+    }
+}
+
+fn create_device_with_debug(
+    driver_type: D3D10_DRIVER_TYPE,
+    debug: bool,
+) -> Result<ID3D10Device> {
+    if !debug {
+        return create_device_of_type(driver_type, D3D10_CREATE_DEVICE_FLAG(0));
+    }
+    match create_device_of_type(driver_type, D3D10_CREATE_DEVICE_DEBUG) {
+        Err(err) if err.code() == DXGI_ERROR_SDK_COMPONENT_MISSING => {
+            log::warn!(
+                "the Direct3D10 debug layer is not installed, creating the \
+                 device without it"
+            );
+            create_device_of_type(driver_type, D3D10_CREATE_DEVICE_FLAG(0))
+        },
+        result => result,
+    }
+}
+
+fn create_device_of_type(
+    driver_type: D3D10_DRIVER_TYPE,
+    flags: D3D10_CREATE_DEVICE_FLAG,
+) -> Result<ID3D10Device> {
+    let mut device = None;
+    unsafe {
+        D3D10CreateDevice(
+            None,
+            driver_type,
+            HMODULE(ptr::null_mut()),
+            flags.0 as u32,
+            D3D10_SDK_VERSION,
+            Some(&mut device),
+        )
+    }?;
+    Ok(device.unwrap())
+}
@@ -0,0 +1,54 @@
+use windows::{
+    Win32::{Foundation::E_INVALIDARG, Graphics::Direct3D10::*},
+    core::{Error, IUnknown, Interface, Result},
+};
+
+use crate::Renderer;
+
+impl Renderer {
+    /// In debug builds, check that `device_context` and the device of
+    /// `render_target` are the device this renderer was created on. Drawing
+    /// with resources of another device, such as one on another adapter, is
+    /// undefined, so this fails loudly with `E_INVALIDARG` instead.
+    pub(crate) fn check_device(
+        &self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+    ) -> Result<()> {
+        if !cfg!(debug_assertions) {
+            return Ok(());
+        }
+        self.check_device_context(device_context)?;
+        if !self.is_own_device(&unsafe { render_target.GetDevice() }?)? {
+            log::error!(
+                "the render target passed to the renderer was not created on \
+                 the device of the renderer."
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        Ok(())
+    }
+
+    /// In debug builds, check that `device_context` is the device this
+    /// renderer was created on, for calls without a render target.
+    pub(crate) fn check_device_context(
+        &self,
+        device_context: &ID3D10Device,
+    ) -> Result<()> {
+        if cfg!(debug_assertions) && !self.is_own_device(device_context)? {
+            log::error!(
+                "the device passed to the renderer is not the device it was \
+                 created on."
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        Ok(())
+    }
+
+    /// Whether `device` is the device of this renderer. Compares the
+    /// `IUnknown` of both, which COM guarantees to be the same pointer for
+    /// the same object.
+    fn is_own_device(&self, device: &ID3D10Device) -> Result<bool> {
+        Ok(device.cast::<IUnknown>()? == self.device.cast::<IUnknown>()?)
+    }
+}
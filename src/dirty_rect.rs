@@ -0,0 +1,439 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use windows::{
+    Win32::{
+        Foundation::RECT,
+        Graphics::{Direct3D10::*, Dxgi::Common::DXGI_FORMAT},
+    },
+    core::Result,
+};
+
+use crate::{MeshData, Renderer};
+
+/// The state of
+/// [`RendererOptions::track_dirty_rect`](crate::RendererOptions::track_dirty_rect)
+/// and [`RendererOptions::damage_tracking`](crate::RendererOptions::damage_tracking).
+pub(crate) struct DirtyRect {
+    /// The region the meshes of the last frame were drawn to, which is the
+    /// intermediate texture if there is one.
+    frame: Option<egui::Rect>,
+    /// The hashes and clip rects of the meshes of the last frame, in draw
+    /// order.
+    meshes: Vec<(u64, egui::Rect)>,
+    /// The part of `frame` that changed, rounded out to whole pixels.
+    dirty: egui::Rect,
+    /// `dirty` in pixels of the render target.
+    rect: RECT,
+}
+
+impl Default for DirtyRect {
+    fn default() -> Self {
+        Self {
+            frame: None,
+            meshes: Vec::new(),
+            dirty: egui::Rect::NOTHING,
+            rect: RECT::default(),
+        }
+    }
+}
+
+impl DirtyRect {
+    /// Compare the meshes of a frame drawn to `frame` with those of the
+    /// previous frame. Meshes for which `is_volatile` returns `true` are
+    /// dirty even if they did not change, because their texture did.
+    ///
+    /// Meshes that were drawn in another order relative to the others are
+    /// dirty too, as raising a window over another one changes the screen
+    /// without changing any mesh. The largest set of meshes whose relative
+    /// order is unchanged is kept, and the others are redrawn.
+    fn update<'a>(
+        &mut self,
+        frame: egui::Rect,
+        meshes: impl IntoIterator<Item = &'a MeshData>,
+        is_volatile: impl Fn(egui::TextureId) -> bool,
+    ) {
+        let mut dirty = egui::Rect::NOTHING;
+        // The indices of the meshes of the last frame, by their hash. Equal
+        // meshes are matched in draw order.
+        let mut previous = HashMap::<u64, VecDeque<usize>>::new();
+        for (index, &(hash, _)) in self.meshes.iter().enumerate() {
+            previous.entry(hash).or_default().push_back(index);
+        }
+        let mut unchanged = Vec::new();
+        let mut matched = vec![false; self.meshes.len()];
+        let mut hashes = Vec::with_capacity(self.meshes.len());
+        for mesh in meshes {
+            let hash = hash_mesh(mesh);
+            let index = previous.get_mut(&hash).and_then(VecDeque::pop_front);
+            if let Some(index) = index {
+                matched[index] = true;
+            }
+            match index {
+                Some(index)
+                    if mesh.callback.is_none() && !is_volatile(mesh.tex) =>
+                {
+                    unchanged.push((index, mesh.clip_rect));
+                },
+                _ => dirty = dirty.union(mesh.clip_rect),
+            }
+            hashes.push((hash, mesh.clip_rect));
+        }
+        for (&(_, clip_rect), matched) in self.meshes.iter().zip(matched) {
+            if !matched {
+                dirty = dirty.union(clip_rect);
+            }
+        }
+        let in_order = longest_increasing_subsequence(
+            &unchanged
+                .iter()
+                .map(|&(index, _)| index)
+                .collect::<Vec<_>>(),
+        );
+        for ((_, clip_rect), in_order) in unchanged.into_iter().zip(in_order) {
+            if !in_order {
+                dirty = dirty.union(clip_rect);
+            }
+        }
+        if self.frame != Some(frame) {
+            dirty = frame;
+        }
+        let dirty = dirty.intersect(frame);
+        self.dirty = if dirty.is_positive() {
+            egui::Rect::from_min_max(dirty.min.floor(), dirty.max.ceil())
+        } else {
+            egui::Rect::NOTHING
+        };
+        self.frame = Some(frame);
+        self.meshes = hashes;
+    }
+}
+
+/// Which of `indices` belong to one of their longest increasing
+/// subsequences, found in `O(n log n)`.
+fn longest_increasing_subsequence(indices: &[usize]) -> Vec<bool> {
+    // The position in `indices` of the smallest last element of an
+    // increasing subsequence of each length, and the predecessor of each
+    // element in the subsequence it ends.
+    let mut tails = Vec::<usize>::new();
+    let mut predecessors = vec![None; indices.len()];
+    for (position, &index) in indices.iter().enumerate() {
+        let length = tails.partition_point(|&tail| indices[tail] < index);
+        predecessors[position] = length.checked_sub(1).map(|last| tails[last]);
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+    let mut in_order = vec![false; indices.len()];
+    let mut position = tails.last().copied();
+    while let Some(current) = position {
+        in_order[current] = true;
+        position = predecessors[current];
+    }
+    in_order
+}
+
+/// The `meshes` that overlap `damaged_rect`, clipped to it, in draw order.
+fn clip_to_damage(
+    damaged_rect: egui::Rect,
+    meshes: Vec<MeshData>,
+) -> Vec<MeshData> {
+    meshes
+        .into_iter()
+        .filter_map(|mut mesh| {
+            mesh.clip_rect = mesh.clip_rect.intersect(damaged_rect);
+            mesh.clip_rect.is_positive().then_some(mesh)
+        })
+        .collect()
+}
+
+fn hash_mesh(mesh: &MeshData) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    mesh.tex.hash(&mut hasher);
+    [mesh.clip_rect.min, mesh.clip_rect.max]
+        .map(|pos| [pos.x.to_bits(), pos.y.to_bits()])
+        .hash(&mut hasher);
+    bytemuck::cast_slice::<_, u8>(&mesh.vtx).hash(&mut hasher);
+    mesh.idx.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Renderer {
+    /// The smallest rect of the render target, in pixels, containing every
+    /// part of the UI that changed in the last call to [`Renderer::render`],
+    /// or `None` if neither
+    /// [`RendererOptions::track_dirty_rect`](crate::RendererOptions::track_dirty_rect)
+    /// nor [`RendererOptions::damage_tracking`](crate::RendererOptions::damage_tracking)
+    /// is enabled. The rect is empty if nothing changed.
+    ///
+    /// Pass it to `IDXGISwapChain1::Present1` as a dirty rect so that the
+    /// compositor only updates that part of the window. Content drawn by
+    /// the application around the UI, and user textures that are updated
+    /// in place, are not tracked; paint callbacks are always dirty.
+    pub fn dirty_rect(&self) -> Option<RECT> {
+        self.dirty_rect.as_ref().map(|dirty_rect| dirty_rect.rect)
+    }
+
+    /// Update the dirty rect with the meshes of a frame, if it is tracked.
+    /// `updated_textures` are the textures whose contents changed this frame.
+    pub(crate) fn track_dirty_rect(
+        &mut self,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        frame_format: DXGI_FORMAT,
+        meshes: &[MeshData],
+        updated_textures: &[egui::TextureId],
+    ) {
+        let frame = egui::Rect::from_min_size(
+            egui::pos2(frame_origin.0 as _, frame_origin.1 as _),
+            egui::vec2(frame_size.0 as _, frame_size.1 as _),
+        );
+        // Meshes are drawn to the top left of the intermediate texture, if
+        // there is one.
+        let intermediate_size =
+            self.intermediate_size(frame_size, frame_format);
+        let mesh_frame = match intermediate_size {
+            Some(size) => egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(size.0 as _, size.1 as _),
+            ),
+            None => frame,
+        };
+        // Textures updated outside of `egui` may change every frame.
+        let volatile_textures =
+            [self.backdrop_texture_id(), self.background_texture_id];
+        let post_process = self.post_process.is_some();
+        let Some(dirty_rect) = &mut self.dirty_rect else {
+            return;
+        };
+        dirty_rect.update(mesh_frame, meshes, |texture_id| {
+            updated_textures.contains(&texture_id)
+                || volatile_textures.contains(&Some(texture_id))
+        });
+        let dirty = dirty_rect.dirty;
+        let dirty = if !dirty.is_positive() || mesh_frame == frame {
+            dirty
+        } else if post_process {
+            // A post process shader may move pixels anywhere.
+            frame
+        } else {
+            // Scale the rect up, with a margin of a pixel of the intermediate
+            // texture for the filter.
+            let scale = frame.size() / mesh_frame.size();
+            let margin = egui::Vec2::splat(1.);
+            egui::Rect::from_min_max(
+                frame.min + (dirty.min.to_vec2() - margin) * scale,
+                frame.min + (dirty.max.to_vec2() + margin) * scale,
+            )
+            .intersect(frame)
+        };
+        dirty_rect.rect = if dirty.is_positive() {
+            RECT {
+                left: dirty.min.x.floor() as _,
+                top: dirty.min.y.floor() as _,
+                right: dirty.max.x.ceil() as _,
+                bottom: dirty.max.y.ceil() as _,
+            }
+        } else {
+            RECT::default()
+        };
+    }
+
+    /// The part of the intermediate texture to redraw in this frame with
+    /// [`RendererOptions::damage_tracking`](crate::RendererOptions::damage_tracking),
+    /// or `None` to redraw all of it.
+    pub(crate) fn damaged_rect(&self) -> Option<egui::Rect> {
+        if !self.options.damage_tracking {
+            return None;
+        }
+        self.dirty_rect.as_ref().map(|dirty_rect| dirty_rect.dirty)
+    }
+
+    /// Clear `damaged_rect` of the intermediate texture, which must be set up
+    /// as the render target, and get the `meshes` that overlap it, clipped
+    /// to it.
+    pub(crate) fn redraw_damaged_rect(
+        &self,
+        ctx: &ID3D10Device,
+        damaged_rect: egui::Rect,
+        meshes: Vec<MeshData>,
+    ) -> Result<Vec<MeshData>> {
+        if !damaged_rect.is_positive() {
+            return Ok(Vec::new());
+        }
+        unsafe {
+            ctx.PSSetShaderResources(0, Some(&[None]));
+            ctx.OMSetBlendState(&self.blend_state_opaque, &[0.; 4], u32::MAX);
+        }
+        // The quad covers the viewport and is clipped to the origin and size.
+        self.draw_quad(
+            ctx,
+            (damaged_rect.min.x as _, damaged_rect.min.y as _),
+            (damaged_rect.width() as _, damaged_rect.height() as _),
+            [0.; 4],
+        )?;
+        unsafe {
+            ctx.OMSetBlendState(&self.blend_state, &[0.; 4], u32::MAX);
+        }
+        Ok(clip_to_damage(damaged_rect, meshes))
+    }
+
+    /// Make the whole frame dirty on the next update, for example after a
+    /// frame in which the intermediate texture was not drawn to.
+    pub(crate) fn invalidate_dirty_rect(&mut self) {
+        if let Some(dirty_rect) = &mut self.dirty_rect {
+            dirty_rect.frame = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: egui::Rect =
+        egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(100., 100.));
+
+    /// A mesh with a hash of its own, clipped to `clip_rect`.
+    fn mesh(id: u32, clip_rect: egui::Rect) -> MeshData {
+        MeshData {
+            vtx: Vec::new(),
+            idx: vec![id],
+            tex: egui::TextureId::Managed(0),
+            clip_rect,
+            callback: None,
+        }
+    }
+
+    fn rect(min: [f32; 2], max: [f32; 2]) -> egui::Rect {
+        egui::Rect::from_min_max(min.into(), max.into())
+    }
+
+    fn update(dirty_rect: &mut DirtyRect, meshes: &[MeshData]) -> egui::Rect {
+        dirty_rect.update(FRAME, meshes, |_| false);
+        dirty_rect.dirty
+    }
+
+    #[test]
+    fn first_frame_is_dirty() {
+        let mut dirty_rect = DirtyRect::default();
+        assert_eq!(update(&mut dirty_rect, &[]), FRAME);
+    }
+
+    #[test]
+    fn unchanged_frame_is_clean() {
+        let mut dirty_rect = DirtyRect::default();
+        let meshes = [
+            mesh(0, rect([0., 0.], [50., 50.])),
+            mesh(1, rect([40., 40.], [90., 90.])),
+        ];
+        update(&mut dirty_rect, &meshes);
+        assert!(!update(&mut dirty_rect, &meshes).is_positive());
+    }
+
+    #[test]
+    fn changed_and_removed_meshes_are_dirty() {
+        let mut dirty_rect = DirtyRect::default();
+        update(
+            &mut dirty_rect,
+            &[
+                mesh(0, rect([0., 0.], [10., 10.])),
+                mesh(1, rect([20., 20.], [30., 30.])),
+            ],
+        );
+        let dirty = update(
+            &mut dirty_rect,
+            &[
+                mesh(0, rect([0., 0.], [10., 10.])),
+                mesh(2, rect([50., 50.], [60.5, 60.])),
+            ],
+        );
+        assert_eq!(dirty, rect([20., 20.], [61., 60.]));
+    }
+
+    #[test]
+    fn reordered_meshes_are_dirty() {
+        let mut dirty_rect = DirtyRect::default();
+        let [back, front] = [
+            mesh(0, rect([0., 0.], [50., 50.])),
+            mesh(1, rect([40., 40.], [90., 90.])),
+        ];
+        let others = (2..6).map(|id| mesh(id, rect([60., 0.], [70., 10.])));
+        let mut meshes = vec![back, front];
+        meshes.extend(others);
+        update(&mut dirty_rect, &meshes);
+        meshes.swap(0, 1);
+        let dirty = update(&mut dirty_rect, &meshes);
+        assert!(dirty.contains_rect(rect([40., 40.], [50., 50.])));
+        assert!(!dirty.contains_rect(rect([60., 0.], [70., 10.])));
+    }
+
+    #[test]
+    fn raising_a_window_only_redraws_it() {
+        let mut dirty_rect = DirtyRect::default();
+        let window = rect([10., 10.], [30., 30.]);
+        let mut meshes = (0..8).map(|id| mesh(id, window)).collect::<Vec<_>>();
+        meshes.push(mesh(8, rect([20., 20.], [40., 40.])));
+        update(&mut dirty_rect, &meshes);
+        let raised = meshes.remove(8);
+        meshes.insert(0, raised);
+        assert_eq!(
+            update(&mut dirty_rect, &meshes),
+            rect([20., 20.], [40., 40.])
+        );
+    }
+
+    #[test]
+    fn reordered_overlapping_meshes_are_redrawn() {
+        let clip_rects = [
+            rect([0., 0.], [50., 50.]),
+            rect([40., 40.], [90., 90.]),
+            rect([60., 0.], [70., 10.]),
+        ];
+        let meshes = |order: [u32; 3]| {
+            Vec::from(order.map(|id| mesh(id, clip_rects[id as usize])))
+        };
+        let mut dirty_rect = DirtyRect::default();
+        update(&mut dirty_rect, &meshes([0, 1, 2]));
+        let damaged_rect = update(&mut dirty_rect, &meshes([1, 0, 2]));
+        let redrawn = clip_to_damage(damaged_rect, meshes([1, 0, 2]));
+        // Both meshes cover their overlap again, in their new order, and
+        // the mesh apart from them is kept.
+        let overlap = rect([40., 40.], [50., 50.]);
+        assert_eq!(redrawn.len(), 2);
+        assert_eq!([&redrawn[0].idx, &redrawn[1].idx], [&[1], &[0]]);
+        assert!(
+            redrawn
+                .iter()
+                .all(|mesh| mesh.clip_rect.contains_rect(overlap))
+        );
+    }
+
+    #[test]
+    fn volatile_textures_are_dirty() {
+        let mut dirty_rect = DirtyRect::default();
+        let mut meshes = [
+            mesh(0, rect([0., 0.], [10., 10.])),
+            mesh(1, rect([20., 20.], [30., 30.])),
+        ];
+        meshes[1].tex = egui::TextureId::User(1);
+        update(&mut dirty_rect, &meshes);
+        dirty_rect.update(FRAME, &meshes, |texture_id| {
+            texture_id == egui::TextureId::User(1)
+        });
+        assert_eq!(dirty_rect.dirty, rect([20., 20.], [30., 30.]));
+    }
+
+    #[test]
+    fn longest_increasing_subsequence_is_kept() {
+        assert_eq!(
+            longest_increasing_subsequence(&[3, 0, 1, 4, 2]),
+            [false, true, true, false, true]
+        );
+        assert_eq!(longest_increasing_subsequence(&[]), [false; 0]);
+    }
+}
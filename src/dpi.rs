@@ -0,0 +1,111 @@
+use windows::{
+    Win32::{
+        Foundation::{HWND, LPARAM, RECT, WPARAM},
+        UI::{HiDpi::GetDpiForWindow, WindowsAndMessaging::*},
+    },
+    core::Result,
+};
+
+/// Tracks the DPI of a window across monitors and converts it into the
+/// `pixels_per_point` of `egui`.
+///
+/// The process must be per-monitor DPI aware (preferably
+/// `DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2`, set in the application
+/// manifest or with `SetProcessDpiAwarenessContext`), otherwise Windows
+/// stretches the window bitmap instead of sending `WM_DPICHANGED`, and the
+/// UI ends up blurry on any monitor but the primary one.
+///
+/// ```ignore
+/// if let Some(change) = dpi_tracker.handle_message(msg, wparam, lparam) {
+///     win32_input.set_native_pixels_per_point(change.pixels_per_point);
+///     change.resize_window(hwnd)?;
+///     return LRESULT(0);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DpiTracker {
+    dpi: u32,
+}
+
+/// A DPI change reported by [`DpiTracker::handle_message`].
+#[derive(Clone, Copy, Debug)]
+pub struct DpiChange {
+    /// The new DPI of the window.
+    pub dpi: u32,
+    /// The new native `pixels_per_point`, `dpi / 96`. This is fractional for
+    /// scale factors such as 125% and 175%.
+    pub pixels_per_point: f32,
+    /// The window rect suggested by Windows for the new DPI, in screen
+    /// coordinates. It keeps the window at the same physical size relative
+    /// to its contents and under the mouse cursor during a drag.
+    pub suggested_rect: RECT,
+}
+
+impl DpiTracker {
+    /// Start tracking the DPI of `hwnd`, querying its current value with
+    /// `GetDpiForWindow`.
+    pub fn new(hwnd: HWND) -> Self {
+        let dpi = match unsafe { GetDpiForWindow(hwnd) } {
+            0 => USER_DEFAULT_SCREEN_DPI,
+            dpi => dpi,
+        };
+        Self { dpi }
+    }
+
+    /// The current DPI of the window.
+    pub fn dpi(&self) -> u32 {
+        self.dpi
+    }
+
+    /// The current native `pixels_per_point`, `dpi / 96`.
+    pub fn pixels_per_point(&self) -> f32 {
+        pixels_per_point(self.dpi)
+    }
+
+    /// Handle `WM_DPICHANGED`; all other messages are ignored and yield
+    /// `None`.
+    ///
+    /// When a change is returned the `WndProc` should apply it (usually with
+    /// [`DpiChange::resize_window`]) and return `0`.
+    pub fn handle_message(
+        &mut self,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Option<DpiChange> {
+        if msg != WM_DPICHANGED {
+            return None;
+        }
+        // Both words of `wparam` hold the same DPI for the X and Y axes.
+        self.dpi = (wparam.0 & 0xffff) as u32;
+        let suggested_rect = unsafe { *(lparam.0 as *const RECT) };
+        Some(DpiChange {
+            dpi: self.dpi,
+            pixels_per_point: self.pixels_per_point(),
+            suggested_rect,
+        })
+    }
+}
+
+impl DpiChange {
+    /// Move and resize `hwnd` to [`DpiChange::suggested_rect`]. The swap
+    /// chain should then be resized in response to the resulting `WM_SIZE`.
+    pub fn resize_window(&self, hwnd: HWND) -> Result<()> {
+        let rect = self.suggested_rect;
+        unsafe {
+            SetWindowPos(
+                hwnd,
+                None,
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            )
+        }
+    }
+}
+
+fn pixels_per_point(dpi: u32) -> f32 {
+    dpi as f32 / USER_DEFAULT_SCREEN_DPI as f32
+}
@@ -0,0 +1,53 @@
+//! The public API of the `egui-directx11` crate, mapped onto this renderer,
+//! for projects maintaining Direct3D10 and Direct3D11 builds of the same
+//! overlay.
+//!
+//! [`Renderer`], [`RendererOutput`] and [`split_output`] already have the
+//! names and methods of their `egui-directx11` counterparts:
+//! `Renderer::new`, `Renderer::render`, `Renderer::register_user_texture`
+//! and `Renderer::unregister_user_texture`. They only differ in the
+//! Direct3D interfaces they take, which are aliased here under their
+//! Direct3D11 names, so that integration code can switch crates with a
+//! single import:
+//!
+//! ```ignore
+//! #[cfg(feature = "dx10")]
+//! use egui_directx10::dx11_compat::{self as egui_dx, ID3D11DeviceContext, ID3D11RenderTargetView};
+//! #[cfg(feature = "dx11")]
+//! use egui_directx11 as egui_dx;
+//! #[cfg(feature = "dx11")]
+//! use windows::Win32::Graphics::Direct3D11::{ID3D11DeviceContext, ID3D11RenderTargetView};
+//!
+//! fn draw(
+//!     renderer: &mut egui_dx::Renderer,
+//!     device_context: &ID3D11DeviceContext,
+//!     render_target: &ID3D11RenderTargetView,
+//!     egui_ctx: &egui::Context,
+//!     full_output: egui::FullOutput,
+//! ) -> windows::core::Result<()> {
+//!     let (renderer_output, _, _) = egui_dx::split_output(full_output);
+//!     renderer.render(device_context, render_target, egui_ctx, renderer_output)
+//! }
+//! ```
+
+use windows::Win32::Graphics::Direct3D10::*;
+
+pub use crate::{Renderer, RendererOutput, split_output};
+
+/// The device resources are created on.
+pub type ID3D11Device = ID3D10Device;
+/// The object draw calls are issued on, which is the device itself in
+/// Direct3D10; see [`immediate_context`].
+pub type ID3D11DeviceContext = ID3D10Device;
+/// The view of the render target to draw into.
+pub type ID3D11RenderTargetView = ID3D10RenderTargetView;
+/// The view of a texture sampled by `egui`.
+pub type ID3D11ShaderResourceView = ID3D10ShaderResourceView;
+/// A texture, such as a back buffer or the texture of a user texture.
+pub type ID3D11Texture2D = ID3D10Texture2D;
+
+/// The counterpart of `ID3D11Device::GetImmediateContext`: the device
+/// itself, as Direct3D10 has no device contexts.
+pub fn immediate_context(device: &ID3D11Device) -> ID3D11DeviceContext {
+    device.clone()
+}
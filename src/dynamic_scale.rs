@@ -0,0 +1,97 @@
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+use crate::{
+    DynamicRenderScale, Renderer,
+    gpu_timer::{FrameTimer, GpuTimer},
+};
+
+/// The fraction of the distance to the estimated ideal scale covered every
+/// frame, to keep the scale from oscillating.
+const SMOOTHING: f32 = 0.25;
+
+/// The state of
+/// [`RendererOptions::dynamic_render_scale`](crate::RendererOptions::dynamic_render_scale).
+pub(crate) struct DynamicScale {
+    options: DynamicRenderScale,
+    timer: GpuTimer<()>,
+    pub scale: f32,
+    pub gpu_time: Option<f32>,
+}
+
+impl DynamicScale {
+    pub fn new(
+        device: &ID3D10Device,
+        options: DynamicRenderScale,
+    ) -> Result<Self> {
+        Ok(Self {
+            options,
+            timer: GpuTimer::new(device)?,
+            scale: options.max_scale,
+            gpu_time: None,
+        })
+    }
+
+    /// Adjust the scale to the oldest measurement, if it is available, and
+    /// start measuring a frame. Returns `None` if the GPU is too far behind
+    /// to measure another frame.
+    pub fn begin(&mut self) -> Option<FrameTimer> {
+        let (timer, measured) = self.timer.begin(())?;
+        if let Some((gpu_time, ())) = measured {
+            self.gpu_time = Some(gpu_time);
+            self.adjust(gpu_time);
+        }
+        Some(timer)
+    }
+
+    fn adjust(&mut self, gpu_time: f32) {
+        let DynamicRenderScale {
+            budget_ms,
+            min_scale,
+            max_scale,
+        } = self.options;
+        if gpu_time <= 0. {
+            return;
+        }
+        // The cost of the UI is roughly proportional to its area.
+        let ideal = self.scale * (budget_ms / gpu_time).sqrt();
+        self.scale += (ideal - self.scale) * SMOOTHING;
+        self.scale = self.scale.clamp(min_scale.min(max_scale), max_scale);
+    }
+}
+
+impl Renderer {
+    /// Replace the [`RendererOptions::dynamic_render_scale`](crate::RendererOptions::dynamic_render_scale)
+    /// of this [`Renderer`]. The scale restarts from
+    /// [`DynamicRenderScale::max_scale`].
+    pub fn set_dynamic_render_scale(
+        &mut self,
+        dynamic_render_scale: Option<DynamicRenderScale>,
+    ) -> Result<()> {
+        self.dynamic_scale = dynamic_render_scale
+            .map(|options| DynamicScale::new(&self.device, options))
+            .transpose()?;
+        self.options.dynamic_render_scale = dynamic_render_scale;
+        Ok(())
+    }
+
+    /// The resolution of the UI relative to the render target, as set with
+    /// [`RendererOptions::render_scale`](crate::RendererOptions::render_scale)
+    /// or chosen by
+    /// [`RendererOptions::dynamic_render_scale`](crate::RendererOptions::dynamic_render_scale).
+    pub fn render_scale(&self) -> f32 {
+        let scale = match (&self.dynamic_scale, self.options.render_scale) {
+            (Some(dynamic_scale), _) => dynamic_scale.scale,
+            (None, Some(render_scale)) => render_scale.scale,
+            (None, None) => 1.,
+        };
+        scale.clamp(0., 1.)
+    }
+
+    /// The GPU time the UI took in a recent frame, in milliseconds, as
+    /// measured for
+    /// [`RendererOptions::dynamic_render_scale`](crate::RendererOptions::dynamic_render_scale).
+    /// `None` if it is disabled or no frame has been measured yet.
+    pub fn gpu_time_ms(&self) -> Option<f32> {
+        self.dynamic_scale.as_ref()?.gpu_time
+    }
+}
@@ -0,0 +1,575 @@
+//! C exports for hosting `egui` in C or C++ programs, such as existing engines
+//! or injectors hooking `IDXGISwapChain::Present`.
+//!
+//! The UI is written in Rust, in a DLL that provides it with [`set_ui`]; the
+//! host drives it through the functions declared in
+//! `include/egui_directx10.h`. The header is written by hand; keep it in
+//! sync with the exports of this module.
+//!
+//! Engines that own the device create an [`EguiDx10Renderer`] with
+//! [`egui_dx10_renderer_create`], register their textures with
+//! `egui_dx10_renderer_register_texture`, which needs the `user-textures`
+//! feature, and draw into their own render targets with
+//! [`egui_dx10_renderer_render`].
+//!
+//! Injectors use the overlay functions instead:
+//!
+//! + [`egui_dx10_init`] once with the hooked swap chain;
+//! + [`egui_dx10_render`] from the `Present` hook, before calling the original
+//!   function;
+//! + [`egui_dx10_resize`] from the `ResizeBuffers` hook, before calling the
+//!   original function;
+//! + [`egui_dx10_shutdown`] before unhooking.
+//!
+//! The overlay functions must be called on the thread that presents the swap
+//! chain. All functions return an `HRESULT`, and never unwind into the host:
+//! panics are caught and reported as `E_FAIL`. See `examples/overlay_dll.rs`
+//! for a template, which is built as a DLL with
+//! `cargo build --example overlay_dll --features ffi`.
+
+use std::{
+    cell::RefCell,
+    ffi::{CStr, c_char, c_void},
+    panic::{self, AssertUnwindSafe},
+    slice,
+    sync::Mutex,
+    time::Instant,
+};
+
+use egui::{Event, Key, Modifiers, PointerButton, Pos2, Rect, Vec2};
+
+use windows::{
+    Win32::{
+        Foundation::{E_FAIL, E_INVALIDARG, S_OK},
+        Graphics::{Direct3D10::*, Dxgi::IDXGISwapChain},
+    },
+    core::{HRESULT, Interface, Result},
+};
+
+use crate::{Overlay, Renderer, RendererOutput};
+
+#[cfg(feature = "user-textures")]
+use windows::Win32::Foundation::S_FALSE;
+
+type Ui = Box<dyn FnMut(&egui::Context) + Send>;
+
+static UI: Mutex<Option<Ui>> = Mutex::new(None);
+
+thread_local! {
+    static STATE: RefCell<Option<FfiState>> = const { RefCell::new(None) };
+}
+
+/// Set the UI shown by [`egui_dx10_render`]. Can be called at any time and
+/// from any thread, including from `DllMain`.
+pub fn set_ui(ui: impl FnMut(&egui::Context) + Send + 'static) {
+    *UI.lock().unwrap_or_else(|err| err.into_inner()) = Some(Box::new(ui));
+}
+
+/// A key press or release passed to [`egui_dx10_render`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EguiDx10Key {
+    /// The name of the key as understood by [`Key::from_name`], such as
+    /// `"Enter"`, `"Backspace"`, `"A"` or `"F1"`, as a NUL-terminated UTF-8
+    /// string.
+    pub name: *const c_char,
+    /// Whether the key was pressed or released.
+    pub pressed: bool,
+}
+
+/// The input of a frame passed to [`egui_dx10_render`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct EguiDx10Input {
+    /// The position of the pointer relative to the top-left corner of the
+    /// back buffer, in pixels.
+    pub pointer_x: f32,
+    /// See [`EguiDx10Input::pointer_x`].
+    pub pointer_y: f32,
+    /// Whether the pointer is over the window. If `false`, the position is
+    /// ignored.
+    pub pointer_inside: bool,
+    /// The mouse buttons currently held: bit 0 for the primary button, bit 1
+    /// for the secondary button and bit 2 for the middle button. Presses and
+    /// releases are derived from the changes since the previous frame.
+    pub buttons: u32,
+    /// The scroll distance since the previous frame, in lines. Positive
+    /// values scroll the content right and down.
+    pub scroll_x: f32,
+    /// See [`EguiDx10Input::scroll_x`].
+    pub scroll_y: f32,
+    /// The modifier keys currently held: bit 0 for Alt, bit 1 for Ctrl and
+    /// bit 2 for Shift.
+    pub modifiers: u32,
+    /// Text typed since the previous frame, as a NUL-terminated UTF-8
+    /// string, or null.
+    pub text: *const c_char,
+    /// Keys pressed or released since the previous frame, or null.
+    pub keys: *const EguiDx10Key,
+    /// The number of elements in [`EguiDx10Input::keys`].
+    pub key_count: usize,
+    /// The number of pixels per point, or zero for `1.0`.
+    pub pixels_per_point: f32,
+}
+
+/// Create the overlay for `swap_chain`, an `IDXGISwapChain*` created on a
+/// Direct3D10 device. Replaces any previous overlay.
+///
+/// # Safety
+///
+/// `swap_chain` must be a valid `IDXGISwapChain` pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_init(swap_chain: *mut c_void) -> HRESULT {
+    guard(|| {
+        let Some(swap_chain) =
+            (unsafe { IDXGISwapChain::from_raw_borrowed(&swap_chain) })
+        else {
+            return Err(E_INVALIDARG.into());
+        };
+        let state = FfiState {
+            overlay: Overlay::new(swap_chain)?,
+            egui_ctx: egui::Context::default(),
+            input: InputState::new(),
+        };
+        STATE.with_borrow_mut(|current| *current = Some(state));
+        Ok(())
+    })
+}
+
+/// Run the UI set with [`set_ui`] with `input`, which may be null, and render
+/// it onto the current back buffer. Does nothing before [`egui_dx10_init`].
+///
+/// # Safety
+///
+/// `input` must be null or point to a valid [`EguiDx10Input`], whose `text`
+/// and `keys` are null or valid for reads.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_render(
+    input: *const EguiDx10Input,
+) -> HRESULT {
+    guard(|| {
+        STATE.with_borrow_mut(|state| {
+            let Some(state) = state else {
+                return Ok(());
+            };
+            let input = unsafe { input.as_ref() };
+            state.render(input.map(|input| unsafe { convert_input(input) }))
+        })
+    })
+}
+
+/// Release the references to the back buffer, so that the host can call the
+/// original `IDXGISwapChain::ResizeBuffers`.
+#[unsafe(no_mangle)]
+pub extern "C" fn egui_dx10_resize() -> HRESULT {
+    guard(|| {
+        STATE.with_borrow_mut(|state| {
+            if let Some(state) = state {
+                state.overlay.release_back_buffer();
+            }
+        });
+        Ok(())
+    })
+}
+
+/// Destroy the overlay and release all references to the swap chain and its
+/// device.
+#[unsafe(no_mangle)]
+pub extern "C" fn egui_dx10_shutdown() -> HRESULT {
+    guard(|| {
+        STATE.with_borrow_mut(|state| *state = None);
+        Ok(())
+    })
+}
+
+/// A renderer and `egui` context driven from C, created with
+/// [`egui_dx10_renderer_create`].
+///
+/// A renderer must only be used by one thread at a time.
+pub struct EguiDx10Renderer {
+    device: ID3D10Device,
+    renderer: Renderer,
+    egui_ctx: egui::Context,
+    input: InputState,
+}
+
+impl EguiDx10Renderer {
+    /// The `egui` context the UI runs in.
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// The [`Renderer`], for example to register textures from Rust.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+}
+
+/// Create a renderer on `device`, an `ID3D10Device*`, and store it in
+/// `*renderer`. Destroy it with [`egui_dx10_renderer_destroy`].
+///
+/// # Safety
+///
+/// `device` must be a valid `ID3D10Device` pointer, and `renderer` must be
+/// valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_renderer_create(
+    device: *mut c_void,
+    renderer: *mut *mut EguiDx10Renderer,
+) -> HRESULT {
+    guard(|| {
+        let Some(device) =
+            (unsafe { ID3D10Device::from_raw_borrowed(&device) })
+        else {
+            return Err(E_INVALIDARG.into());
+        };
+        if renderer.is_null() {
+            return Err(E_INVALIDARG.into());
+        }
+        let created = Box::new(EguiDx10Renderer {
+            device: device.clone(),
+            renderer: Renderer::new(device)?,
+            egui_ctx: egui::Context::default(),
+            input: InputState::new(),
+        });
+        unsafe { *renderer = Box::into_raw(created) };
+        Ok(())
+    })
+}
+
+/// Destroy a renderer created with [`egui_dx10_renderer_create`]. Does
+/// nothing if `renderer` is null.
+///
+/// # Safety
+///
+/// `renderer` must be null or a renderer that has not been destroyed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_renderer_destroy(
+    renderer: *mut EguiDx10Renderer,
+) -> HRESULT {
+    guard(|| {
+        if !renderer.is_null() {
+            drop(unsafe { Box::from_raw(renderer) });
+        }
+        Ok(())
+    })
+}
+
+/// Register `shader_resource_view`, an `ID3D10ShaderResourceView*` holding
+/// sRGB colors, and store the id to use for it in `egui` in `*texture_id`.
+/// The UI refers to it as `egui::TextureId::User(texture_id)`.
+///
+/// # Safety
+///
+/// `renderer` must be a valid renderer, `shader_resource_view` a valid
+/// `ID3D10ShaderResourceView` pointer, and `texture_id` valid for writes.
+#[cfg(feature = "user-textures")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_renderer_register_texture(
+    renderer: *mut EguiDx10Renderer,
+    shader_resource_view: *mut c_void,
+    texture_id: *mut u64,
+) -> HRESULT {
+    guard(|| {
+        let renderer = unsafe { renderer.as_mut() };
+        let srv = unsafe {
+            ID3D10ShaderResourceView::from_raw_borrowed(&shader_resource_view)
+        };
+        let (Some(renderer), Some(srv)) = (renderer, srv) else {
+            return Err(E_INVALIDARG.into());
+        };
+        if texture_id.is_null() {
+            return Err(E_INVALIDARG.into());
+        }
+        let egui::TextureId::User(id) =
+            renderer.renderer.register_user_texture(srv.clone())
+        else {
+            unreachable!("user textures always get user ids");
+        };
+        unsafe { *texture_id = id };
+        Ok(())
+    })
+}
+
+/// Unregister a texture registered with
+/// [`egui_dx10_renderer_register_texture`]. Returns `S_FALSE` if it was not
+/// registered.
+///
+/// # Safety
+///
+/// `renderer` must be a valid renderer.
+#[cfg(feature = "user-textures")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_renderer_unregister_texture(
+    renderer: *mut EguiDx10Renderer,
+    texture_id: u64,
+) -> HRESULT {
+    guard_with(|| {
+        let Some(renderer) = (unsafe { renderer.as_mut() }) else {
+            return Err(E_INVALIDARG.into());
+        };
+        let removed = renderer
+            .renderer
+            .unregister_user_texture(egui::TextureId::User(texture_id));
+        Ok(if removed { S_OK } else { S_FALSE })
+    })
+}
+
+/// Run the UI set with [`set_ui`] with `input`, which may be null, and render
+/// it into `render_target`, an `ID3D10RenderTargetView*`. The render target
+/// is not cleared, and the pipeline state is overridden as documented in
+/// [`Renderer::render`].
+///
+/// # Safety
+///
+/// `renderer` must be a valid renderer, `render_target` a valid
+/// `ID3D10RenderTargetView` pointer, and `input` as in [`egui_dx10_render`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn egui_dx10_renderer_render(
+    renderer: *mut EguiDx10Renderer,
+    render_target: *mut c_void,
+    input: *const EguiDx10Input,
+) -> HRESULT {
+    guard(|| {
+        let renderer = unsafe { renderer.as_mut() };
+        let render_target = unsafe {
+            ID3D10RenderTargetView::from_raw_borrowed(&render_target)
+        };
+        let (Some(renderer), Some(render_target)) = (renderer, render_target)
+        else {
+            return Err(E_INVALIDARG.into());
+        };
+        let input = unsafe { input.as_ref() }
+            .map(|input| unsafe { convert_input(input) });
+        let size = Renderer::get_render_target_size(render_target)?;
+        let raw_input = renderer.input.raw_input(input, size);
+        let renderer_output = run_ui(&renderer.egui_ctx, raw_input);
+        renderer.renderer.render(
+            &renderer.device,
+            render_target,
+            &renderer.egui_ctx,
+            renderer_output,
+        )
+    })
+}
+
+fn guard(f: impl FnOnce() -> Result<()>) -> HRESULT {
+    guard_with(|| f().map(|()| S_OK))
+}
+
+fn guard_with(f: impl FnOnce() -> Result<HRESULT>) -> HRESULT {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(code)) => code,
+        Ok(Err(err)) => err.code(),
+        Err(_) => {
+            log::error!("panicked while handling a call from the host");
+            E_FAIL
+        },
+    }
+}
+
+struct FfiState {
+    overlay: Overlay,
+    egui_ctx: egui::Context,
+    input: InputState,
+}
+
+impl FfiState {
+    fn render(&mut self, input: Option<Input>) -> Result<()> {
+        let size = self.overlay.back_buffer_size()?;
+        let raw_input = self.input.raw_input(input, size);
+        let renderer_output = run_ui(&self.egui_ctx, raw_input);
+        self.overlay.render(&self.egui_ctx, renderer_output)
+    }
+}
+
+fn run_ui(
+    egui_ctx: &egui::Context,
+    raw_input: egui::RawInput,
+) -> RendererOutput {
+    let egui_output = egui_ctx.run(raw_input, |ctx| {
+        // The UI runs without the lock held, so that it can call `set_ui`
+        // itself, for example to switch screens.
+        let ui = UI.lock().unwrap_or_else(|err| err.into_inner()).take();
+        if let Some(mut ui) = ui {
+            ui(ctx);
+            UI.lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .get_or_insert(ui);
+        }
+    });
+    crate::split_output(egui_output).0
+}
+
+/// [`EguiDx10Input`] with its pointers resolved.
+struct Input {
+    pointer_pos: Option<Pos2>,
+    buttons: u32,
+    scroll: Vec2,
+    modifiers: Modifiers,
+    text: String,
+    keys: Vec<(Key, bool)>,
+    pixels_per_point: f32,
+}
+
+unsafe fn convert_input(input: &EguiDx10Input) -> Input {
+    let text = if input.text.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(input.text) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    let keys = if input.keys.is_null() {
+        &[][..]
+    } else {
+        unsafe { slice::from_raw_parts(input.keys, input.key_count) }
+    };
+    let keys = keys
+        .iter()
+        .filter(|key| !key.name.is_null())
+        .filter_map(|key| {
+            let name = unsafe { CStr::from_ptr(key.name) }.to_str().ok()?;
+            Some((Key::from_name(name)?, key.pressed))
+        })
+        .collect();
+    Input {
+        pointer_pos: input
+            .pointer_inside
+            .then(|| Pos2::new(input.pointer_x, input.pointer_y)),
+        buttons: input.buttons,
+        scroll: Vec2::new(input.scroll_x, input.scroll_y),
+        modifiers: Modifiers {
+            alt: input.modifiers & 1 != 0,
+            ctrl: input.modifiers & 2 != 0,
+            shift: input.modifiers & 4 != 0,
+            mac_cmd: false,
+            command: input.modifiers & 2 != 0,
+        },
+        text,
+        keys,
+        pixels_per_point: if input.pixels_per_point > 0.0 {
+            input.pixels_per_point
+        } else {
+            1.0
+        },
+    }
+}
+
+/// Input state carried between frames.
+struct InputState {
+    start_time: Instant,
+    pointer_pos: Option<Pos2>,
+    buttons: u32,
+}
+
+impl InputState {
+    fn new() -> Self {
+        Self {
+            start_time: Instant::now(),
+            pointer_pos: None,
+            buttons: 0,
+        }
+    }
+
+    fn raw_input(
+        &mut self,
+        input: Option<Input>,
+        (width, height): (u32, u32),
+    ) -> egui::RawInput {
+        let pixels_per_point =
+            input.as_ref().map_or(1.0, |input| input.pixels_per_point);
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                Vec2::new(width as f32, height as f32) / pixels_per_point,
+            )),
+            time: Some(self.start_time.elapsed().as_secs_f64()),
+            focused: true,
+            ..Default::default()
+        };
+        raw_input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(pixels_per_point);
+        let Some(input) = input else {
+            return raw_input;
+        };
+
+        raw_input.modifiers = input.modifiers;
+        let events = &mut raw_input.events;
+        let pointer_pos = input.pointer_pos.map(|pos| pos / pixels_per_point);
+        match pointer_pos {
+            Some(pos) => events.push(Event::PointerMoved(pos)),
+            None => events.push(Event::PointerGone),
+        }
+        // Releases outside of the window are reported at the last position.
+        if pointer_pos.is_some() {
+            self.pointer_pos = pointer_pos;
+        }
+        let changed = self.buttons ^ input.buttons;
+        for (bit, button) in [
+            PointerButton::Primary,
+            PointerButton::Secondary,
+            PointerButton::Middle,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if changed & (1 << bit) != 0
+                && let Some(pos) = self.pointer_pos
+            {
+                events.push(Event::PointerButton {
+                    pos,
+                    button,
+                    pressed: input.buttons & (1 << bit) != 0,
+                    modifiers: input.modifiers,
+                });
+            }
+        }
+        self.buttons = input.buttons;
+        if input.scroll != Vec2::ZERO {
+            events.push(Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Line,
+                delta: input.scroll,
+                modifiers: input.modifiers,
+            });
+        }
+        for (key, pressed) in input.keys {
+            events.push(Event::Key {
+                key,
+                physical_key: None,
+                pressed,
+                repeat: false,
+                modifiers: input.modifiers,
+            });
+        }
+        if !input.text.is_empty() {
+            events.push(Event::Text(input.text));
+        }
+        raw_input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn ui_can_replace_itself() {
+        static SECOND_RUNS: AtomicUsize = AtomicUsize::new(0);
+        set_ui(|_| {
+            set_ui(|_| {
+                SECOND_RUNS.fetch_add(1, Ordering::Relaxed);
+            });
+        });
+        let egui_ctx = egui::Context::default();
+        run_ui(&egui_ctx, Default::default());
+        assert_eq!(SECOND_RUNS.load(Ordering::Relaxed), 0);
+        run_ui(&egui_ctx, Default::default());
+        assert_eq!(SECOND_RUNS.load(Ordering::Relaxed), 1);
+    }
+}
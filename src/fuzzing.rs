@@ -0,0 +1,140 @@
+//! Deterministic entry points for the fuzz targets in `fuzz/`, which run the
+//! state machines of the renderer without a device.
+//!
+//! They are driven by a backend that creates no GPU objects and instead
+//! panics on any call the Direct3D10 runtime would turn into an
+//! out-of-bounds read or write, so that the fuzzer reports them as crashes.
+
+use egui::{Color32, TexturesDelta};
+
+use windows::{
+    Win32::{
+        Foundation::RECT,
+        Graphics::Direct3D10::{D3D10_BIND_FLAG, D3D10_BOX, D3D10_MAP},
+    },
+    core::Result,
+};
+
+use crate::{
+    DefaultTextureAllocator, TextureAllocator, VertexData,
+    backend::{Backend, DrawRange},
+    texture::TexturePool,
+};
+
+/// A [`TexturePool`] on a backend without a device, to feed it arbitrary
+/// sequences of [`TexturesDelta`]s.
+pub struct TexturePoolFuzzer {
+    pool: TexturePool<NullBackend>,
+}
+
+impl TexturePoolFuzzer {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self {
+            pool: TexturePool::new(
+                NullBackend,
+                Box::new(DefaultTextureAllocator),
+            ),
+        }
+    }
+
+    /// Apply `delta`, as [`Renderer::update_textures`](crate::Renderer::update_textures)
+    /// does.
+    pub fn update(&mut self, delta: TexturesDelta) {
+        self.pool.update(delta).unwrap();
+    }
+
+    /// Release the textures, as [`Renderer::trim`](crate::Renderer::trim)
+    /// does, to be recreated by the next update.
+    pub fn evict(&mut self) {
+        self.pool.evict();
+    }
+}
+
+impl Default for TexturePoolFuzzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`Backend`] whose textures only hold their size.
+struct NullBackend;
+
+impl Backend for NullBackend {
+    type Buffer = ();
+    type Texture = (usize, usize);
+    type ShaderResourceView = ();
+
+    fn create_vertex_buffer(&self, _data: &[VertexData]) -> Result<()> {
+        Ok(())
+    }
+
+    fn create_index_buffer(&self, _data: &[u32]) -> Result<()> {
+        Ok(())
+    }
+
+    fn create_dynamic_buffer(
+        &self,
+        _size: usize,
+        _bind_flag: D3D10_BIND_FLAG,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn write_buffer<'a>(
+        &self,
+        _buffer: &(),
+        _map_type: D3D10_MAP,
+        _offset: usize,
+        _slices: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn create_texture(
+        &self,
+        _allocator: &mut dyn TextureAllocator,
+        width: usize,
+        height: usize,
+        pixels: &[Color32],
+    ) -> Result<((usize, usize), ())> {
+        assert!(width > 0 && height > 0, "empty texture");
+        assert_eq!(pixels.len(), width * height, "texture data size");
+        Ok(((width, height), ()))
+    }
+
+    fn free_texture(
+        &self,
+        _allocator: &mut dyn TextureAllocator,
+        _texture: (usize, usize),
+    ) {
+    }
+
+    fn update_texture(
+        &self,
+        &(width, height): &(usize, usize),
+        region: D3D10_BOX,
+        data: &[u8],
+    ) {
+        assert!(
+            region.left <= region.right
+                && region.right as usize <= width
+                && region.top <= region.bottom
+                && region.bottom as usize <= height,
+            "texture update region",
+        );
+        let pixels = (region.right - region.left) as usize
+            * (region.bottom - region.top) as usize;
+        assert_eq!(data.len(), pixels * 4, "texture update data size");
+    }
+
+    fn draw_indexed(
+        &self,
+        _vertex_buffer: &(),
+        _index_buffer: &(),
+        _range: DrawRange,
+        _scissor_rect: RECT,
+        _srv: Option<&()>,
+    ) {
+    }
+}
@@ -0,0 +1,83 @@
+use windows::{
+    Win32::Graphics::{Direct3D10::*, Dxgi::Common::*},
+    core::Interface,
+};
+
+use crate::{ColorSpace, Renderer};
+
+impl Renderer {
+    /// The view the UI is drawn to instead of `render_target`.
+    ///
+    /// `egui` blends in gamma space, which render target views with an
+    /// `_SRGB` format prevent by converting to linear. If the texture of
+    /// such a view is `_TYPELESS`, or `_UNORM` as the back buffers of flip
+    /// model swap chains viewed as sRGB are, the UI is drawn through a
+    /// `_UNORM` view of the same texture instead. This is skipped when the
+    /// options convert to linear on purpose, and otherwise `render_target`
+    /// itself is returned.
+    ///
+    /// The view is created every frame rather than cached, so that it does
+    /// not keep back buffers alive across `IDXGISwapChain::ResizeBuffers`.
+    /// Creating a view is cheap next to drawing the UI.
+    pub(crate) fn gamma_render_target(
+        &self,
+        render_target: &ID3D10RenderTargetView,
+    ) -> ID3D10RenderTargetView {
+        if self.options.linear_blending
+            || self.options.target_color_space != ColorSpace::Srgb
+        {
+            return render_target.clone();
+        }
+        self.unorm_view(render_target)
+            .unwrap_or_else(|| render_target.clone())
+    }
+
+    /// A `_UNORM` view of the texture of `render_target`, if it is an
+    /// 8-bit `_SRGB` view of a `_TYPELESS` or `_UNORM` texture. `_SRGB`
+    /// textures cannot be viewed without the conversion.
+    fn unorm_view(
+        &self,
+        render_target: &ID3D10RenderTargetView,
+    ) -> Option<ID3D10RenderTargetView> {
+        let mut desc = D3D10_RENDER_TARGET_VIEW_DESC::default();
+        unsafe { render_target.GetDesc(&mut desc) };
+        let (typeless, unorm) = match desc.Format {
+            DXGI_FORMAT_R8G8B8A8_UNORM_SRGB => {
+                (DXGI_FORMAT_R8G8B8A8_TYPELESS, DXGI_FORMAT_R8G8B8A8_UNORM)
+            },
+            DXGI_FORMAT_B8G8R8A8_UNORM_SRGB => {
+                (DXGI_FORMAT_B8G8R8A8_TYPELESS, DXGI_FORMAT_B8G8R8A8_UNORM)
+            },
+            DXGI_FORMAT_B8G8R8X8_UNORM_SRGB => {
+                (DXGI_FORMAT_B8G8R8X8_TYPELESS, DXGI_FORMAT_B8G8R8X8_UNORM)
+            },
+            _ => return None,
+        };
+        let texture = unsafe { render_target.GetResource() }
+            .ok()?
+            .cast::<ID3D10Texture2D>()
+            .ok()?;
+        let mut texture_desc = D3D10_TEXTURE2D_DESC::default();
+        unsafe { texture.GetDesc(&mut texture_desc) };
+        if texture_desc.Format != typeless && texture_desc.Format != unorm {
+            return None;
+        }
+        let mut view = None;
+        let result = unsafe {
+            self.device.CreateRenderTargetView(
+                &texture,
+                Some(&D3D10_RENDER_TARGET_VIEW_DESC {
+                    Format: unorm,
+                    ..desc
+                }),
+                Some(&mut view),
+            )
+        };
+        if let Err(err) = result {
+            log::warn!(
+                "failed to create a view without sRGB conversion: {err}"
+            );
+        }
+        view
+    }
+}
@@ -0,0 +1,402 @@
+use std::{marker::PhantomData, mem};
+
+use egui::Pos2;
+
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+use crate::{
+    MeshData, VertexData,
+    backend::{Backend, DrawRange},
+};
+
+/// The vertex and index buffers the meshes of every frame are written to,
+/// instead of creating buffers for every mesh.
+///
+/// Both are `D3D10_USAGE_DYNAMIC` buffers used as rings: the meshes of a
+/// frame are appended after those of the previous frames with
+/// `D3D10_MAP_WRITE_NO_OVERWRITE`, which does not wait for the GPU, until
+/// they no longer fit, and the buffer is then discarded and written from the
+/// start again. Buffers grow to the next power of two when a frame does not
+/// fit at all.
+pub(crate) struct GeometryBuffers<B: Backend = ID3D10Device> {
+    vertices: RingBuffer<B, VertexData>,
+    indices: RingBuffer<B, u32>,
+}
+
+impl<B: Backend> Default for GeometryBuffers<B> {
+    fn default() -> Self {
+        Self {
+            vertices: RingBuffer::default(),
+            indices: RingBuffer::default(),
+        }
+    }
+}
+
+impl<B: Backend> GeometryBuffers<B> {
+    /// Write the vertices and indices of `meshes` in one map of each buffer,
+    /// and get the range of every mesh in [`GeometryBuffers::buffers`].
+    pub fn upload(
+        &mut self,
+        device: &B,
+        meshes: &[MeshData],
+    ) -> Result<Vec<DrawRange>> {
+        let vertex_count = meshes.iter().map(|mesh| mesh.vtx.len()).sum();
+        let index_count = meshes.iter().map(|mesh| mesh.idx.len()).sum();
+        if index_count == 0 {
+            return Ok(vec![DrawRange::default(); meshes.len()]);
+        }
+        let first_vertex = self.vertices.write(
+            device,
+            D3D10_BIND_VERTEX_BUFFER,
+            vertex_count,
+            meshes.iter().map(|mesh| mesh.vtx.as_slice()),
+        )?;
+        let first_index = self.indices.write(
+            device,
+            D3D10_BIND_INDEX_BUFFER,
+            index_count,
+            meshes.iter().map(|mesh| mesh.idx.as_slice()),
+        )?;
+        let mut base_vertex = first_vertex;
+        let mut start_index = first_index;
+        Ok(meshes
+            .iter()
+            .map(|mesh| {
+                let range = DrawRange {
+                    index_count: mesh.idx.len() as _,
+                    start_index: start_index as _,
+                    base_vertex: base_vertex as _,
+                };
+                base_vertex += mesh.vtx.len();
+                start_index += mesh.idx.len();
+                range
+            })
+            .collect())
+    }
+
+    /// The vertex and index buffers, once a frame was uploaded.
+    pub fn buffers(&self) -> Option<(&B::Buffer, &B::Buffer)> {
+        self.vertices
+            .buffer
+            .as_ref()
+            .zip(self.indices.buffer.as_ref())
+    }
+
+    /// Release the buffers, to be created again by the next frame.
+    pub fn release(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// A dynamic buffer of `T` written as a ring.
+struct RingBuffer<B: Backend, T> {
+    buffer: Option<B::Buffer>,
+    /// The size of the buffer, in elements.
+    capacity: usize,
+    /// The first element not written since the buffer was last discarded.
+    cursor: usize,
+    element: PhantomData<T>,
+}
+
+impl<B: Backend, T> Default for RingBuffer<B, T> {
+    fn default() -> Self {
+        Self {
+            buffer: None,
+            capacity: 0,
+            cursor: 0,
+            element: PhantomData,
+        }
+    }
+}
+
+impl<B: Backend, T: bytemuck::Pod> RingBuffer<B, T> {
+    /// The smallest buffer created, in elements, so that small UIs do not
+    /// grow it several times in their first frames.
+    const MIN_CAPACITY: usize = 1 << 12;
+
+    /// Write `slices`, holding `count` elements in total, one after the
+    /// other, and get the index of their first element.
+    fn write<'a>(
+        &mut self,
+        device: &B,
+        bind_flag: D3D10_BIND_FLAG,
+        count: usize,
+        slices: impl Iterator<Item = &'a [T]>,
+    ) -> Result<usize> {
+        if self.buffer.is_none() || count > self.capacity {
+            let capacity = count.next_power_of_two().max(Self::MIN_CAPACITY);
+            self.buffer = Some(device.create_dynamic_buffer(
+                capacity * mem::size_of::<T>(),
+                bind_flag,
+            )?);
+            self.capacity = capacity;
+            self.cursor = capacity;
+        }
+        let buffer = self.buffer.as_ref().unwrap();
+        // Elements before the cursor may still be read by the GPU.
+        let map_type = if self.cursor + count <= self.capacity {
+            D3D10_MAP_WRITE_NO_OVERWRITE
+        } else {
+            self.cursor = 0;
+            D3D10_MAP_WRITE_DISCARD
+        };
+        let first = self.cursor;
+        device.write_buffer(
+            buffer,
+            map_type,
+            first * mem::size_of::<T>(),
+            slices.map(bytemuck::cast_slice),
+        )?;
+        self.cursor += count;
+        Ok(first)
+    }
+}
+
+/// The buffers of the quads drawn over whole regions, such as clears and
+/// blits: a dynamic vertex buffer, rewritten for every quad as their colors
+/// differ, and the indices of its two triangles. They are created once with
+/// the renderer.
+pub(crate) struct QuadBuffers<B: Backend = ID3D10Device> {
+    vertices: B::Buffer,
+    indices: B::Buffer,
+}
+
+impl<B: Backend> QuadBuffers<B> {
+    pub fn new(device: &B) -> Result<Self> {
+        Ok(Self {
+            vertices: device.create_dynamic_buffer(
+                4 * mem::size_of::<VertexData>(),
+                D3D10_BIND_VERTEX_BUFFER,
+            )?,
+            indices: device.create_index_buffer(&[0, 1, 2, 2, 1, 3])?,
+        })
+    }
+
+    /// Write a quad of `color` covering the viewport, with texture
+    /// coordinates from `(0, 0)` at the top left to `(1, 1)` at the bottom
+    /// right, and get the vertex and index buffers to draw its 6 indices
+    /// from.
+    pub fn write(
+        &self,
+        device: &B,
+        color: [f32; 4],
+    ) -> Result<(&B::Buffer, &B::Buffer)> {
+        let vertex = |x, y| VertexData {
+            pos: Pos2::new(x, y),
+            uv: Pos2::new((x + 1.) / 2., (1. - y) / 2.),
+            color,
+        };
+        let vertices = [
+            vertex(-1., 1.),
+            vertex(1., 1.),
+            vertex(-1., -1.),
+            vertex(1., -1.),
+        ];
+        device.write_buffer(
+            &self.vertices,
+            D3D10_MAP_WRITE_DISCARD,
+            0,
+            [bytemuck::cast_slice(&vertices)],
+        )?;
+        Ok((&self.vertices, &self.indices))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::recording::*;
+
+    const VERTEX_SIZE: usize = mem::size_of::<VertexData>();
+    const INDEX_SIZE: usize = mem::size_of::<u32>();
+
+    fn mesh(vertices: usize, indices: usize) -> MeshData {
+        MeshData {
+            vtx: vec![bytemuck::Zeroable::zeroed(); vertices],
+            idx: vec![0; indices],
+            tex: egui::TextureId::Managed(0),
+            clip_rect: egui::Rect::EVERYTHING,
+            callback: None,
+        }
+    }
+
+    fn create(size: usize, bind_flag: D3D10_BIND_FLAG) -> Call {
+        Call::CreateDynamicBuffer { size, bind_flag }
+    }
+
+    fn write(map_type: D3D10_MAP, offset: usize, size: usize) -> Call {
+        Call::WriteBuffer {
+            map_type,
+            offset,
+            size,
+        }
+    }
+
+    #[test]
+    fn first_frame_creates_buffers() {
+        let backend = RecordingBackend::default();
+        let mut geometry = GeometryBuffers::default();
+        let ranges = geometry
+            .upload(&backend, &[mesh(4, 6), mesh(3, 3)])
+            .unwrap();
+        assert_eq!(
+            ranges,
+            [
+                DrawRange {
+                    index_count: 6,
+                    start_index: 0,
+                    base_vertex: 0,
+                },
+                DrawRange {
+                    index_count: 3,
+                    start_index: 6,
+                    base_vertex: 4,
+                },
+            ]
+        );
+        assert_eq!(
+            backend.take_calls(),
+            [
+                create(4096 * VERTEX_SIZE, D3D10_BIND_VERTEX_BUFFER),
+                write(D3D10_MAP_WRITE_DISCARD, 0, 7 * VERTEX_SIZE),
+                create(4096 * INDEX_SIZE, D3D10_BIND_INDEX_BUFFER),
+                write(D3D10_MAP_WRITE_DISCARD, 0, 9 * INDEX_SIZE),
+            ]
+        );
+        assert_eq!(
+            geometry.buffers(),
+            Some((&(4096 * VERTEX_SIZE), &(4096 * INDEX_SIZE)))
+        );
+    }
+
+    #[test]
+    fn frames_are_appended_without_overwriting() {
+        let backend = RecordingBackend::default();
+        let mut geometry = GeometryBuffers::default();
+        geometry.upload(&backend, &[mesh(4, 6)]).unwrap();
+        backend.take_calls();
+
+        let ranges = geometry.upload(&backend, &[mesh(3, 3)]).unwrap();
+        assert_eq!(
+            ranges,
+            [DrawRange {
+                index_count: 3,
+                start_index: 6,
+                base_vertex: 4,
+            }]
+        );
+        assert_eq!(
+            backend.take_calls(),
+            [
+                write(
+                    D3D10_MAP_WRITE_NO_OVERWRITE,
+                    4 * VERTEX_SIZE,
+                    3 * VERTEX_SIZE
+                ),
+                write(
+                    D3D10_MAP_WRITE_NO_OVERWRITE,
+                    6 * INDEX_SIZE,
+                    3 * INDEX_SIZE
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn full_buffers_are_discarded_and_written_from_the_start() {
+        let backend = RecordingBackend::default();
+        let mut geometry = GeometryBuffers::default();
+        geometry.upload(&backend, &[mesh(3000, 3000)]).unwrap();
+        backend.take_calls();
+
+        let ranges = geometry.upload(&backend, &[mesh(2000, 2000)]).unwrap();
+        assert_eq!(
+            ranges,
+            [DrawRange {
+                index_count: 2000,
+                start_index: 0,
+                base_vertex: 0,
+            }]
+        );
+        assert_eq!(
+            backend.take_calls(),
+            [
+                write(D3D10_MAP_WRITE_DISCARD, 0, 2000 * VERTEX_SIZE),
+                write(D3D10_MAP_WRITE_DISCARD, 0, 2000 * INDEX_SIZE),
+            ]
+        );
+
+        // The cursor was reset, so the next frame is appended again.
+        geometry.upload(&backend, &[mesh(1, 1)]).unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [
+                write(
+                    D3D10_MAP_WRITE_NO_OVERWRITE,
+                    2000 * VERTEX_SIZE,
+                    VERTEX_SIZE
+                ),
+                write(
+                    D3D10_MAP_WRITE_NO_OVERWRITE,
+                    2000 * INDEX_SIZE,
+                    INDEX_SIZE
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn buffers_grow_to_the_next_power_of_two() {
+        let backend = RecordingBackend::default();
+        let mut geometry = GeometryBuffers::default();
+        geometry.upload(&backend, &[mesh(4, 6)]).unwrap();
+        backend.take_calls();
+
+        geometry.upload(&backend, &[mesh(5000, 6)]).unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [
+                create(8192 * VERTEX_SIZE, D3D10_BIND_VERTEX_BUFFER),
+                write(D3D10_MAP_WRITE_DISCARD, 0, 5000 * VERTEX_SIZE),
+                write(
+                    D3D10_MAP_WRITE_NO_OVERWRITE,
+                    6 * INDEX_SIZE,
+                    6 * INDEX_SIZE
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn frames_without_indices_write_nothing() {
+        let backend = RecordingBackend::default();
+        let mut geometry = GeometryBuffers::default();
+        let ranges = geometry.upload(&backend, &[mesh(0, 0)]).unwrap();
+        assert_eq!(ranges, [DrawRange::default()]);
+        assert_eq!(backend.take_calls(), []);
+        assert_eq!(geometry.buffers(), None);
+    }
+
+    #[test]
+    fn quads_are_rewritten_without_creating_buffers() {
+        let backend = RecordingBackend::default();
+        let quad = QuadBuffers::new(&backend).unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [
+                create(4 * VERTEX_SIZE, D3D10_BIND_VERTEX_BUFFER),
+                Call::CreateIndexBuffer(6),
+            ]
+        );
+        for color in [[0.; 4], [1.; 4]] {
+            assert_eq!(
+                quad.write(&backend, color).unwrap(),
+                (&(4 * VERTEX_SIZE), &6)
+            );
+        }
+        assert_eq!(
+            backend.take_calls(),
+            [write(D3D10_MAP_WRITE_DISCARD, 0, 4 * VERTEX_SIZE); 2]
+        );
+    }
+}
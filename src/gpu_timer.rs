@@ -0,0 +1,136 @@
+use std::mem;
+
+use windows::{
+    Win32::{Foundation::S_OK, Graphics::Direct3D10::*},
+    core::{Interface, Result},
+};
+
+/// The number of frames measured at once. Results are read this many frames
+/// after they were issued, so the CPU never waits for the GPU.
+const FRAMES_IN_FLIGHT: usize = 3;
+
+/// Measures the GPU time of frames with timestamp queries, along with `T`,
+/// data about each frame that is returned with its measurement.
+pub(crate) struct GpuTimer<T> {
+    slots: [(FrameQueries, Option<T>); FRAMES_IN_FLIGHT],
+    next: usize,
+}
+
+struct FrameQueries {
+    disjoint: ID3D10Query,
+    start: ID3D10Query,
+    end: ID3D10Query,
+}
+
+/// Ends the measurement of a frame when dropped.
+pub(crate) struct FrameTimer {
+    disjoint: ID3D10Query,
+    end: ID3D10Query,
+}
+
+impl<T> GpuTimer<T> {
+    pub fn new(device: &ID3D10Device) -> Result<Self> {
+        Ok(Self {
+            slots: [
+                (FrameQueries::new(device)?, None),
+                (FrameQueries::new(device)?, None),
+                (FrameQueries::new(device)?, None),
+            ],
+            next: 0,
+        })
+    }
+
+    /// Start measuring a frame with `data`. Returns the timer of the frame,
+    /// and the GPU time in milliseconds and the data of the frame measured
+    /// [`FRAMES_IN_FLIGHT`] frames earlier, if it could be measured. Returns
+    /// `None` if the GPU is too far behind to measure another frame.
+    pub fn begin(&mut self, data: T) -> Option<(FrameTimer, Option<(f32, T)>)> {
+        let (queries, pending) = &mut self.slots[self.next];
+        let measured = match pending {
+            Some(_) => {
+                let gpu_time = queries.read()?;
+                gpu_time.zip(pending.take())
+            },
+            None => None,
+        };
+        unsafe {
+            queries.disjoint.Begin();
+            queries.start.End();
+        }
+        *pending = Some(data);
+        let timer = FrameTimer {
+            disjoint: queries.disjoint.clone(),
+            end: queries.end.clone(),
+        };
+        self.next = (self.next + 1) % FRAMES_IN_FLIGHT;
+        Some((timer, measured))
+    }
+
+    /// The data of the frame measured last.
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        let last = (self.next + FRAMES_IN_FLIGHT - 1) % FRAMES_IN_FLIGHT;
+        self.slots[last].1.as_mut()
+    }
+}
+
+impl FrameQueries {
+    fn new(device: &ID3D10Device) -> Result<Self> {
+        let create = |query| {
+            let mut result = None;
+            unsafe {
+                device.CreateQuery(
+                    &D3D10_QUERY_DESC {
+                        Query: query,
+                        MiscFlags: 0,
+                    },
+                    Some(&mut result),
+                )
+            }?;
+            Result::Ok(result.unwrap())
+        };
+        Ok(Self {
+            disjoint: create(D3D10_QUERY_TIMESTAMP_DISJOINT)?,
+            start: create(D3D10_QUERY_TIMESTAMP)?,
+            end: create(D3D10_QUERY_TIMESTAMP)?,
+        })
+    }
+
+    /// The GPU time of the frame in milliseconds, `Some(None)` if it could
+    /// not be measured, or `None` if the results are not available yet.
+    fn read(&self) -> Option<Option<f32>> {
+        let disjoint: D3D10_QUERY_DATA_TIMESTAMP_DISJOINT =
+            get_data(&self.disjoint)?;
+        let start: u64 = get_data(&self.start)?;
+        let end: u64 = get_data(&self.end)?;
+        if disjoint.Disjoint.as_bool() || disjoint.Frequency == 0 {
+            return Some(None);
+        }
+        let ticks = end.saturating_sub(start);
+        Some(Some(ticks as f32 * 1000. / disjoint.Frequency as f32))
+    }
+}
+
+impl Drop for FrameTimer {
+    fn drop(&mut self) {
+        unsafe {
+            self.end.End();
+            self.disjoint.End();
+        }
+    }
+}
+
+/// The result of `query` if it is available, without flushing the device.
+fn get_data<T: Default>(query: &ID3D10Asynchronous) -> Option<T> {
+    let mut data = T::default();
+    // `GetData` returns `S_FALSE` while the result is not available, which
+    // the wrapper of `windows` treats as success.
+    let result = unsafe {
+        (Interface::vtable(query).GetData)(
+            Interface::as_raw(query),
+            &mut data as *mut T as _,
+            mem::size_of::<T>() as _,
+            D3D10_ASYNC_GETDATA_DONOTFLUSH.0 as _,
+        )
+    };
+    (result == S_OK).then_some(data)
+}
@@ -0,0 +1,98 @@
+use windows::{
+    Win32::{
+        Foundation::{E_FAIL, HWND},
+        Graphics::{
+            Direct3D10::*,
+            Dxgi::{Common::*, *},
+            Gdi::{MONITOR_DEFAULTTONEAREST, MonitorFromWindow},
+        },
+    },
+    core::{Error, Interface, Result},
+};
+
+use crate::{ColorSpace, RendererOptions};
+
+/// The color space and luminance of a monitor, as reported by
+/// `IDXGIOutput6::GetDesc1`, to configure the renderer for HDR swap chains.
+///
+/// The renderer has no tone mapping, so the luminance is only reported:
+/// on an scRGB swap chain, the UI is drawn at the SDR reference white of
+/// 80 nits.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OutputHdrInfo {
+    /// The color space of the signal sent to the monitor:
+    /// `DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020` when HDR is enabled in
+    /// the display settings of Windows.
+    pub color_space: DXGI_COLOR_SPACE_TYPE,
+    /// The number of bits per color channel of the signal.
+    pub bits_per_color: u32,
+    /// The minimum luminance of the monitor, in nits.
+    pub min_luminance: f32,
+    /// The peak luminance of the monitor on a small area, in nits.
+    pub max_luminance: f32,
+    /// The peak luminance of the monitor on the whole screen, in nits.
+    pub max_full_frame_luminance: f32,
+}
+
+impl OutputHdrInfo {
+    /// Query the monitor `swap_chain` is mostly displayed on.
+    pub fn for_swap_chain(swap_chain: &IDXGISwapChain) -> Result<Self> {
+        Self::from_output(&unsafe { swap_chain.GetContainingOutput() }?)
+    }
+
+    /// Query the monitor `hwnd` is mostly displayed on, among the outputs of
+    /// the adapter of `device`. Returns `E_FAIL` if that monitor is
+    /// connected to another adapter.
+    pub fn for_window(device: &ID3D10Device, hwnd: HWND) -> Result<Self> {
+        let monitor =
+            unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) };
+        let adapter = unsafe { device.cast::<IDXGIDevice>()?.GetAdapter() }?;
+        for index in 0.. {
+            let Ok(output) = (unsafe { adapter.EnumOutputs(index) }) else {
+                break;
+            };
+            if unsafe { output.GetDesc() }?.Monitor == monitor {
+                return Self::from_output(&output);
+            }
+        }
+        log::error!(
+            "the monitor of the window is not an output of the device."
+        );
+        Err(Error::from_hresult(E_FAIL))
+    }
+
+    /// Read the description of `output`, which needs Windows 10 version
+    /// 1703 or later for `IDXGIOutput6`.
+    fn from_output(output: &IDXGIOutput) -> Result<Self> {
+        let desc = unsafe { output.cast::<IDXGIOutput6>()?.GetDesc1() }?;
+        Ok(Self {
+            color_space: desc.ColorSpace,
+            bits_per_color: desc.BitsPerColor,
+            min_luminance: desc.MinLuminance,
+            max_luminance: desc.MaxLuminance,
+            max_full_frame_luminance: desc.MaxFullFrameLuminance,
+        })
+    }
+
+    /// Whether HDR is enabled for the monitor.
+    pub fn is_hdr(&self) -> bool {
+        self.color_space == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020
+    }
+
+    /// Set the [`RendererOptions::target_color_space`] for a render target of
+    /// `format` on this monitor: [`ColorSpace::Linear`] for scRGB
+    /// (`DXGI_FORMAT_R16G16B16A16_FLOAT`) render targets while HDR is
+    /// enabled, and [`ColorSpace::Srgb`] otherwise.
+    pub fn configure(
+        &self,
+        options: &mut RendererOptions,
+        format: DXGI_FORMAT,
+    ) {
+        options.target_color_space =
+            if self.is_hdr() && format == DXGI_FORMAT_R16G16B16A16_FLOAT {
+                ColorSpace::Linear
+            } else {
+                ColorSpace::Srgb
+            };
+    }
+}
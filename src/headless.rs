@@ -0,0 +1,169 @@
+use windows::{
+    Win32::Graphics::{Direct3D10::*, Dxgi::Common::*},
+    core::Result,
+};
+
+use crate::{
+    DeviceOptions, Renderer, RendererOutput, compat, create_device, readback,
+};
+
+/// Renders `egui` output to an offscreen texture and reads it back as an
+/// [`egui::ColorImage`], without a window or swap chain, for snapshot tests
+/// of UI crates on Windows CI machines, which usually only have WARP.
+///
+/// With the `kittest` feature, it implements the `TestRenderer` trait of
+/// `egui_kittest`, to render its snapshot tests:
+///
+/// ```ignore
+/// let renderer = egui_directx10::HeadlessRenderer::new().unwrap();
+/// let mut harness = egui_kittest::Harness::builder()
+///     .renderer(renderer)
+///     .build_ui(|ui| {
+///         ui.label("Hello");
+///     });
+/// harness.snapshot("hello");
+/// ```
+pub struct HeadlessRenderer {
+    device: ID3D10Device,
+    renderer: Renderer,
+    target: Option<(ID3D10Texture2D, ID3D10RenderTargetView)>,
+    clear_color: [f32; 4],
+}
+
+impl HeadlessRenderer {
+    /// Create a [`Renderer`] on a new device with [`create_device`], which
+    /// falls back to WARP if there is no hardware device.
+    pub fn new() -> Result<Self> {
+        let (device, _) = create_device(&DeviceOptions::default())?;
+        let renderer = Renderer::new(&device)?;
+        Ok(Self::with_renderer(&device, renderer))
+    }
+
+    /// Render with `renderer`, which must have been created on `device`, for
+    /// example to test with other [`RendererOptions`](crate::RendererOptions).
+    pub fn with_renderer(device: &ID3D10Device, renderer: Renderer) -> Self {
+        Self {
+            device: device.clone(),
+            renderer,
+            target: None,
+            clear_color: [0.; 4],
+        }
+    }
+
+    /// The [`Renderer`], for example to register user textures.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// Set the color the texture is cleared to before drawing the UI.
+    /// Defaults to transparent black.
+    pub fn set_clear_color(&mut self, clear_color: [f32; 4]) {
+        self.clear_color = clear_color;
+    }
+
+    /// Apply the texture changes of a frame of `egui`, which
+    /// [`HeadlessRenderer::render`] does not do itself.
+    pub fn handle_delta(&mut self, delta: &egui::TexturesDelta) -> Result<()> {
+        self.renderer.update_textures(&self.device, delta.clone())
+    }
+
+    /// Render the shapes of `output` at the size of the viewport of `ctx`,
+    /// and read the result back. Colors are premultiplied, as in the render
+    /// target. The textures delta of `output` must have been passed to
+    /// [`HeadlessRenderer::handle_delta`] first.
+    pub fn render(
+        &mut self,
+        ctx: &egui::Context,
+        output: &egui::FullOutput,
+    ) -> Result<egui::ColorImage> {
+        let size = compat::viewport_rect(ctx).size() * output.pixels_per_point;
+        let size = (
+            (size.x.round() as u32).max(1),
+            (size.y.round() as u32).max(1),
+        );
+        let (texture, render_target) = self.target(size)?;
+        unsafe {
+            self.device
+                .ClearRenderTargetView(&render_target, &self.clear_color)
+        };
+        self.renderer.render(
+            &self.device,
+            &render_target,
+            ctx,
+            RendererOutput {
+                textures_delta: Default::default(),
+                shapes: output.shapes.clone(),
+                pixels_per_point: output.pixels_per_point,
+            },
+        )?;
+        readback::read_texture(&self.device, &texture)
+    }
+
+    /// The texture rendered to and its view, recreated when the size of
+    /// the viewport changes.
+    fn target(
+        &mut self,
+        size: (u32, u32),
+    ) -> Result<(ID3D10Texture2D, ID3D10RenderTargetView)> {
+        if let Some((texture, render_target)) = &self.target {
+            let mut desc = D3D10_TEXTURE2D_DESC::default();
+            unsafe { texture.GetDesc(&mut desc) };
+            if (desc.Width, desc.Height) == size {
+                return Ok((texture.clone(), render_target.clone()));
+            }
+        }
+        let desc = D3D10_TEXTURE2D_DESC {
+            Width: size.0,
+            Height: size.1,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D10_USAGE_DEFAULT,
+            BindFlags: D3D10_BIND_RENDER_TARGET.0 as _,
+            CPUAccessFlags: 0,
+            MiscFlags: 0,
+        };
+        let mut render_target = None;
+        let texture = unsafe {
+            let texture = self.device.CreateTexture2D(&desc, None)?;
+            self.device.CreateRenderTargetView(
+                &texture,
+                None,
+                Some(&mut render_target),
+            )?;
+            texture
+        };
+        let target = (texture, render_target.unwrap());
+        self.target = Some(target.clone());
+        Ok(target)
+    }
+}
+
+#[cfg(feature = "kittest")]
+impl egui_kittest::TestRenderer for HeadlessRenderer {
+    fn handle_delta(&mut self, delta: &egui::TexturesDelta) {
+        if let Err(err) = HeadlessRenderer::handle_delta(self, delta) {
+            log::error!("failed to update the textures of egui: {err}");
+        }
+    }
+
+    fn render(
+        &mut self,
+        ctx: &egui::Context,
+        output: &egui::FullOutput,
+    ) -> std::result::Result<image::RgbaImage, String> {
+        let image = HeadlessRenderer::render(self, ctx, output)
+            .map_err(|err| err.to_string())?;
+        let [width, height] = image.size;
+        image::RgbaImage::from_raw(
+            width as _,
+            height as _,
+            image.as_raw().to_vec(),
+        )
+        .ok_or_else(|| "the rendered image has an invalid size".to_owned())
+    }
+}
@@ -0,0 +1,439 @@
+use std::{
+    any::Any,
+    collections::HashSet,
+    mem,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+    },
+    thread::{self, JoinHandle},
+};
+
+use egui::{Color32, ColorImage, TextureId};
+use windows::{
+    Win32::{
+        Foundation::{E_FAIL, E_INVALIDARG},
+        Graphics::{Direct3D10::*, Dxgi::Common::*},
+    },
+    core::{Error, Result},
+};
+
+use crate::{Renderer, compat};
+
+/// A function producing an image on a worker thread, typically decoding a
+/// file with the `image` crate.
+type Decode =
+    Box<dyn FnOnce() -> std::result::Result<ColorImage, String> + Send>;
+
+/// A decode queued by [`ImageLoader::load`].
+struct Job {
+    tid: TextureId,
+    decode: Decode,
+    max_size: Option<usize>,
+}
+
+/// A job a worker has finished.
+struct Done {
+    tid: TextureId,
+    image: std::result::Result<ColorImage, String>,
+}
+
+/// An image [`ImageLoader::poll`] has uploaded, or failed to.
+#[derive(Debug)]
+pub struct LoadedImage {
+    /// The texture returned by [`ImageLoader::load`].
+    pub texture_id: TextureId,
+    /// The size of the uploaded texture in pixels, after downscaling, or
+    /// the error of the decode function or of the upload. Panics of the
+    /// decode function are reported as errors too. The placeholder stays
+    /// registered on errors.
+    pub result: std::result::Result<[usize; 2], String>,
+}
+
+/// Decodes and downscales large images on worker threads, so that UI code
+/// never blocks on them.
+///
+/// [`ImageLoader::load`] immediately registers a transparent placeholder
+/// user texture and returns its [`TextureId`], which can be used in `egui`
+/// right away. [`ImageLoader::poll`], called on the render thread, uploads
+/// the finished images and swaps them in under the same `TextureId`:
+///
+/// ```no_run
+/// # fn f(device: &windows::Win32::Graphics::Direct3D10::ID3D10Device, renderer: &mut egui_directx10::Renderer, bytes: Vec<u8>, decode: fn(&[u8]) -> Result<egui::ColorImage, String>) -> windows::core::Result<()> {
+/// let mut loader = egui_directx10::ImageLoader::new(device, 2)?;
+/// let tid = loader.load(renderer, move || decode(&bytes));
+/// // Every frame, before rendering:
+/// for image in loader.poll(renderer) {
+///     // `image.texture_id` now shows the decoded image.
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The textures are user textures: unregister them with
+/// [`Renderer::unregister_user_texture`] when they are no longer needed,
+/// even while they are loading. Dropping the loader waits for the decodes
+/// in progress and discards the queued ones.
+pub struct ImageLoader {
+    device: ID3D10Device,
+    placeholder: ID3D10ShaderResourceView,
+    max_size: Option<usize>,
+    jobs: Option<Sender<Job>>,
+    cancelled: Arc<AtomicBool>,
+    done: Receiver<Done>,
+    pending: HashSet<TextureId>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ImageLoader {
+    /// Create a loader uploading to `device`, which must be the device of
+    /// the renderers passed to it, with `worker_count` threads. Fails if a
+    /// thread cannot be spawned.
+    pub fn new(device: &ID3D10Device, worker_count: usize) -> Result<Self> {
+        let placeholder = create_texture(
+            device,
+            &compat::color_image([1, 1], vec![Color32::TRANSPARENT]),
+        )?;
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (done_sender, done) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut loader = Self {
+            device: device.clone(),
+            placeholder,
+            max_size: None,
+            jobs: Some(jobs),
+            cancelled: cancelled.clone(),
+            done,
+            pending: HashSet::new(),
+            workers: Vec::with_capacity(worker_count.max(1)),
+        };
+        for index in 0..worker_count.max(1) {
+            let job_receiver = job_receiver.clone();
+            let done_sender = done_sender.clone();
+            let cancelled = cancelled.clone();
+            // On errors, dropping the loader stops the workers spawned so far.
+            let worker = thread::Builder::new()
+                .name(format!("egui-directx10 image loader {index}"))
+                .spawn(move || work(&job_receiver, &done_sender, &cancelled))
+                .map_err(|err| {
+                    log::error!(
+                        "failed to spawn an image loader thread: {err}"
+                    );
+                    Error::from_hresult(E_FAIL)
+                })?;
+            loader.workers.push(worker);
+        }
+        Ok(loader)
+    }
+
+    /// Downscale images whose width or height exceeds `max_size` pixels,
+    /// keeping their aspect ratio, on the worker threads. This applies to
+    /// the images loaded afterwards. `None` keeps the decoded size, which
+    /// still has to fit the texture size limit of the device.
+    pub fn set_max_size(&mut self, max_size: Option<usize>) {
+        self.max_size = max_size;
+    }
+
+    /// Queue `decode` to run on a worker thread, and get the `TextureId`
+    /// the image will be shown under. Until it is uploaded by
+    /// [`ImageLoader::poll`], the texture is a transparent placeholder.
+    pub fn load(
+        &mut self,
+        renderer: &mut Renderer,
+        decode: impl FnOnce() -> std::result::Result<ColorImage, String>
+        + Send
+        + 'static,
+    ) -> TextureId {
+        let tid = renderer.register_user_texture(self.placeholder.clone());
+        self.pending.insert(tid);
+        let job = Job {
+            tid,
+            decode: Box::new(decode),
+            max_size: self.max_size,
+        };
+        if let Some(jobs) = &self.jobs {
+            // The workers only exit once this sender is dropped.
+            let _ = jobs.send(job);
+        }
+        tid
+    }
+
+    /// Whether the image of `tid` is still being decoded.
+    pub fn is_pending(&self, tid: TextureId) -> bool {
+        self.pending.contains(&tid)
+    }
+
+    /// The number of images still being decoded.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Upload the images decoded since the last call, on the render thread,
+    /// and return them. Images whose texture was unregistered in the
+    /// meantime are dropped without being uploaded or returned. Failed
+    /// uploads, such as of empty images, are returned as errors like failed
+    /// decodes.
+    pub fn poll(&mut self, renderer: &mut Renderer) -> Vec<LoadedImage> {
+        let mut loaded = Vec::new();
+        for Done { tid, image } in self.done.try_iter() {
+            self.pending.remove(&tid);
+            if renderer.texture_pool.textures().get_srv(tid).is_none() {
+                continue;
+            }
+            let result = image.and_then(|image| {
+                let srv = create_texture(&self.device, &image)
+                    .map_err(|err| err.to_string())?;
+                renderer
+                    .texture_pool
+                    .textures_mut()
+                    .replace_user_texture(tid, srv);
+                Ok(image.size)
+            });
+            if let Err(err) = &result {
+                log::warn!("failed to load the image of {tid:?}: {err}");
+            }
+            loaded.push(LoadedImage {
+                texture_id: tid,
+                result,
+            });
+        }
+        loaded
+    }
+}
+
+impl Drop for ImageLoader {
+    fn drop(&mut self) {
+        // Skip the queued jobs, and disconnect the workers so that they exit
+        // once the queue is empty.
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.jobs = None;
+        for worker in mem::take(&mut self.workers) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Run the jobs of `jobs` until the loader is dropped.
+fn work(
+    jobs: &Mutex<Receiver<Job>>,
+    done: &Sender<Done>,
+    cancelled: &AtomicBool,
+) {
+    loop {
+        let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+        let Ok(Job {
+            tid,
+            decode,
+            max_size,
+        }) = job
+        else {
+            return;
+        };
+        if cancelled.load(Ordering::Relaxed) {
+            continue;
+        }
+        // A panicking decoder must neither leave its image pending forever
+        // nor take the worker down with it.
+        let image = panic::catch_unwind(AssertUnwindSafe(|| {
+            decode().and_then(check_size).map(|image| match max_size {
+                Some(max_size) => downscale(image, max_size),
+                None => image,
+            })
+        }))
+        .unwrap_or_else(|payload| Err(panic_message(payload.as_ref())));
+        if done.send(Done { tid, image }).is_err() {
+            return;
+        }
+    }
+}
+
+/// Describe the panic of a decode function.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => message,
+        None => payload
+            .downcast_ref::<String>()
+            .map_or("unknown panic", String::as_str),
+    };
+    format!("the decode function panicked: {message}")
+}
+
+/// Reject images whose pixels do not match their size, which custom
+/// decoders may produce, and empty images, which cannot be uploaded.
+fn check_size(image: ColorImage) -> std::result::Result<ColorImage, String> {
+    let [width, height] = image.size;
+    if width == 0 || height == 0 {
+        return Err(format!("the image is empty ({width}x{height})"));
+    }
+    if width.checked_mul(height) != Some(image.pixels.len()) {
+        return Err(format!(
+            "the image has {} pixels instead of {width}x{height}",
+            image.pixels.len()
+        ));
+    }
+    Ok(image)
+}
+
+/// Shrink `image` to fit in `max_size` pixels on both sides, averaging the
+/// source pixels each destination pixel covers. The pixels of `egui` are
+/// premultiplied, so averaging them does not darken transparent edges.
+fn downscale(image: ColorImage, max_size: usize) -> ColorImage {
+    let [width, height] = image.size;
+    let max_size = max_size.max(1);
+    if width <= max_size && height <= max_size {
+        return image;
+    }
+    let scale = max_size as f64 / width.max(height) as f64;
+    let new_width =
+        ((width as f64 * scale).round() as usize).clamp(1, max_size);
+    let new_height =
+        ((height as f64 * scale).round() as usize).clamp(1, max_size);
+    let mut pixels = Vec::with_capacity(new_width * new_height);
+    for y in 0..new_height {
+        let y0 = y * height / new_height;
+        let y1 = ((y + 1) * height / new_height).max(y0 + 1);
+        for x in 0..new_width {
+            let x0 = x * width / new_width;
+            let x1 = ((x + 1) * width / new_width).max(x0 + 1);
+            // Large enough for any number of pixels a `Vec` can hold.
+            let mut sum = [0u64; 4];
+            for row in image.pixels[y0 * width..y1 * width].chunks_exact(width)
+            {
+                for pixel in &row[x0..x1] {
+                    for (sum, channel) in sum.iter_mut().zip(pixel.to_array()) {
+                        *sum += channel as u64;
+                    }
+                }
+            }
+            let count = ((y1 - y0) * (x1 - x0)) as u64;
+            let [r, g, b, a] = sum.map(|sum| ((sum + count / 2) / count) as u8);
+            pixels.push(Color32::from_rgba_premultiplied(r, g, b, a));
+        }
+    }
+    compat::color_image([new_width, new_height], pixels)
+}
+
+/// Upload `image` to an immutable texture, as it is never updated. Returns
+/// `E_INVALIDARG` if its pixels do not match its size.
+fn create_texture(
+    device: &ID3D10Device,
+    image: &ColorImage,
+) -> Result<ID3D10ShaderResourceView> {
+    let [width, height] = image.size;
+    if width.checked_mul(height) != Some(image.pixels.len()) {
+        log::error!(
+            "the image has {} pixels instead of {width}x{height}.",
+            image.pixels.len()
+        );
+        return Err(Error::from_hresult(E_INVALIDARG));
+    }
+    let desc = D3D10_TEXTURE2D_DESC {
+        Width: width as _,
+        Height: height as _,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D10_USAGE_IMMUTABLE,
+        BindFlags: D3D10_BIND_SHADER_RESOURCE.0 as _,
+        ..Default::default()
+    };
+    let data = D3D10_SUBRESOURCE_DATA {
+        pSysMem: image.pixels.as_ptr() as _,
+        SysMemPitch: (width * mem::size_of::<Color32>()) as u32,
+        SysMemSlicePitch: 0,
+    };
+    let texture = unsafe { device.CreateTexture2D(&desc, Some(&data)) }?;
+    let mut srv = None;
+    unsafe { device.CreateShaderResourceView(&texture, None, Some(&mut srv)) }?;
+    Ok(srv.unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_panics_are_reported() {
+        let (jobs, job_receiver) = mpsc::channel();
+        let (done_sender, done) = mpsc::channel();
+        let decodes: [Decode; 2] = [
+            Box::new(|| panic!("corrupt file")),
+            Box::new(|| Ok(compat::color_image([1, 1], vec![Color32::RED]))),
+        ];
+        for (index, decode) in decodes.into_iter().enumerate() {
+            jobs.send(Job {
+                tid: TextureId::User(index as _),
+                decode,
+                max_size: None,
+            })
+            .unwrap();
+        }
+        drop(jobs);
+        work(
+            &Mutex::new(job_receiver),
+            &done_sender,
+            &AtomicBool::new(false),
+        );
+        let done = done.try_iter().collect::<Vec<_>>();
+        assert_eq!(done.len(), 2);
+        assert_eq!(
+            done[0].image.as_ref().unwrap_err(),
+            "the decode function panicked: corrupt file"
+        );
+        assert_eq!(done[1].image.as_ref().unwrap().size, [1, 1]);
+    }
+
+    #[test]
+    fn malformed_images_are_rejected() {
+        let pixels = vec![Color32::WHITE; 6];
+        assert!(
+            check_size(compat::color_image([2, 3], pixels.clone())).is_ok()
+        );
+        let mut image = compat::color_image([2, 3], pixels);
+        image.pixels.pop();
+        assert!(check_size(image).is_err());
+        assert!(check_size(compat::color_image([0, 3], Vec::new())).is_err());
+    }
+
+    #[test]
+    fn downscale_keeps_aspect_ratio() {
+        let image = compat::color_image([400, 100], vec![Color32::RED; 40_000]);
+        let image = downscale(image, 100);
+        assert_eq!(image.size, [100, 25]);
+        assert_eq!(image.pixels.len(), 100 * 25);
+        assert!(image.pixels.iter().all(|&pixel| pixel == Color32::RED));
+    }
+
+    #[test]
+    fn downscale_averages_pixels() {
+        let pixels = [Color32::WHITE, Color32::TRANSPARENT].repeat(2);
+        let image = downscale(compat::color_image([2, 2], pixels), 1);
+        assert_eq!(image.size, [1, 1]);
+        assert_eq!(
+            image.pixels[0],
+            Color32::from_rgba_premultiplied(128, 128, 128, 128)
+        );
+    }
+
+    #[test]
+    fn downscale_averages_many_pixels() {
+        // More white pixels than the sum of their channels fits in a `u32`.
+        let size = 4200;
+        let pixels = vec![Color32::WHITE; size * size];
+        let image = downscale(compat::color_image([size, size], pixels), 1);
+        assert_eq!(image.pixels, [Color32::WHITE]);
+    }
+
+    #[test]
+    fn small_images_are_not_downscaled() {
+        let image = compat::color_image([3, 2], vec![Color32::BLUE; 6]);
+        assert_eq!(downscale(image.clone(), 3), image);
+    }
+}
@@ -0,0 +1,152 @@
+use windows::{
+    Win32::Graphics::{Direct3D10::*, Dxgi::Common::*},
+    core::Result,
+};
+
+use crate::{AlphaOutput, Renderer};
+
+/// The device objects of [`RendererOptions::chroma_key`] and
+/// [`AlphaOutput::Coverage`], created on first use.
+///
+/// [`RendererOptions::chroma_key`]: crate::RendererOptions::chroma_key
+#[derive(Default)]
+pub(crate) struct Keying {
+    /// A 1x1 white texture, multiplied by the vertex color of the quad
+    /// filling the region with the key color.
+    white: Option<ID3D10ShaderResourceView>,
+    /// Blending disabled, writing only the alpha channel.
+    alpha_only: Option<ID3D10BlendState>,
+}
+
+impl Keying {
+    fn white(
+        &mut self,
+        device: &ID3D10Device,
+    ) -> Result<ID3D10ShaderResourceView> {
+        if let Some(white) = &self.white {
+            return Ok(white.clone());
+        }
+        let texture = unsafe {
+            device.CreateTexture2D(
+                &D3D10_TEXTURE2D_DESC {
+                    Width: 1,
+                    Height: 1,
+                    MipLevels: 1,
+                    ArraySize: 1,
+                    Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+                    SampleDesc: DXGI_SAMPLE_DESC {
+                        Count: 1,
+                        Quality: 0,
+                    },
+                    Usage: D3D10_USAGE_IMMUTABLE,
+                    BindFlags: D3D10_BIND_SHADER_RESOURCE.0 as _,
+                    ..Default::default()
+                },
+                Some(&D3D10_SUBRESOURCE_DATA {
+                    pSysMem: [u8::MAX; 4].as_ptr() as _,
+                    SysMemPitch: 4,
+                    SysMemSlicePitch: 0,
+                }),
+            )
+        }?;
+        let mut srv = None;
+        unsafe {
+            device.CreateShaderResourceView(&texture, None, Some(&mut srv))
+        }?;
+        Ok(self.white.insert(srv.unwrap()).clone())
+    }
+
+    fn alpha_only(
+        &mut self,
+        device: &ID3D10Device,
+    ) -> Result<ID3D10BlendState> {
+        if let Some(alpha_only) = &self.alpha_only {
+            return Ok(alpha_only.clone());
+        }
+        let mut desc = Renderer::BLEND_DESC_OPAQUE;
+        desc.RenderTargetWriteMask[0] = D3D10_COLOR_WRITE_ENABLE_ALPHA.0 as _;
+        let mut blend_state = None;
+        unsafe { device.CreateBlendState(&desc, Some(&mut blend_state)) }?;
+        Ok(self.alpha_only.insert(blend_state.unwrap()).clone())
+    }
+}
+
+impl Renderer {
+    /// Replace the [`RendererOptions::chroma_key`](crate::RendererOptions::chroma_key)
+    /// of this [`Renderer`], taking effect on the next frame.
+    pub fn set_chroma_key(&mut self, chroma_key: Option<[f32; 3]>) {
+        self.options.chroma_key = chroma_key;
+    }
+
+    /// Whether [`Renderer::key_region`] draws anything, in which case even
+    /// frames without shapes are drawn.
+    pub(crate) fn keys_region(&self) -> bool {
+        self.options.chroma_key.is_some()
+            || self.options.alpha_output == AlphaOutput::Coverage
+    }
+
+    /// Fill the region with the chroma key, and clear its alpha channel for
+    /// [`AlphaOutput::Coverage`], before the UI is drawn. The pipeline must
+    /// be set up for the render target, and is restored afterwards.
+    pub(crate) fn key_region(
+        &mut self,
+        ctx: &ID3D10Device,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        frame_format: DXGI_FORMAT,
+    ) -> Result<()> {
+        if !self.keys_region() {
+            return Ok(());
+        }
+        // Create the device objects first, so that errors return before the
+        // pipeline is changed.
+        let white = match self.options.chroma_key {
+            Some(chroma_key) => {
+                Some((chroma_key, self.keying.white(&self.device)?))
+            },
+            None => None,
+        };
+        let alpha_only = if self.options.alpha_output == AlphaOutput::Coverage
+            && !Self::has_narrow_alpha(frame_format)
+        {
+            Some(self.keying.alpha_only(&self.device)?)
+        } else {
+            None
+        };
+        // The built-in shader draws the texture times the vertex color,
+        // without the conversions and dithering of the UI.
+        unsafe { ctx.PSSetShader(&self.blit_pixel_shader) };
+        let mut result = Ok(());
+        if let Some(([r, g, b], white)) = white {
+            unsafe {
+                ctx.PSSetShaderResources(0, Some(&[Some(white)]));
+                ctx.OMSetBlendState(
+                    &self.blend_state_opaque,
+                    &[0.; 4],
+                    u32::MAX,
+                );
+            }
+            result =
+                self.draw_quad(ctx, frame_origin, frame_size, [r, g, b, 1.]);
+        }
+        if let Some(alpha_only) = alpha_only
+            && result.is_ok()
+        {
+            unsafe {
+                ctx.PSSetShaderResources(0, Some(&[None]));
+                ctx.OMSetBlendState(&alpha_only, &[0.; 4], u32::MAX);
+            }
+            result = self.draw_quad(ctx, frame_origin, frame_size, [0.; 4]);
+        }
+        unsafe {
+            ctx.PSSetShaderResources(0, Some(&[None]));
+            ctx.PSSetShader(&self.pixel_shader);
+            ctx.OMSetBlendState(
+                self.blend_state_for(frame_format),
+                &[0.; 4],
+                u32::MAX,
+            );
+        }
+        result
+    }
+}
@@ -0,0 +1,222 @@
+use std::{mem, ptr, slice};
+
+use windows::{
+    Win32::{
+        Foundation::{COLORREF, HWND, POINT, SIZE},
+        Graphics::{Direct3D10::*, Dxgi::Common::*, Gdi::*},
+        UI::WindowsAndMessaging::{ULW_ALPHA, UpdateLayeredWindow},
+    },
+    core::{Error, Result},
+};
+
+/// Presents a rendered texture on a layered window (`WS_EX_LAYERED`) with
+/// per-pixel transparency, using `UpdateLayeredWindow`.
+///
+/// Render `egui` with [`Renderer::render`](crate::Renderer::render) into a
+/// texture cleared to transparent black (`[0.0; 4]`). The default
+/// [`AlphaOutput::Composite`](crate::AlphaOutput::Composite) produces
+/// premultiplied alpha, which is exactly what layered windows expect. Then
+/// call [`LayeredWindowPresenter::present`] with that texture. Combined with
+/// `WS_EX_TRANSPARENT`, this gives a click-through overlay window.
+///
+/// The texture is copied to the CPU through a staging texture every frame,
+/// so this is best suited for small to medium-sized overlays.
+pub struct LayeredWindowPresenter {
+    staging: Option<(ID3D10Texture2D, D3D10_TEXTURE2D_DESC)>,
+    dib: Option<Dib>,
+}
+
+struct Dib {
+    dc: HDC,
+    bitmap: HBITMAP,
+    old_bitmap: HGDIOBJ,
+    bits: *mut u8,
+    width: u32,
+    height: u32,
+}
+
+impl Drop for Dib {
+    fn drop(&mut self) {
+        unsafe {
+            SelectObject(self.dc, self.old_bitmap);
+            let _ = DeleteObject(self.bitmap.into());
+            let _ = DeleteDC(self.dc);
+        }
+    }
+}
+
+impl Default for LayeredWindowPresenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayeredWindowPresenter {
+    /// Create a presenter. GPU and GDI resources are created on the first
+    /// call to [`LayeredWindowPresenter::present`] and recreated whenever
+    /// the size of the texture changes.
+    pub fn new() -> Self {
+        Self {
+            staging: None,
+            dib: None,
+        }
+    }
+
+    /// Copy `texture` to the layered window `hwnd`, replacing its contents.
+    ///
+    /// `texture` must be a single-sampled 2D texture with format
+    /// `DXGI_FORMAT_R8G8B8A8_UNORM` or `DXGI_FORMAT_B8G8R8A8_UNORM` (or
+    /// their typeless counterparts), holding premultiplied colors. The window
+    /// is resized to the size of the texture; its position is unchanged.
+    pub fn present(
+        &mut self,
+        device: &ID3D10Device,
+        texture: &ID3D10Texture2D,
+        hwnd: HWND,
+    ) -> Result<()> {
+        let mut desc = D3D10_TEXTURE2D_DESC::default();
+        unsafe { texture.GetDesc(&mut desc) };
+        let swap_red_blue = match desc.Format {
+            DXGI_FORMAT_R8G8B8A8_UNORM | DXGI_FORMAT_R8G8B8A8_TYPELESS => true,
+            DXGI_FORMAT_B8G8R8A8_UNORM | DXGI_FORMAT_B8G8R8A8_TYPELESS => false,
+            _ => {
+                log::error!(
+                    "layered windows do not support textures of format {:?}.",
+                    desc.Format
+                );
+                return Err(Error::from_hresult(
+                    windows::Win32::Foundation::E_INVALIDARG,
+                ));
+            },
+        };
+
+        let staging = self.staging(device, &desc)?;
+        let dib = self.dib(desc.Width, desc.Height)?;
+        unsafe {
+            device.CopyResource(&staging, texture);
+            let mapped = staging.Map(0, D3D10_MAP_READ, 0)?;
+            let row_bytes = desc.Width as usize * 4;
+            for y in 0..desc.Height as usize {
+                let src = slice::from_raw_parts(
+                    (mapped.pData as *const u8)
+                        .add(y * mapped.RowPitch as usize),
+                    row_bytes,
+                );
+                let dst = slice::from_raw_parts_mut(
+                    dib.bits.add(y * row_bytes),
+                    row_bytes,
+                );
+                dst.copy_from_slice(src);
+                if swap_red_blue {
+                    for pixel in dst.chunks_exact_mut(4) {
+                        pixel.swap(0, 2);
+                    }
+                }
+            }
+            staging.Unmap(0);
+
+            UpdateLayeredWindow(
+                hwnd,
+                None,
+                None,
+                Some(&SIZE {
+                    cx: desc.Width as _,
+                    cy: desc.Height as _,
+                }),
+                Some(dib.dc),
+                Some(&POINT::default()),
+                COLORREF(0),
+                Some(&BLENDFUNCTION {
+                    BlendOp: AC_SRC_OVER as _,
+                    BlendFlags: 0,
+                    SourceConstantAlpha: 255,
+                    AlphaFormat: AC_SRC_ALPHA as _,
+                }),
+                ULW_ALPHA,
+            )
+        }
+    }
+
+    fn staging(
+        &mut self,
+        device: &ID3D10Device,
+        desc: &D3D10_TEXTURE2D_DESC,
+    ) -> Result<ID3D10Texture2D> {
+        if let Some((staging, staging_desc)) = &self.staging
+            && staging_desc.Width == desc.Width
+            && staging_desc.Height == desc.Height
+            && staging_desc.Format == desc.Format
+        {
+            return Ok(staging.clone());
+        }
+        let staging_desc = D3D10_TEXTURE2D_DESC {
+            Width: desc.Width,
+            Height: desc.Height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: desc.Format,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D10_USAGE_STAGING,
+            BindFlags: 0,
+            CPUAccessFlags: D3D10_CPU_ACCESS_READ.0 as _,
+            MiscFlags: 0,
+        };
+        let staging = unsafe { device.CreateTexture2D(&staging_desc, None) }?;
+        self.staging = Some((staging.clone(), staging_desc));
+        Ok(staging)
+    }
+
+    fn dib(&mut self, width: u32, height: u32) -> Result<&Dib> {
+        if self
+            .dib
+            .as_ref()
+            .is_some_and(|dib| dib.width == width && dib.height == height)
+        {
+            return Ok(self.dib.as_ref().unwrap());
+        }
+        self.dib = None;
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: mem::size_of::<BITMAPINFOHEADER>() as _,
+                biWidth: width as _,
+                // negative height for a top-down bitmap
+                biHeight: -(height as i32),
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        unsafe {
+            let dc = CreateCompatibleDC(None);
+            let mut bits = ptr::null_mut();
+            let bitmap = match CreateDIBSection(
+                Some(dc),
+                &info,
+                DIB_RGB_COLORS,
+                &mut bits,
+                None,
+                0,
+            ) {
+                Ok(bitmap) => bitmap,
+                Err(err) => {
+                    let _ = DeleteDC(dc);
+                    return Err(err);
+                },
+            };
+            let old_bitmap = SelectObject(dc, bitmap.into());
+            Ok(self.dib.insert(Dib {
+                dc,
+                bitmap,
+                old_bitmap,
+                bits: bits as _,
+                width,
+                height,
+            }))
+        }
+    }
+}
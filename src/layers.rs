@@ -0,0 +1,264 @@
+use std::{mem, ops::RangeBounds, sync::Arc};
+
+use egui::{
+    LayerId, Order, Rect,
+    epaint::{ClippedShape, PaintCallback, Shape},
+    layers::ShapeIdx,
+};
+
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+use crate::{Renderer, RendererOutput};
+
+/// Enable [`Renderer::render_layers`] for `egui_ctx`, from its next pass on.
+///
+/// At the end of every pass, this marks where the shapes of each layer
+/// start and end in [`egui::FullOutput::shapes`], since `egui` flattens its
+/// layers into a single list. [`Renderer::render`] removes the markers, but
+/// other renderers drawing the same output see them as paint callbacks.
+/// Calling this more than once has no effect.
+pub fn track_layers(egui_ctx: &egui::Context) {
+    #[cfg(feature = "egui-0_33")]
+    egui_ctx.add_plugin(LayerTracker);
+    #[cfg(not(feature = "egui-0_33"))]
+    crate::compat::on_end_pass_once(
+        egui_ctx,
+        LayerTracker::DEBUG_NAME,
+        LayerTracker::mark_layers,
+    );
+}
+
+/// Which layers [`Renderer::render_routed`] draws to a render target.
+///
+/// Layers are identified with [`track_layers`]. Shapes of layers that are
+/// not known, such as those painted with [`egui::Context::layer_painter`]
+/// outside of any area, only pass [`LayerFilter::All`] and
+/// [`LayerFilter::Deny`]; prefer [`LayerFilter::Allow`] for targets that must
+/// not show anything else.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LayerFilter {
+    /// Every layer.
+    All,
+    /// Only the given layers.
+    Allow(Vec<LayerId>),
+    /// Every layer except the given ones.
+    Deny(Vec<LayerId>),
+}
+
+impl LayerFilter {
+    /// Whether shapes of `layer_id`, or of an unknown layer if it is `None`,
+    /// pass the filter.
+    pub fn matches(&self, layer_id: Option<LayerId>) -> bool {
+        match (self, layer_id) {
+            (Self::All, _) => true,
+            (Self::Allow(layer_ids), Some(layer_id)) => {
+                layer_ids.contains(&layer_id)
+            },
+            (Self::Allow(_), None) => false,
+            (Self::Deny(layer_ids), Some(layer_id)) => {
+                !layer_ids.contains(&layer_id)
+            },
+            (Self::Deny(_), None) => true,
+        }
+    }
+}
+
+/// The plugin added by [`track_layers`].
+struct LayerTracker;
+
+/// The payload of the paint callbacks marking the start or end of a layer.
+struct LayerMarker {
+    layer_id: LayerId,
+    start: bool,
+}
+
+#[cfg(feature = "egui-0_33")]
+impl egui::Plugin for LayerTracker {
+    fn debug_name(&self) -> &'static str {
+        Self::DEBUG_NAME
+    }
+
+    fn on_end_pass(&mut self, ctx: &egui::Context) {
+        Self::mark_layers(ctx);
+    }
+}
+
+impl LayerTracker {
+    const DEBUG_NAME: &str = "egui_directx10::LayerTracker";
+
+    fn mark_layers(ctx: &egui::Context) {
+        // Layers painted outside of areas, other than the background and
+        // debug layers, are not known, and their shapes are not marked.
+        let mut layer_ids =
+            ctx.memory(|memory| memory.layer_ids().collect::<Vec<_>>());
+        for layer_id in [LayerId::background(), LayerId::debug()] {
+            if !layer_ids.contains(&layer_id) {
+                layer_ids.push(layer_id);
+            }
+        }
+        ctx.graphics_mut(|graphics| {
+            for layer_id in layer_ids {
+                let Some(list) = graphics.get_mut(layer_id) else {
+                    continue;
+                };
+                if list.is_empty() {
+                    continue;
+                }
+                list.mutate_shape(ShapeIdx(0), |first| {
+                    let shape = mem::replace(&mut first.shape, Shape::Noop);
+                    first.shape =
+                        Shape::Vec(vec![marker(layer_id, true), shape]);
+                });
+                list.add(Rect::ZERO, marker(layer_id, false));
+            }
+        });
+    }
+}
+
+fn marker(layer_id: LayerId, start: bool) -> Shape {
+    Shape::Callback(PaintCallback {
+        rect: Rect::ZERO,
+        callback: Arc::new(LayerMarker { layer_id, start }),
+    })
+}
+
+/// The marker `shape` is, if any.
+fn as_marker(shape: &Shape) -> Option<&LayerMarker> {
+    match shape {
+        Shape::Callback(callback) => callback.callback.downcast_ref(),
+        _ => None,
+    }
+}
+
+/// Remove the markers of [`track_layers`] from `shapes`, and get the layer
+/// of every remaining shape, or `None` if it is not known.
+pub(crate) fn split_layers(
+    shapes: Vec<ClippedShape>,
+) -> impl Iterator<Item = (Option<LayerId>, ClippedShape)> {
+    let mut layer_id = None;
+    shapes.into_iter().filter_map(move |mut clipped_shape| {
+        if let Some(marker) = as_marker(&clipped_shape.shape) {
+            layer_id = marker.start.then_some(marker.layer_id);
+            return None;
+        }
+        if let Shape::Vec(shapes) = &mut clipped_shape.shape
+            && shapes.len() == 2
+            && let Some(marker) = as_marker(&shapes[0])
+        {
+            layer_id = marker.start.then_some(marker.layer_id);
+            clipped_shape.shape = shapes.pop().unwrap();
+        }
+        Some((layer_id, clipped_shape))
+    })
+}
+
+/// Remove the markers of [`track_layers`] from `shapes`.
+pub(crate) fn strip_markers(shapes: Vec<ClippedShape>) -> Vec<ClippedShape> {
+    split_layers(shapes).map(|(_, shape)| shape).collect()
+}
+
+impl Renderer {
+    /// Render the layers of `egui_output` whose [`Order`] is in `orders`,
+    /// like [`Renderer::render`], so that a frame can be split around the
+    /// passes of an engine, for example the background layers, then the 3D
+    /// viewport, then the windows and tooltips on top of it.
+    ///
+    /// The textures of the output are updated on the first call, and can be
+    /// drawn by the following calls with the same output. Layers are only
+    /// known if [`track_layers`] was called for `egui_ctx` before its pass,
+    /// which this does for the following passes. Shapes of layers that are
+    /// not known are only rendered if `orders` covers every [`Order`].
+    ///
+    /// ```ignore
+    /// renderer.render_layers(&ctx, &target, &egui_ctx, &mut output, ..=Order::Background)?;
+    /// draw_viewport();
+    /// renderer.render_layers(&ctx, &target, &egui_ctx, &mut output, Order::Middle..)?;
+    /// ```
+    ///
+    /// [`RendererOptions::track_dirty_rect`](crate::RendererOptions::track_dirty_rect)
+    /// and [`RendererOptions::damage_tracking`](crate::RendererOptions::damage_tracking)
+    /// compare every call with the previous one, so they do not save any
+    /// work when a frame is split.
+    pub fn render_layers(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        egui_ctx: &egui::Context,
+        egui_output: &mut RendererOutput,
+        orders: impl RangeBounds<Order>,
+    ) -> Result<()> {
+        let all_orders =
+            orders.contains(&Order::Background) && orders.contains(&Order::TOP);
+        self.render_filtered(
+            device_context,
+            render_target,
+            egui_ctx,
+            egui_output,
+            |layer_id| match layer_id {
+                Some(layer_id) => orders.contains(&layer_id.order),
+                None => all_orders,
+            },
+        )
+    }
+
+    /// Render the layers of `egui_output` to several render targets, each
+    /// with a [`LayerFilter`] selecting the layers drawn to it, like
+    /// [`Renderer::render`], for example to send a HUD to a capture or
+    /// streaming target while debug windows only appear in the local
+    /// window:
+    ///
+    /// ```ignore
+    /// renderer.render_routed(&ctx, &egui_ctx, output, &[
+    ///     (&swap_chain_target, LayerFilter::All),
+    ///     (&capture_target, LayerFilter::Deny(debug_layers)),
+    /// ])?;
+    /// ```
+    ///
+    /// Layers are identified as in [`Renderer::render_layers`], whose notes
+    /// on dirty rects apply as well.
+    pub fn render_routed(
+        &mut self,
+        device_context: &ID3D10Device,
+        egui_ctx: &egui::Context,
+        mut egui_output: RendererOutput,
+        routes: &[(&ID3D10RenderTargetView, LayerFilter)],
+    ) -> Result<()> {
+        for (render_target, filter) in routes {
+            self.render_filtered(
+                device_context,
+                render_target,
+                egui_ctx,
+                &mut egui_output,
+                |layer_id| filter.matches(layer_id),
+            )?;
+        }
+        if routes.is_empty() {
+            self.update_context_textures(egui_ctx, egui_output.textures_delta)?;
+        }
+        Ok(())
+    }
+
+    /// Render the shapes of `egui_output` in the layers passing `filter`,
+    /// and take its textures delta, which only has to be applied once.
+    fn render_filtered(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        egui_ctx: &egui::Context,
+        egui_output: &mut RendererOutput,
+        filter: impl Fn(Option<LayerId>) -> bool,
+    ) -> Result<()> {
+        track_layers(egui_ctx);
+        let shapes = split_layers(egui_output.shapes.clone())
+            .filter(|(layer_id, _)| filter(*layer_id))
+            .map(|(_, shape)| shape)
+            .collect();
+        let output = RendererOutput {
+            textures_delta: mem::take(&mut egui_output.textures_delta),
+            shapes,
+            pixels_per_point: egui_output.pixels_per_point,
+        };
+        self.render(device_context, render_target, egui_ctx, output)
+    }
+}
@@ -21,10 +21,168 @@
 //! with Direct3D10 and `egui`. This example uses `winit` for window management
 //! and event handling, while native Win32 APIs should also work well.
 
+#[cfg(not(any(
+    feature = "egui-0_31",
+    feature = "egui-0_32",
+    feature = "egui-0_33"
+)))]
+compile_error!(
+    "one of the `egui-0_31`, `egui-0_32` and `egui-0_33` features must be enabled"
+);
+#[cfg(all(
+    feature = "egui-0_31",
+    not(any(feature = "egui-0_32", feature = "egui-0_33"))
+))]
+extern crate egui_0_31 as egui;
+#[cfg(all(feature = "egui-0_32", not(feature = "egui-0_33")))]
+extern crate egui_0_32 as egui;
+
+mod allocator;
+#[cfg(feature = "winit")]
+mod app;
+#[cfg(feature = "overlay")]
+mod back_buffer;
+mod backdrop;
+mod backend;
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench;
+mod builder;
+mod bundle;
+mod callback;
+mod capture;
+mod compat;
+#[cfg(feature = "software-cursor")]
+mod cursor;
+#[cfg(feature = "dcomp")]
+mod dcomp;
+mod device;
+mod device_check;
+mod dirty_rect;
+#[cfg(feature = "win32-input")]
+mod dpi;
+#[cfg(feature = "dx11-compat")]
+pub mod dx11_compat;
+mod dynamic_scale;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+mod gamma_view;
+mod geometry;
+mod gpu_timer;
+#[cfg(feature = "hdr")]
+mod hdr;
+#[cfg(feature = "headless")]
+mod headless;
+#[cfg(feature = "image-loader")]
+mod image_loader;
+mod keying;
+#[cfg(feature = "layered-window")]
+mod layered_window;
+mod layers;
+mod limits;
+mod namespace;
+mod options;
+#[cfg(feature = "overlay")]
+mod overlay;
+mod painter;
+mod permutation;
+mod pipeline;
+mod post_process;
+mod prewarm;
+mod readback;
+#[cfg(feature = "shader-validation")]
+mod reflection;
+mod renderer_trait;
+mod repaint;
+mod resources;
+#[cfg(feature = "winit")]
+mod run;
+mod shader;
+mod shared;
+mod snapshot;
+mod state_guard;
+mod stats;
+#[cfg(any(feature = "winit", feature = "win32"))]
+mod swap_chain;
+mod sync_renderer;
+#[cfg(any(feature = "software-cursor", feature = "win32"))]
+mod system_cursor;
+#[cfg(feature = "testing")]
+mod testing;
 mod texture;
+mod user_constants;
+mod watchdog;
+#[cfg(feature = "win32")]
+pub mod win32;
+#[cfg(feature = "win32-input")]
+mod win32_input;
+#[cfg(feature = "winit")]
+pub mod winit;
+pub use allocator::{DefaultTextureAllocator, TextureAllocator};
+#[cfg(feature = "winit")]
+pub use app::{
+    App, CreationContext, FileStorage, Frame, NativeOptions, Storage,
+    run_native,
+};
+use backdrop::Backdrop;
+use backend::{Backend, DrawRange};
+pub use builder::RendererBuilder;
+pub use bundle::EguiD3D10;
+use callback::Callback;
+pub use callback::CallbackFn;
+pub use compat::ViewportOutputs;
+pub use device::{
+    DeviceLock, DeviceOptions, DriverType, create_device,
+    is_multithread_protected, set_multithread_protected,
+};
+use dirty_rect::DirtyRect;
+#[cfg(feature = "win32-input")]
+pub use dpi::{DpiChange, DpiTracker};
+use dynamic_scale::DynamicScale;
+use geometry::{GeometryBuffers, QuadBuffers};
+#[cfg(feature = "hdr")]
+pub use hdr::OutputHdrInfo;
+#[cfg(feature = "headless")]
+pub use headless::HeadlessRenderer;
+#[cfg(feature = "image-loader")]
+pub use image_loader::{ImageLoader, LoadedImage};
+use keying::Keying;
+#[cfg(feature = "layered-window")]
+pub use layered_window::LayeredWindowPresenter;
+pub use layers::{LayerFilter, track_layers};
+use namespace::Namespace;
+pub use options::*;
+#[cfg(feature = "overlay")]
+pub use overlay::Overlay;
+pub use painter::{GpuMesh, Painter};
+use permutation::{Permutation, PixelShaderCache};
+use pipeline::UnusedStages;
+use post_process::{PostProcess, RenderTexture};
+#[cfg(feature = "shader-validation")]
+pub use reflection::validate_vertex_shader;
+pub use renderer_trait::EguiDxRenderer;
+#[cfg(feature = "user-textures")]
+pub use renderer_trait::EguiDxUserTextures;
+pub use repaint::RepaintScheduler;
+#[cfg(feature = "winit")]
+pub use run::{SimpleOptions, run_simple};
+pub use shared::{SharedResources, SharedTexturePool};
+use snapshot::Snapshot;
+pub use state_guard::DeviceStateGuard;
+use stats::StatsCollector;
+pub use stats::{BufferStats, BufferUsage, FrameCallback, RenderStats};
+pub use sync_renderer::SyncRenderer;
+#[cfg(feature = "testing")]
+pub use testing::{TextureEvent, TextureEventKind, TextureState};
 use texture::TexturePool;
+pub use texture::{TextureInfo, TextureKind};
+use user_constants::UserConstants;
+#[cfg(feature = "win32-input")]
+pub use win32_input::Win32Input;
 
-use std::mem;
+use std::{collections::HashMap, mem};
 
 const fn zeroed<T>() -> T {
     unsafe { mem::zeroed() }
@@ -32,7 +190,7 @@ const fn zeroed<T>() -> T {
 
 use egui::{
     ClippedPrimitive, Pos2,
-    epaint::{ClippedShape, Primitive, Vertex, textures::TexturesDelta},
+    epaint::{ClippedShape, Mesh, Primitive, Vertex, textures::TexturesDelta},
 };
 
 use windows::{
@@ -50,12 +208,81 @@ pub struct Renderer {
 
     input_layout: ID3D10InputLayout,
     vertex_shader: ID3D10VertexShader,
+    vertex_shader_override: Option<ID3D10VertexShader>,
     pixel_shader: ID3D10PixelShader,
+    pixel_shader_permutation: Permutation,
+    pixel_shaders: PixelShaderCache,
+    blit_pixel_shader: ID3D10PixelShader,
     rasterizer_state: ID3D10RasterizerState,
     sampler_state: ID3D10SamplerState,
+    sampler_state_clamp: ID3D10SamplerState,
+    sampler_state_point: ID3D10SamplerState,
+    sampler_state_repeat: ID3D10SamplerState,
+    sampler_state_mirror: ID3D10SamplerState,
     blend_state: ID3D10BlendState,
+    blend_state_no_alpha: ID3D10BlendState,
+    blend_state_opaque: ID3D10BlendState,
+    blend_state_additive: ID3D10BlendState,
+    blend_state_override: Option<ID3D10BlendState>,
+    blend_state_subpixel: Option<[ID3D10BlendState; 2]>,
+    constant_buffer: Option<ID3D10Buffer>,
+
+    options: RendererOptions,
+    texture_pool: SharedTexturePool,
+    user_constants: UserConstants,
+    texture_pixel_shaders: HashMap<egui::TextureId, ID3D10PixelShader>,
+    post_process: Option<PostProcess>,
+    intermediate: Option<RenderTexture>,
+    dynamic_scale: Option<DynamicScale>,
+    dirty_rect: Option<DirtyRect>,
+    stats: Option<StatsCollector>,
+    buffer_stats: BufferStats,
+    backdrop: Option<Backdrop>,
+    background_snapshot: Option<Snapshot>,
+    background_texture_id: Option<egui::TextureId>,
+    capture_texture: Option<ID3D10Texture2D>,
+    keying: Keying,
+    geometry: GeometryBuffers,
+    quad: QuadBuffers,
+    mesh_hook: Option<MeshHook>,
+    draw_list_callback: Option<DrawListCallback>,
+    #[cfg(feature = "software-cursor")]
+    software_cursor: Option<cursor::SoftwareCursor>,
+    #[cfg(feature = "overlay")]
+    back_buffer: Option<back_buffer::BackBuffer>,
+}
+
+/// The output of one `egui` context drawn in a frame.
+struct FramePart<'a> {
+    egui_ctx: &'a egui::Context,
+    namespace: Namespace,
+    shapes: Vec<ClippedShape>,
+    pixels_per_point: f32,
+    zoom_factor: f32,
+}
+
+/// A function called by [`Renderer::render`] on every tessellated mesh and
+/// its clip rect, in points, before they are uploaded. Returning `false`
+/// drops the mesh. See [`Renderer::set_mesh_hook`].
+pub type MeshHook = Box<dyn FnMut(&mut Mesh, &mut egui::Rect) -> bool>;
+
+/// A function called by [`Renderer::render`] with the draw calls of every
+/// frame before they are issued. See [`Renderer::set_draw_list_callback`].
+pub type DrawListCallback = Box<dyn FnMut(&[DrawCommand])>;
 
-    texture_pool: TexturePool,
+/// A draw call [`Renderer::render`] is about to issue, as passed to a
+/// [`DrawListCallback`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawCommand {
+    /// The texture bound for the draw, with the id the `egui` context
+    /// drawing it knows it by.
+    pub texture_id: egui::TextureId,
+    /// The number of vertices uploaded for the draw.
+    pub vertex_count: usize,
+    /// The number of indices drawn.
+    pub index_count: usize,
+    /// The scissor rect of the draw, in pixels of the render target.
+    pub clip_rect: egui::Rect,
 }
 
 /// Part of [`egui::FullOutput`] that is consumed by [`Renderer::render`].
@@ -84,11 +311,7 @@ pub struct RendererOutput {
 /// ```
 pub fn split_output(
     full_output: egui::FullOutput,
-) -> (
-    RendererOutput,
-    egui::PlatformOutput,
-    egui::OrderedViewportIdMap<egui::ViewportOutput>,
-) {
+) -> (RendererOutput, egui::PlatformOutput, ViewportOutputs) {
     (
         RendererOutput {
             textures_delta: full_output.textures_delta,
@@ -100,6 +323,7 @@ pub fn split_output(
     )
 }
 
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 struct VertexData {
     pos: Pos2,
@@ -107,11 +331,169 @@ struct VertexData {
     color: [f32; 4],
 }
 
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+struct PixelConstants {
+    dither_scale: f32,
+    texture_color_space: u32,
+    target_color_space: u32,
+    subpixel_text: u32,
+    color_matrix: [[f32; 4]; 3],
+    color_adjustment: [f32; 2],
+    text_gamma: f32,
+    _padding: f32,
+}
+
+/// The pixel shader constants that depend on the texture of a mesh.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct TextureConstants {
+    color_space: ColorSpace,
+    font_atlas: bool,
+}
+
 struct MeshData {
     vtx: Vec<VertexData>,
     idx: Vec<u32>,
     tex: egui::TextureId,
     clip_rect: egui::Rect,
+    /// The paint callback drawn instead of the mesh, which is then empty.
+    callback: Option<Callback>,
+}
+
+/// The region of the render target a frame is drawn to, and how points map
+/// to it.
+#[derive(Clone, Copy)]
+struct FrameInfo {
+    origin: (i32, i32),
+    size: (u32, u32),
+    format: DXGI_FORMAT,
+    pixels_per_point: f32,
+    zoom_factor: f32,
+    snap_to_pixels: bool,
+}
+
+impl FrameInfo {
+    fn convert_vertex(&self, Vertex { pos, uv, color }: Vertex) -> VertexData {
+        let mut pos = pos * self.pixels_per_point * self.zoom_factor;
+        if self.snap_to_pixels {
+            pos = pos.round();
+        }
+        VertexData {
+            pos: Pos2::new(
+                pos.x / self.size.0 as f32 * 2.0 - 1.0,
+                1.0 - pos.y / self.size.1 as f32 * 2.0,
+            ),
+            uv,
+            color: [
+                color[0] as f32 / 255.0,
+                color[1] as f32 / 255.0,
+                color[2] as f32 / 255.0,
+                color[3] as f32 / 255.0,
+            ],
+        }
+    }
+
+    /// Convert a clip rect in points to pixels of the render target.
+    fn clip_rect_in_pixels(&self, clip_rect: egui::Rect) -> egui::Rect {
+        let mut clip_rect =
+            clip_rect * self.pixels_per_point * self.zoom_factor;
+        if self.snap_to_pixels {
+            clip_rect = egui::Rect::from_min_max(
+                clip_rect.min.round(),
+                clip_rect.max.round(),
+            );
+        }
+        clip_rect
+            .translate(egui::vec2(self.origin.0 as f32, self.origin.1 as f32))
+    }
+
+    /// Convert a mesh to vertex data, or `None` if there is nothing to draw.
+    fn convert_mesh(
+        &self,
+        mesh: Mesh,
+        clip_rect: egui::Rect,
+    ) -> Option<MeshData> {
+        if mesh.indices.is_empty() {
+            return None;
+        }
+        if !mesh.indices.len().is_multiple_of(3) {
+            log::warn!(concat!(
+                "egui wants to draw a incomplete triangle. ",
+                "this request will be ignored."
+            ));
+            return None;
+        }
+        Some(MeshData {
+            vtx: mesh
+                .vertices
+                .into_iter()
+                .map(|vertex| self.convert_vertex(vertex))
+                .collect(),
+            idx: mesh.indices,
+            tex: mesh.texture_id,
+            clip_rect: self.clip_rect_in_pixels(clip_rect),
+            callback: None,
+        })
+    }
+
+    /// Convert a mesh to vertex data or position a paint callback, or
+    /// `None` if there is nothing to draw.
+    fn convert_primitive(
+        &self,
+        primitive: Primitive,
+        clip_rect: egui::Rect,
+    ) -> Option<MeshData> {
+        match primitive {
+            Primitive::Mesh(mesh) => self.convert_mesh(mesh, clip_rect),
+            Primitive::Callback(callback) => {
+                self.convert_callback(callback, clip_rect)
+            },
+        }
+    }
+
+    /// Convert primitives to vertex data in order, skipping those with
+    /// nothing to draw.
+    #[cfg(not(feature = "rayon"))]
+    fn convert_primitives(
+        &self,
+        primitives: impl Iterator<Item = (Primitive, egui::Rect)>,
+    ) -> Vec<MeshData> {
+        primitives
+            .filter_map(|(primitive, clip_rect)| {
+                self.convert_primitive(primitive, clip_rect)
+            })
+            .collect()
+    }
+
+    /// Convert primitives to vertex data in order, skipping those with
+    /// nothing to draw. Frames with many meshes are converted on the thread
+    /// pool of `rayon`, which keeps the order of the meshes.
+    #[cfg(feature = "rayon")]
+    fn convert_primitives(
+        &self,
+        primitives: impl Iterator<Item = (Primitive, egui::Rect)>,
+    ) -> Vec<MeshData> {
+        use rayon::prelude::*;
+
+        // Below this, distributing the work costs more than it saves.
+        const MIN_PARALLEL_MESHES: usize = 64;
+
+        let primitives = primitives.collect::<Vec<_>>();
+        if primitives.len() < MIN_PARALLEL_MESHES {
+            return primitives
+                .into_iter()
+                .filter_map(|(primitive, clip_rect)| {
+                    self.convert_primitive(primitive, clip_rect)
+                })
+                .collect();
+        }
+        primitives
+            .into_par_iter()
+            .filter_map(|(primitive, clip_rect)| {
+                self.convert_primitive(primitive, clip_rect)
+            })
+            .collect()
+    }
 }
 
 impl Renderer {
@@ -122,53 +504,310 @@ impl Renderer {
     /// If any Direct3D resource creation fails, this function will return an
     /// error. You can create the Direct3D10 device with debug layer enabled
     /// to find out details on the error.
+    ///
+    /// Use [`Renderer::builder`] to configure the renderer instead.
     pub fn new(device: &ID3D10Device) -> Result<Self> {
-        let mut input_layout = None;
-        let mut vertex_shader = None;
-        let mut pixel_shader = None;
-        let mut rasterizer_state = None;
-        let mut sampler_state = None;
-        let mut blend_state = None;
-        unsafe {
-            device.CreateInputLayout(
-                &Self::INPUT_ELEMENTS_DESC,
-                Self::VS_BLOB,
-                Some(&mut input_layout),
-            )?;
-            device
-                .CreateVertexShader(Self::VS_BLOB, Some(&mut vertex_shader))?;
-            device.CreatePixelShader(
-                Self::PS_BLOB,
-                Some(&mut pixel_shader),
-            )?;
-            device.CreateRasterizerState(
-                &Self::RASTERIZER_DESC,
-                Some(&mut rasterizer_state),
-            )?;
-            device.CreateSamplerState(
-                &Self::SAMPLER_DESC,
-                Some(&mut sampler_state),
-            )?;
-            device
-                .CreateBlendState(&Self::BLEND_DESC, Some(&mut blend_state))?;
+        Self::with_options(device, RendererOptions::default())
+    }
+
+    /// Create a [`Renderer`] using the provided Direct3D10 device and
+    /// [`RendererOptions`], which hold every construction-time option of
+    /// the renderer. [`Renderer::builder`] sets them one at a time.
+    ///
+    /// Options that require a different pixel shader compile it from the
+    /// embedded HLSL source, which needs `d3d10.dll` to provide the shader
+    /// compiler. Errors are handled as in [`Renderer::new`], and compiler
+    /// diagnostics are reported through the `log` crate.
+    #[doc(alias = "with_config")]
+    pub fn with_options(
+        device: &ID3D10Device,
+        options: RendererOptions,
+    ) -> Result<Self> {
+        Self::with_texture_allocator(
+            device,
+            options,
+            Box::new(DefaultTextureAllocator),
+        )
+    }
+
+    /// Create a [`Renderer`] like [`Renderer::with_options`], creating and
+    /// destroying the textures managed by `egui` through `texture_allocator`.
+    pub fn with_texture_allocator(
+        device: &ID3D10Device,
+        options: RendererOptions,
+        texture_allocator: Box<dyn TextureAllocator>,
+    ) -> Result<Self> {
+        Self::from_shared_resources(
+            &SharedResources::new(device)?,
+            options,
+            SharedTexturePool::with_texture_allocator(
+                device,
+                texture_allocator,
+            ),
+        )
+    }
+
+    fn from_shared_resources(
+        shared: &SharedResources,
+        options: RendererOptions,
+        texture_pool: SharedTexturePool,
+    ) -> Result<Self> {
+        let device = &shared.device;
+        if options.multithread_protected {
+            set_multithread_protected(device, true)?;
+        }
+        let pixel_shader_permutation = Permutation::new(
+            &options,
+            texture_pool.textures().has_non_srgb_textures(),
+        );
+        let pixel_shader = shared
+            .pixel_shaders
+            .get_or_create(device, pixel_shader_permutation)?;
+        let constant_buffer = (!pixel_shader_permutation.is_empty())
+            .then(|| Self::create_constant_buffer(device))
+            .transpose()?;
+        let blend_state_subpixel = if options.subpixel_text {
+            let mut composite = None;
+            let mut no_alpha = None;
+            unsafe {
+                device.CreateBlendState(
+                    &Self::BLEND_DESC_SUBPIXEL,
+                    Some(&mut composite),
+                )?;
+                device.CreateBlendState(
+                    &Self::BLEND_DESC_SUBPIXEL_NO_ALPHA,
+                    Some(&mut no_alpha),
+                )?;
+            }
+            Some([composite.unwrap(), no_alpha.unwrap()])
+        } else {
+            None
         };
-        Ok(Self {
+        let dirty_rect = (options.track_dirty_rect || options.damage_tracking)
+            .then(DirtyRect::default);
+        let dynamic_scale = options
+            .dynamic_render_scale
+            .map(|options| DynamicScale::new(device, options))
+            .transpose()?;
+        let stats = (options.frame_watchdog.is_some()
+            || options.on_frame.is_some())
+        .then(|| StatsCollector::new(device))
+        .transpose()?;
+        let backdrop = options
+            .backdrop_blur
+            .map(|blur| {
+                Backdrop::new(device, &mut texture_pool.textures_mut(), blur)
+            })
+            .transpose()?;
+        let mut renderer = Self {
             device: device.clone(),
-            input_layout: input_layout.unwrap(),
-            vertex_shader: vertex_shader.unwrap(),
-            pixel_shader: pixel_shader.unwrap(),
-            rasterizer_state: rasterizer_state.unwrap(),
-            sampler_state: sampler_state.unwrap(),
-            blend_state: blend_state.unwrap(),
-            texture_pool: TexturePool::new(device),
-        })
+            input_layout: shared.input_layout.clone(),
+            vertex_shader: shared.vertex_shader.clone(),
+            vertex_shader_override: None,
+            pixel_shader,
+            pixel_shader_permutation,
+            pixel_shaders: shared.pixel_shaders.clone(),
+            blit_pixel_shader: shared.blit_pixel_shader.clone(),
+            rasterizer_state: shared.rasterizer_state.clone(),
+            sampler_state: shared.sampler_state.clone(),
+            sampler_state_clamp: shared.sampler_state_clamp.clone(),
+            sampler_state_point: shared.sampler_state_point.clone(),
+            sampler_state_repeat: shared.sampler_state_repeat.clone(),
+            sampler_state_mirror: shared.sampler_state_mirror.clone(),
+            blend_state: shared.blend_state.clone(),
+            blend_state_no_alpha: shared.blend_state_no_alpha.clone(),
+            blend_state_opaque: shared.blend_state_opaque.clone(),
+            blend_state_additive: shared.blend_state_additive.clone(),
+            blend_state_override: None,
+            blend_state_subpixel,
+            constant_buffer,
+            #[cfg(feature = "software-cursor")]
+            software_cursor: options
+                .software_cursor
+                .then(cursor::SoftwareCursor::new),
+            options,
+            texture_pool,
+            user_constants: UserConstants::default(),
+            texture_pixel_shaders: HashMap::new(),
+            post_process: None,
+            intermediate: None,
+            dynamic_scale,
+            dirty_rect,
+            stats,
+            buffer_stats: BufferStats::default(),
+            backdrop,
+            background_snapshot: None,
+            background_texture_id: None,
+            capture_texture: None,
+            keying: Keying::default(),
+            geometry: GeometryBuffers::default(),
+            quad: QuadBuffers::new(device)?,
+            mesh_hook: None,
+            draw_list_callback: None,
+            #[cfg(feature = "overlay")]
+            back_buffer: None,
+        };
+        if renderer.options.prewarm {
+            renderer.prewarm()?;
+        }
+        Ok(renderer)
+    }
+
+    /// Replace the [`RendererOptions::color_transform`] of this [`Renderer`].
+    ///
+    /// Updating the matrix of a [`ColorTransform::Matrix`] or the texture of
+    /// a [`ColorTransform::Lut`] is cheap. Switching between kinds of
+    /// transforms, or enabling or disabling the transform, recompiles the
+    /// pixel shader. Call this whenever the window moves to a monitor with a
+    /// different color profile.
+    pub fn set_color_transform(
+        &mut self,
+        color_transform: Option<ColorTransform>,
+    ) -> Result<()> {
+        self.options.color_transform = color_transform;
+        self.update_pixel_shader()
+    }
+
+    /// Replace the [`RendererOptions::pixels_per_point`] of this
+    /// [`Renderer`], taking effect on the next frame.
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: Option<f32>) {
+        self.options.pixels_per_point = pixels_per_point;
+    }
+
+    /// Replace the [`RendererOptions::time_slicing`] of this [`Renderer`],
+    /// taking effect on the next frame, for example only while a huge frame
+    /// is expected.
+    pub fn set_time_slicing(&mut self, time_slicing: Option<TimeSlicing>) {
+        self.options.time_slicing = time_slicing;
+    }
+
+    /// Draw with `vertex_shader` instead of the built-in vertex shader, or
+    /// go back to the built-in one with `None`, for effects on the whole UI
+    /// such as screen shake, CRT curvature or slide and scale animations.
+    ///
+    /// The shader must have the same input signature as the built-in one:
+    /// `POSITION` (a `float2` in normalized device coordinates), `TEXCOORD`
+    /// (a `float2`) and `COLOR` (a `float4`), and the same outputs:
+    /// `SV_POSITION`, `TEXCOORD` and `COLOR`. Animation parameters can be
+    /// passed with [`Renderer::set_user_constants`]. With the
+    /// `shader-validation` feature, `validate_vertex_shader` checks the
+    /// inputs of a compiled shader.
+    pub fn set_vertex_shader(
+        &mut self,
+        vertex_shader: Option<ID3D10VertexShader>,
+    ) {
+        self.vertex_shader_override = vertex_shader;
+    }
+
+    /// Replace the [`RendererOptions::depth_range`] of this [`Renderer`],
+    /// taking effect on the next frame.
+    pub fn set_depth_range(&mut self, depth_range: DepthRange) {
+        self.options.depth_range = depth_range;
+    }
+
+    /// Replace the [`RendererOptions::user_texture_filter`] of this
+    /// [`Renderer`], taking effect on the next frame.
+    pub fn set_user_texture_filter(
+        &mut self,
+        user_texture_filter: ScaleFilter,
+    ) {
+        self.options.user_texture_filter = user_texture_filter;
+    }
+
+    /// Draw with `blend_state` instead of the one selected by
+    /// [`RendererOptions::blend_mode`], or go back to it with `None`, for
+    /// blending not covered by the [`BlendMode`] presets, for example for
+    /// additive blending, for opaque HUDs without blending, or to control how
+    /// the destination alpha is written for capture pipelines.
+    ///
+    /// The pixel shader outputs premultiplied colors. The blend factor is
+    /// zero and the sample mask enables all samples.
+    /// [`RendererOptions::alpha_output`] is ignored while a blend state is
+    /// set.
+    pub fn set_blend_state(&mut self, blend_state: Option<ID3D10BlendState>) {
+        self.blend_state_override = blend_state;
+    }
+
+    /// Set a function to inspect, modify or drop the meshes of every frame
+    /// before they are uploaded, for example to dim the whole UI by scaling
+    /// the vertex colors, offset it, or skip the meshes outside a region.
+    /// Pass `None` to remove it.
+    ///
+    /// Meshes drawn through a [`Painter`] do not go through the hook.
+    pub fn set_mesh_hook(&mut self, mesh_hook: Option<MeshHook>) {
+        self.mesh_hook = mesh_hook;
+    }
+
+    /// Set a function to observe the draw calls of every frame, after the
+    /// [`MeshHook`] and before they are issued, for diagnostics or to feed
+    /// custom culling systems. Pass `None` to remove it.
+    pub fn set_draw_list_callback(
+        &mut self,
+        draw_list_callback: Option<DrawListCallback>,
+    ) {
+        self.draw_list_callback = draw_list_callback;
+    }
+
+    /// Replace the [`RendererOptions::color_adjustment`] of this
+    /// [`Renderer`].
+    ///
+    /// Changing the brightness or gamma is cheap, so this can be called every
+    /// frame to follow the scene behind an overlay. Enabling or disabling the
+    /// adjustment recompiles the pixel shader.
+    pub fn set_color_adjustment(
+        &mut self,
+        color_adjustment: Option<ColorAdjustment>,
+    ) -> Result<()> {
+        self.options.color_adjustment = color_adjustment;
+        self.update_pixel_shader()
+    }
+
+    /// Replace the [`RendererOptions::gamma_lut`] of this [`Renderer`], for
+    /// example when the gamma slider of a game moves. Replacing the texture
+    /// is cheap; enabling or disabling the lookup table recompiles the pixel
+    /// shader.
+    pub fn set_gamma_lut(
+        &mut self,
+        gamma_lut: Option<ID3D10ShaderResourceView>,
+    ) -> Result<()> {
+        self.options.gamma_lut = gamma_lut;
+        self.update_pixel_shader()
+    }
+
+    /// Replace the [`RendererOptions::text_gamma`] of this [`Renderer`], for
+    /// example to follow a text weight preference of the user. Enabling or
+    /// disabling the adjustment recompiles the pixel shader.
+    pub fn set_text_gamma(&mut self, text_gamma: Option<f32>) -> Result<()> {
+        self.options.text_gamma = text_gamma;
+        self.update_pixel_shader()
+    }
+
+    /// Replace the [`RendererOptions::linear_blending`] of this [`Renderer`],
+    /// for example to compare styles tuned for either blending space.
+    /// Enabling it for the first time compiles the color space conversion
+    /// into the pixel shader.
+    pub fn set_linear_blending(&mut self, linear_blending: bool) -> Result<()> {
+        self.options.linear_blending = linear_blending;
+        self.invalidate_dirty_rect();
+        self.update_pixel_shader()
+    }
+
+    /// Set the cursor drawn when [`RendererOptions::software_cursor`] is
+    /// enabled. Pass the [`egui::PlatformOutput::cursor_icon`] of every frame
+    /// here before calling [`Renderer::render`].
+    #[cfg(feature = "software-cursor")]
+    pub fn set_cursor_icon(&mut self, cursor_icon: egui::CursorIcon) {
+        if let Some(software_cursor) = &mut self.software_cursor {
+            software_cursor.set_icon(cursor_icon);
+        }
     }
 
     /// Register a user-provided `ID3D10ShaderResourceView` and get a [`egui::TextureId`] for it.
     ///
     /// This allows you to use your own DirectX10 textures within egui. The returned
     /// [`egui::TextureId`] can be used with [`egui::Image`], [`egui::ImageButton`], or
-    /// any other egui widget that accepts a texture ID.
+    /// any other egui widget that accepts a texture ID. Requires the
+    /// `user-textures` feature, which is enabled by default.
     ///
     /// The texture will remain registered until you call [`Renderer::unregister_user_texture`]
     /// or the [`Renderer`] is dropped.
@@ -185,11 +824,63 @@ impl Renderer {
     ///     egui::vec2(256.0, 256.0),
     /// )));
     /// ```
+    #[cfg(feature = "user-textures")]
     pub fn register_user_texture(
         &mut self,
         srv: ID3D10ShaderResourceView,
     ) -> egui::TextureId {
-        self.texture_pool.register_user_texture(srv)
+        self.texture_pool
+            .textures_mut()
+            .register_user_texture(srv, ColorSpace::Srgb)
+    }
+
+    /// Register a user-provided `ID3D10ShaderResourceView` whose contents are
+    /// encoded in `color_space`, and get a [`egui::TextureId`] for it.
+    ///
+    /// This works like [`Renderer::register_user_texture`], except that the
+    /// sampled texels are converted from `color_space` to the sRGB color
+    /// space of `egui` before blending, and then to
+    /// [`RendererOptions::target_color_space`] along with everything else.
+    /// Use this for video frames decoded in [`ColorSpace::Rec709`], so that
+    /// they and the UI drawn around them end up with matching grays.
+    ///
+    /// Registering the first texture with a color space other than
+    /// [`ColorSpace::Srgb`] recompiles the pixel shader on the next call to
+    /// [`Renderer::render`].
+    #[cfg(feature = "user-textures")]
+    pub fn register_user_texture_with_color_space(
+        &mut self,
+        srv: ID3D10ShaderResourceView,
+        color_space: ColorSpace,
+    ) -> egui::TextureId {
+        self.texture_pool
+            .textures_mut()
+            .register_user_texture(srv, color_space)
+    }
+
+    /// Register a user-provided `ID3D10ShaderResourceView` that is drawn with
+    /// `pixel_shader` instead of the built-in one, and get a
+    /// [`egui::TextureId`] for it.
+    ///
+    /// Use this for effects that only apply to one image, such as a
+    /// checkerboard behind transparent pixels or showing a single channel in
+    /// a texture viewer. The shader receives the outputs of the built-in
+    /// vertex shader: `SV_POSITION`, `TEXCOORD` (a `float2`) and `COLOR` (a
+    /// `float4` holding the premultiplied, gamma-encoded vertex color), and
+    /// the texture and sampler are bound at `t0` and `s0`. Its output is
+    /// blended as premultiplied alpha.
+    #[cfg(feature = "user-textures")]
+    pub fn register_user_texture_with_pixel_shader(
+        &mut self,
+        srv: ID3D10ShaderResourceView,
+        pixel_shader: ID3D10PixelShader,
+    ) -> egui::TextureId {
+        let tid = self
+            .texture_pool
+            .textures_mut()
+            .register_user_texture(srv, ColorSpace::Srgb);
+        self.texture_pixel_shaders.insert(tid, pixel_shader);
+        tid
     }
 
     /// Unregister a user texture by its [`egui::TextureId`].
@@ -197,8 +888,69 @@ impl Renderer {
     /// Returns `true` if the texture was found and removed, `false` otherwise.
     /// Note that this only works for user-registered textures, not textures
     /// managed by egui itself.
+    #[cfg(feature = "user-textures")]
     pub fn unregister_user_texture(&mut self, tid: egui::TextureId) -> bool {
-        self.texture_pool.unregister_user_texture(tid)
+        self.user_constants.remove_texture(tid);
+        self.texture_pixel_shaders.remove(&tid);
+        self.texture_pool
+            .textures_mut()
+            .unregister_user_texture(tid)
+    }
+
+    /// Apply [`RendererOutput::textures_delta`] without drawing anything.
+    ///
+    /// [`Renderer::render`] does this itself; call this instead when the
+    /// shapes of a frame are drawn separately, or not at all, so that the
+    /// textures of `egui` stay in sync. The textures belong to the first
+    /// context drawn by the renderer; use
+    /// [`Renderer::update_context_textures`] for the others.
+    ///
+    /// In debug builds, `device_context` is checked to be the device the
+    /// renderer was created on, returning `E_INVALIDARG` otherwise.
+    pub fn update_textures(
+        &mut self,
+        device_context: &ID3D10Device,
+        textures_delta: TexturesDelta,
+    ) -> Result<()> {
+        self.check_device_context(device_context)?;
+        self.update_textures_in(Namespace::default(), textures_delta)
+    }
+
+    fn update_textures_in(
+        &mut self,
+        namespace: Namespace,
+        textures_delta: TexturesDelta,
+    ) -> Result<()> {
+        let _device_lock = self.lock_device()?;
+        self.texture_pool
+            .textures_mut()
+            .update(namespace.apply_to_delta(textures_delta))?;
+        self.update_pixel_shader()
+    }
+
+    /// Release the memory the renderer only needs while drawing, such as
+    /// the intermediate texture, the vertex and index buffers and the copies
+    /// of the render target taken for [`RendererOptions::backdrop_blur`], for
+    /// example when the window is minimized or the application is suspended.
+    /// They are recreated on the next call to [`Renderer::render`].
+    ///
+    /// With `evict_textures`, the GPU textures of the images managed by
+    /// `egui`, such as the font atlas, are released as well, and recreated
+    /// from their pixels on the next call to [`Renderer::render`] or
+    /// [`Renderer::update_textures`]. User textures and the texture returned
+    /// by [`Renderer::capture_background`] are owned elsewhere and kept.
+    pub fn trim(&mut self, evict_textures: bool) -> Result<()> {
+        let _device_lock = self.lock_device()?;
+        self.intermediate = None;
+        self.geometry.release();
+        // With damage tracking, the intermediate texture held the UI of the
+        // last frame.
+        self.invalidate_dirty_rect();
+        self.trim_backdrop()?;
+        if evict_textures {
+            self.texture_pool.textures_mut().evict();
+        }
+        Ok(())
     }
 
     /// Render the output of `egui` to the provided `render_target`.
@@ -208,9 +960,28 @@ impl Renderer {
     /// non-sRGB-aware** (i.e. do NOT use `_SRGB` format in the texture and
     /// the view).
     ///
+    /// Render target views with an 8-bit `_SRGB` format of a `_TYPELESS` or
+    /// `_UNORM` texture, such as back buffers of swap chains viewed as sRGB,
+    /// are accepted: the UI is drawn through a `_UNORM` view of the same
+    /// texture, created by the renderer.
+    ///
     /// If you have to render to a render target in linear color space or
-    /// one that is sRGB-aware, you must create an intermediate render target
-    /// in gamma color space and perform a blit operation afterwards.
+    /// another one that is sRGB-aware, either set
+    /// [`RendererOptions::target_color_space`] to [`ColorSpace::Linear`],
+    /// accepting blending in linear space, or enable
+    /// [`RendererOptions::srgb_intermediate`] for views with an `_SRGB`
+    /// format, which draws the UI to an intermediate render target in gamma
+    /// color space and blits it to the render target. To blend in linear
+    /// space on purpose, enable [`RendererOptions::linear_blending`].
+    ///
+    /// 10-bit `DXGI_FORMAT_R10G10B10A2_UNORM` render targets are supported.
+    /// As their 2-bit alpha channel cannot hold meaningful coverage, the
+    /// alpha channel of such targets is left untouched, as with
+    /// [`AlphaOutput::PreserveDestination`].
+    ///
+    /// Views of mip levels other than 0, and of other slices of texture
+    /// arrays, are drawn to at the size of that mip level, for example to
+    /// build mip chains or thumbnails of a UI.
     ///
     /// The `scale_factor` should be the scale factor of your window and not
     /// confused with [`egui::Context::zoom_factor`]. If you are using `winit`,
@@ -225,14 +996,21 @@ impl Renderer {
     /// If the device has been lost, you should drop the [`Renderer`] and create
     /// a new one.
     ///
+    /// In debug builds, `device_context` and the device of `render_target`
+    /// are checked to be the device the renderer was created on, returning
+    /// `E_INVALIDARG` otherwise.
+    ///
     /// ## Pipeline State Management
     ///
     /// This function sets up its own Direct3D10 pipeline state for rendering on
     /// the provided device context. It assumes that the hull shader, domain
     /// shader and geometry shader stages are not active on the provided device
-    /// context without any further checks. It is all *your* responsibility to
-    /// backup the current pipeline state and restore it afterwards if your
-    /// rendering pipeline depends on it.
+    /// context without any further checks, unless
+    /// [`RendererOptions::disable_unused_stages`] is enabled. It is all
+    /// *your* responsibility to backup the current pipeline state and restore
+    /// it afterwards if your rendering pipeline depends on it, unless
+    /// [`RendererOptions::preserve_device_state`] is enabled, which restores
+    /// all of the state listed below.
     ///
     /// Particularly, it overrides:
     /// + The input layout, vertex buffer, index buffer and primitive topology
@@ -242,6 +1020,16 @@ impl Renderer {
     /// + The current shader, shader resource slot 0 and sampler slot 0 in the
     ///   pixel shader stage;
     /// + The render target(s) and blend state in the output merger stage;
+    ///
+    /// If [`RendererOptions::dithering`],
+    /// [`RendererOptions::color_transform`] or
+    /// [`RendererOptions::color_adjustment`] is enabled, it also overrides the
+    /// constant buffer slot 0 in the pixel shader stage. A
+    /// [`ColorTransform::Lut`] additionally overrides the shader resource
+    /// slot 1 and sampler slot 1 in the pixel shader stage, and
+    /// [`RendererOptions::gamma_lut`] the shader resource slot 2 and sampler
+    /// slot 1. The same constant buffer slot is used for conversion between
+    /// [`ColorSpace`]s.
     pub fn render(
         &mut self,
         device_context: &ID3D10Device,
@@ -249,141 +1037,799 @@ impl Renderer {
         egui_ctx: &egui::Context,
         egui_output: RendererOutput,
     ) -> Result<()> {
-        self.texture_pool
-            .update(device_context, egui_output.textures_delta)?;
+        let frame_size = Self::get_render_target_size(render_target)?;
+        self.render_region(
+            device_context,
+            render_target,
+            (0, 0),
+            frame_size,
+            None,
+            egui_ctx,
+            egui_output,
+        )
+    }
 
-        if egui_output.shapes.is_empty() {
-            return Ok(());
-        }
+    /// Clear the whole `render_target` to `clear_color` and then render to it
+    /// like [`Renderer::render`], for standalone applications and offscreen
+    /// targets that should start every frame from a known state.
+    ///
+    /// `clear_color` is written to the render target as is, through the
+    /// same view as the UI, so it is in
+    /// [`RendererOptions::target_color_space`] and its alpha must be
+    /// premultiplied for transparent targets. Only `_SRGB` views of `_SRGB`
+    /// textures, which the UI is drawn through as well, convert it from
+    /// linear.
+    ///
+    /// The render target is cleared as part of the frame, while holding the
+    /// [`DeviceLock`] of [`RendererOptions::multithread_protected`] and within
+    /// the state saved for [`RendererOptions::preserve_device_state`].
+    pub fn render_with_clear(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        clear_color: [f32; 4],
+        egui_ctx: &egui::Context,
+        egui_output: RendererOutput,
+    ) -> Result<()> {
+        let frame_size = Self::get_render_target_size(render_target)?;
+        self.render_region(
+            device_context,
+            render_target,
+            (0, 0),
+            frame_size,
+            Some(clear_color),
+            egui_ctx,
+            egui_output,
+        )
+    }
 
+    /// Render the outputs of several `egui` contexts to `render_target`, in
+    /// order, like [`Renderer::render`] does with one, for example a game
+    /// HUD and a debug UI on top of it.
+    ///
+    /// The outputs are drawn in a single pass, sharing the pipeline setup,
+    /// the intermediate texture and the dirty rect of the frame, which is
+    /// cheaper than calling [`Renderer::render`] for each of them. Each
+    /// context keeps its own managed textures, see
+    /// [`Renderer::update_context_textures`].
+    pub fn render_many<'a>(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        outputs: impl IntoIterator<Item = (&'a egui::Context, RendererOutput)>,
+    ) -> Result<()> {
         let frame_size = Self::get_render_target_size(render_target)?;
-        let frame_size_scaled = (
-            frame_size.0 as f32 / egui_output.pixels_per_point,
-            frame_size.1 as f32 / egui_output.pixels_per_point,
+        self.render_outputs(
+            device_context,
+            render_target,
+            (0, 0),
+            frame_size,
+            None,
+            outputs.into_iter().collect(),
+        )
+    }
+
+    /// Take the lock of the device if
+    /// [`RendererOptions::multithread_protected`] is enabled.
+    pub(crate) fn lock_device(&self) -> Result<Option<DeviceLock>> {
+        self.options
+            .multithread_protected
+            .then(|| DeviceLock::new(&self.device))
+            .transpose()
+    }
+
+    /// Render to the region of `render_target` starting at `frame_origin`
+    /// with size `frame_size`, optionally clearing the region to `clear`
+    /// first.
+    #[allow(clippy::too_many_arguments)]
+    fn render_region(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        clear: Option<[f32; 4]>,
+        egui_ctx: &egui::Context,
+        egui_output: RendererOutput,
+    ) -> Result<()> {
+        self.render_outputs(
+            device_context,
+            render_target,
+            frame_origin,
+            frame_size,
+            clear,
+            vec![(egui_ctx, egui_output)],
+        )
+    }
+
+    /// Render the outputs of one or more contexts, in order, to the region
+    /// of `render_target` like [`Renderer::render_region`].
+    fn render_outputs(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        clear: Option<[f32; 4]>,
+        outputs: Vec<(&egui::Context, RendererOutput)>,
+    ) -> Result<()> {
+        self.check_device(device_context, render_target)?;
+        let _device_lock = self.lock_device()?;
+        let _state_guard = self
+            .options
+            .preserve_device_state
+            .then(|| DeviceStateGuard::new(device_context))
+            .transpose()?;
+        let gpu_timer = self.begin_stats(
+            frame_size,
+            outputs.iter().map(|(_, output)| &output.textures_delta),
         );
-        let zoom_factor = egui_ctx.zoom_factor();
+        let result = self
+            .draw_region(
+                device_context,
+                render_target,
+                frame_origin,
+                frame_size,
+                clear,
+                outputs,
+            )
+            .and_then(|()| {
+                self.copy_to_capture(
+                    device_context,
+                    render_target,
+                    frame_origin,
+                    frame_size,
+                )
+            });
+        drop(gpu_timer);
+        self.end_stats();
+        result
+    }
 
-        self.setup(device_context, render_target, frame_size);
-        let meshes = egui_ctx
-            .tessellate(egui_output.shapes, egui_output.pixels_per_point)
-            .into_iter()
-            .filter_map(
-                |ClippedPrimitive {
-                     primitive,
-                     clip_rect,
-                 }| match primitive {
-                    Primitive::Mesh(mesh) => Some((mesh, clip_rect)),
-                    Primitive::Callback(..) => {
-                        log::warn!("paint callbacks are not yet supported.");
-                        None
+    fn draw_region(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        clear: Option<[f32; 4]>,
+        outputs: Vec<(&egui::Context, RendererOutput)>,
+    ) -> Result<()> {
+        let render_target = &self.gamma_render_target(render_target);
+        let mut updated_textures = Vec::new();
+        let mut parts = Vec::with_capacity(outputs.len());
+        for (egui_ctx, egui_output) in outputs {
+            // The managed textures of every context live in a namespace of
+            // their own.
+            let namespace = self.texture_pool.namespaces_mut().get(egui_ctx);
+            let textures_delta =
+                namespace.apply_to_delta(egui_output.textures_delta);
+            if self.dirty_rect.is_some() {
+                updated_textures.extend(
+                    textures_delta
+                        .set
+                        .iter()
+                        .map(|(texture_id, _)| *texture_id),
+                );
+            }
+            self.texture_pool.textures_mut().update(textures_delta)?;
+            parts.push(FramePart {
+                egui_ctx,
+                namespace,
+                shapes: layers::strip_markers(egui_output.shapes),
+                pixels_per_point: self
+                    .options
+                    .pixels_per_point
+                    .unwrap_or(egui_output.pixels_per_point),
+                zoom_factor: self.zoom_factor(egui_ctx),
+            });
+        }
+        self.update_pixel_shader()?;
+
+        // The cursor is drawn on top of the last output.
+        #[cfg(feature = "software-cursor")]
+        if let Some(software_cursor) = &mut self.software_cursor
+            && let Some(part) = parts.last_mut()
+        {
+            software_cursor.paint(
+                &mut self.texture_pool.textures_mut(),
+                part.egui_ctx,
+                1.0 / (part.pixels_per_point * part.zoom_factor),
+                &mut part.shapes,
+            )?;
+        }
+
+        let frame_format = Self::get_render_target_format(render_target);
+        let is_empty = parts.iter().all(|part| part.shapes.is_empty());
+        if is_empty {
+            self.track_dirty_rect(
+                frame_origin,
+                frame_size,
+                frame_format,
+                &[],
+                &[],
+            );
+            // The intermediate texture is not drawn to, so it is stale in
+            // the next frame.
+            self.invalidate_dirty_rect();
+            if clear.is_none() && !self.keys_region() {
+                return Ok(());
+            }
+        }
+
+        let _unused_stages = self
+            .options
+            .disable_unused_stages
+            .then(|| UnusedStages::disable(device_context));
+        if cfg!(debug_assertions) && self.options.validate_pipeline {
+            pipeline::validate(device_context);
+        }
+        let _frame_timer =
+            self.dynamic_scale.as_mut().and_then(DynamicScale::begin);
+        self.capture_backdrop(
+            device_context,
+            render_target,
+            frame_origin,
+            frame_size,
+        )?;
+        self.setup(
+            device_context,
+            render_target,
+            frame_origin,
+            frame_size,
+            frame_format,
+        );
+        if let Some(clear_color) = clear {
+            if frame_origin == (0, 0)
+                && frame_size == Self::get_render_target_size(render_target)?
+            {
+                unsafe {
+                    device_context
+                        .ClearRenderTargetView(render_target, &clear_color)
+                };
+            } else {
+                self.clear_region(
+                    device_context,
+                    frame_origin,
+                    frame_size,
+                    frame_format,
+                    clear_color,
+                )?;
+            }
+        }
+        self.key_region(
+            device_context,
+            frame_origin,
+            frame_size,
+            frame_format,
+        )?;
+        if is_empty {
+            return Ok(());
+        }
+
+        // With a post process pass, a render scale, an sRGB render target or
+        // linear blending, the UI is drawn to an intermediate texture first.
+        let intermediate_size =
+            self.intermediate_size(frame_size, frame_format);
+        let intermediate = match intermediate_size {
+            Some(size) => Some(self.intermediate_target(
+                size,
+                self.intermediate_kind(frame_format),
+            )?),
+            None => None,
+        };
+        if let (Some((intermediate, _)), Some(size)) =
+            (&intermediate, intermediate_size)
+        {
+            // With damage tracking, the UI of the last frame is kept and only
+            // the damaged part is cleared once it is known.
+            if !self.options.damage_tracking {
+                unsafe {
+                    device_context.ClearRenderTargetView(intermediate, &[0.; 4])
+                };
+            }
+            self.setup(
+                device_context,
+                intermediate,
+                (0, 0),
+                size,
+                frame_format,
+            );
+            unsafe {
+                device_context.OMSetBlendState(
+                    &self.blend_state,
+                    &[0.; 4],
+                    u32::MAX,
+                );
+            }
+        }
+
+        let mut meshes = Vec::new();
+        for part in parts {
+            let frame = FrameInfo {
+                origin: match intermediate {
+                    Some(_) => (0, 0),
+                    None => frame_origin,
+                },
+                size: intermediate_size.unwrap_or(frame_size),
+                format: frame_format,
+                pixels_per_point: match intermediate_size {
+                    Some(size) => {
+                        part.pixels_per_point * size.0 as f32
+                            / frame_size.0 as f32
                     },
+                    None => part.pixels_per_point,
                 },
-            )
-            .filter_map(|(mesh, clip_rect)| {
-                if mesh.indices.is_empty() {
-                    return None;
+                zoom_factor: part.zoom_factor,
+                snap_to_pixels: self.options.snap_to_pixels,
+            };
+            let primitives =
+                part.egui_ctx.tessellate(part.shapes, part.pixels_per_point);
+            let part_primitives = primitives.into_iter().filter_map(
+                |ClippedPrimitive {
+                     mut primitive,
+                     mut clip_rect,
+                 }| {
+                    if let Primitive::Mesh(mesh) = &mut primitive {
+                        let keep = self
+                            .mesh_hook
+                            .as_mut()
+                            .is_none_or(|hook| hook(mesh, &mut clip_rect));
+                        mesh.texture_id = part.namespace.apply(mesh.texture_id);
+                        if !keep {
+                            return None;
+                        }
+                    }
+                    Some((primitive, clip_rect))
+                },
+            );
+            meshes.extend(frame.convert_primitives(part_primitives));
+        }
+        if let Some(frame_limits) = &self.options.frame_limits {
+            frame_limits.truncate(&mut meshes);
+        }
+        if let Some(callback) = &mut self.draw_list_callback {
+            let draw_list = meshes
+                .iter()
+                .filter(|mesh| mesh.callback.is_none())
+                .map(|mesh| DrawCommand {
+                    texture_id: namespace::strip(mesh.tex),
+                    vertex_count: mesh.vtx.len(),
+                    index_count: mesh.idx.len(),
+                    clip_rect: mesh.clip_rect,
+                })
+                .collect::<Vec<_>>();
+            callback(&draw_list);
+        }
+        self.count_meshes(&meshes);
+        self.track_dirty_rect(
+            frame_origin,
+            frame_size,
+            frame_format,
+            &meshes,
+            &updated_textures,
+        );
+        let meshes = match self.damaged_rect() {
+            Some(damaged_rect) => {
+                self.redraw_damaged_rect(device_context, damaged_rect, meshes)?
+            },
+            None => meshes,
+        };
+        let ranges = self.geometry.upload(device_context, &meshes)?;
+        let mut texture_constants = TextureConstants::default();
+        let mut unsubmitted_vertices = 0;
+        for (mesh, range) in meshes.into_iter().zip(ranges) {
+            if let Some(callback) = &mesh.callback {
+                callback.call(
+                    device_context,
+                    mesh.clip_rect,
+                    self.options.depth_range,
+                );
+                // The callback may have changed any state.
+                match (&intermediate, intermediate_size) {
+                    (Some((intermediate, _)), Some(size)) => {
+                        self.setup(
+                            device_context,
+                            intermediate,
+                            (0, 0),
+                            size,
+                            frame_format,
+                        );
+                        unsafe {
+                            device_context.OMSetBlendState(
+                                &self.blend_state,
+                                &[0.; 4],
+                                u32::MAX,
+                            );
+                        }
+                    },
+                    _ => self.setup(
+                        device_context,
+                        render_target,
+                        frame_origin,
+                        frame_size,
+                        frame_format,
+                    ),
                 }
-                if mesh.indices.len() % 3 != 0 {
-                    log::warn!(concat!(
-                        "egui wants to draw a incomplete triangle. ",
-                        "this request will be ignored."
-                    ));
-                    return None;
+                texture_constants = TextureConstants::default();
+                continue;
+            }
+            if let Some(time_slicing) = &self.options.time_slicing {
+                if unsubmitted_vertices >= time_slicing.vertices_per_submission
+                {
+                    unsafe { device_context.Flush() };
+                    unsubmitted_vertices = 0;
                 }
-                Some(MeshData {
-                    vtx: mesh
-                        .vertices
-                        .into_iter()
-                        .map(|Vertex { pos, uv, color }| VertexData {
-                            pos: Pos2::new(
-                                pos.x * zoom_factor / frame_size_scaled.0 * 2.0
-                                    - 1.0,
-                                1.0 - pos.y * zoom_factor / frame_size_scaled.1
-                                    * 2.0,
-                            ),
-                            uv,
-                            color: [
-                                color[0] as f32 / 255.0,
-                                color[1] as f32 / 255.0,
-                                color[2] as f32 / 255.0,
-                                color[3] as f32 / 255.0,
-                            ],
-                        })
-                        .collect(),
-                    idx: mesh.indices,
-                    tex: mesh.texture_id,
-                    clip_rect: clip_rect
-                        * egui_output.pixels_per_point
-                        * zoom_factor,
-                })
-            });
-        for mesh in meshes {
-            Self::draw_mesh(
-                &self.device,
+                unsubmitted_vertices += mesh.vtx.len();
+            }
+            if self.constant_buffer.is_some()
+                && self.texture_constants(mesh.tex) != texture_constants
+            {
+                texture_constants = self.texture_constants(mesh.tex);
+                self.update_constants(
+                    device_context,
+                    frame_format,
+                    texture_constants,
+                );
+            }
+            self.user_constants.bind_texture(device_context, mesh.tex);
+            self.bind_pixel_shader(device_context, mesh.tex);
+            self.bind_sampler(device_context, mesh.tex);
+            if let Some((vertex_buffer, index_buffer)) = self.geometry.buffers()
+            {
+                Self::draw_mesh(
+                    device_context,
+                    &self.texture_pool.textures(),
+                    (vertex_buffer, index_buffer),
+                    range,
+                    &mesh,
+                );
+            }
+        }
+
+        if let Some((_, intermediate)) = intermediate {
+            self.setup(
                 device_context,
-                &self.texture_pool,
-                mesh,
+                render_target,
+                frame_origin,
+                frame_size,
+                frame_format,
+            );
+            self.draw_intermediate(
+                device_context,
+                &intermediate,
+                frame_origin,
+                frame_size,
             )?;
         }
 
         Ok(())
     }
 
+    /// The factor positions are scaled by on top of the pixels per point.
+    fn zoom_factor(&self, egui_ctx: &egui::Context) -> f32 {
+        if self.options.ignore_zoom_factor {
+            1.
+        } else {
+            egui_ctx.zoom_factor()
+        }
+    }
+
     fn setup(
         &mut self,
         ctx: &ID3D10Device,
         render_target: &ID3D10RenderTargetView,
+        frame_origin: (i32, i32),
         frame_size: (u32, u32),
+        frame_format: DXGI_FORMAT,
     ) {
+        let blend_state = self.blend_state_for(frame_format);
         unsafe {
             ctx.IASetPrimitiveTopology(D3D10_PRIMITIVE_TOPOLOGY_TRIANGLELIST);
             ctx.IASetInputLayout(&self.input_layout);
-            ctx.VSSetShader(&self.vertex_shader);
+            ctx.VSSetShader(
+                self.vertex_shader_override
+                    .as_ref()
+                    .unwrap_or(&self.vertex_shader),
+            );
             ctx.PSSetShader(&self.pixel_shader);
             ctx.RSSetState(&self.rasterizer_state);
             ctx.RSSetViewports(Some(&[D3D10_VIEWPORT {
-                TopLeftX: 0,
-                TopLeftY: 0,
+                TopLeftX: frame_origin.0,
+                TopLeftY: frame_origin.1,
                 Width: frame_size.0 as _,
                 Height: frame_size.1 as _,
-                MinDepth: 0.,
-                MaxDepth: 1.,
+                MinDepth: self.options.depth_range.min_depth,
+                MaxDepth: self.options.depth_range.max_depth,
             }]));
             ctx.PSSetSamplers(0, Some(&[Some(self.sampler_state.clone())]));
             ctx.OMSetRenderTargets(Some(&[Some(render_target.clone())]), None);
-            ctx.OMSetBlendState(&self.blend_state, &[0.; 4], u32::MAX);
+            ctx.OMSetBlendState(blend_state, &[0.; 4], u32::MAX);
+        }
+        if let Some(constant_buffer) = &self.constant_buffer {
+            self.update_constants(
+                ctx,
+                frame_format,
+                TextureConstants::default(),
+            );
+            unsafe {
+                ctx.PSSetConstantBuffers(
+                    0,
+                    Some(&[Some(constant_buffer.clone())]),
+                );
+            }
+        }
+        self.user_constants.bind(ctx);
+        if let Some(ColorTransform::Lut(lut)) = &self.options.color_transform {
+            unsafe {
+                ctx.PSSetShaderResources(1, Some(&[Some(lut.clone())]));
+                ctx.PSSetSamplers(
+                    1,
+                    Some(&[Some(self.sampler_state_clamp.clone())]),
+                );
+            }
+        }
+        if let Some(gamma_lut) = &self.options.gamma_lut {
+            unsafe {
+                ctx.PSSetShaderResources(2, Some(&[Some(gamma_lut.clone())]));
+                ctx.PSSetSamplers(
+                    1,
+                    Some(&[Some(self.sampler_state_clamp.clone())]),
+                );
+            }
         }
     }
 
-    fn draw_mesh(
-        device: &ID3D10Device,
-        device_context: &ID3D10Device,
-        texture_pool: &TexturePool,
-        mesh: MeshData,
+    /// Bind the pixel shader registered for `texture_id`, or the built-in
+    /// one. Does nothing unless some texture has its own shader.
+    fn bind_pixel_shader(
+        &self,
+        ctx: &ID3D10Device,
+        texture_id: egui::TextureId,
+    ) {
+        if self.texture_pixel_shaders.is_empty() {
+            return;
+        }
+        let pixel_shader = self
+            .texture_pixel_shaders
+            .get(&texture_id)
+            .unwrap_or(&self.pixel_shader);
+        unsafe { ctx.PSSetShader(pixel_shader) };
+    }
+
+    /// Bind the sampler matching the [`egui::TextureWrapMode`] of
+    /// `texture_id`, or the one of [`RendererOptions::user_texture_filter`]
+    /// for user textures.
+    fn bind_sampler(&self, ctx: &ID3D10Device, texture_id: egui::TextureId) {
+        let sampler_state =
+            match self.texture_pool.textures().wrap_mode(texture_id) {
+                Some(egui::TextureWrapMode::ClampToEdge) => {
+                    &self.sampler_state_clamp
+                },
+                Some(egui::TextureWrapMode::Repeat) => {
+                    &self.sampler_state_repeat
+                },
+                Some(egui::TextureWrapMode::MirroredRepeat) => {
+                    &self.sampler_state_mirror
+                },
+                None => match self.options.user_texture_filter {
+                    ScaleFilter::Linear => &self.sampler_state,
+                    ScaleFilter::Point => &self.sampler_state_point,
+                },
+            };
+        unsafe { ctx.PSSetSamplers(0, Some(&[Some(sampler_state.clone())])) };
+    }
+
+    fn blend_state_for(&self, frame_format: DXGI_FORMAT) -> &ID3D10BlendState {
+        if let Some(blend_state) = &self.blend_state_override {
+            return blend_state;
+        }
+        if let Some([composite, no_alpha]) = self.subpixel_blend_states() {
+            return if Self::has_narrow_alpha(frame_format)
+                || self.options.alpha_output == AlphaOutput::PreserveDestination
+            {
+                no_alpha
+            } else {
+                composite
+            };
+        }
+        match self.options.blend_mode {
+            BlendMode::PremultipliedOver
+                if Self::has_narrow_alpha(frame_format)
+                    || self.options.alpha_output
+                        == AlphaOutput::PreserveDestination =>
+            {
+                &self.blend_state_no_alpha
+            },
+            BlendMode::PremultipliedOver => &self.blend_state,
+            BlendMode::PreserveDstAlpha => &self.blend_state_no_alpha,
+            BlendMode::Additive => &self.blend_state_additive,
+            BlendMode::Opaque => &self.blend_state_opaque,
+        }
+    }
+
+    /// Overwrite the region with `color` by drawing a quad with blending
+    /// disabled. `ClearRenderTargetView` cannot be used as it always clears
+    /// the whole render target.
+    fn clear_region(
+        &self,
+        ctx: &ID3D10Device,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        frame_format: DXGI_FORMAT,
+        color: [f32; 4],
     ) -> Result<()> {
-        let ib = Self::create_index_buffer(device, &mesh.idx)?;
-        let vb = Self::create_vertex_buffer(device, &mesh.vtx)?;
         unsafe {
-            device_context.IASetVertexBuffers(
+            ctx.PSSetShaderResources(0, Some(&[None]));
+            ctx.OMSetBlendState(&self.blend_state_opaque, &[0.; 4], u32::MAX);
+        }
+        self.draw_quad(ctx, frame_origin, frame_size, color)?;
+        unsafe {
+            ctx.OMSetBlendState(
+                self.blend_state_for(frame_format),
+                &[0.; 4],
+                u32::MAX,
+            );
+        }
+        Ok(())
+    }
+
+    /// Draw a quad covering the region, with texture coordinates from
+    /// `(0, 0)` at the top left to `(1, 1)` at the bottom right.
+    fn draw_quad(
+        &self,
+        ctx: &ID3D10Device,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+        color: [f32; 4],
+    ) -> Result<()> {
+        let (vb, ib) = self.quad.write(ctx, color)?;
+        ctx.draw_indexed(
+            vb,
+            ib,
+            DrawRange {
+                index_count: 6,
+                start_index: 0,
+                base_vertex: 0,
+            },
+            RECT {
+                left: frame_origin.0,
+                top: frame_origin.1,
+                right: frame_origin.0 + frame_size.0 as i32,
+                bottom: frame_origin.1 + frame_size.1 as i32,
+            },
+            None,
+        );
+        Ok(())
+    }
+
+    /// The dual-source blend states of [`RendererOptions::subpixel_text`],
+    /// if it is enabled and not superseded by other blending options.
+    fn subpixel_blend_states(&self) -> Option<&[ID3D10BlendState; 2]> {
+        self.blend_state_subpixel.as_ref().filter(|_| {
+            self.options.blend_mode == BlendMode::PremultipliedOver
+                && self.blend_state_override.is_none()
+                && self.post_process.is_none()
+                && self.options.render_scale.is_none()
+                && self.dynamic_scale.is_none()
+                && !self.options.damage_tracking
+        })
+    }
+
+    fn texture_constants(
+        &self,
+        texture_id: egui::TextureId,
+    ) -> TextureConstants {
+        TextureConstants {
+            color_space: self.texture_pool.textures().color_space(texture_id),
+            font_atlas: namespace::is_font_atlas(texture_id),
+        }
+    }
+
+    fn update_constants(
+        &self,
+        ctx: &ID3D10Device,
+        frame_format: DXGI_FORMAT,
+        texture: TextureConstants,
+    ) {
+        let Some(constant_buffer) = &self.constant_buffer else {
+            return;
+        };
+        let constants = PixelConstants {
+            dither_scale: match self.options.dithering {
+                Some(_) => Self::dither_scale(frame_format),
+                None => 0.,
+            },
+            texture_color_space: texture.color_space as u32,
+            target_color_space: self.target_color_space(frame_format) as u32,
+            subpixel_text: (texture.font_atlas
+                && self.subpixel_blend_states().is_some())
+                as u32,
+            color_matrix: match &self.options.color_transform {
+                Some(ColorTransform::Matrix(m)) => [
+                    [m[0][0], m[0][1], m[0][2], 0.],
+                    [m[1][0], m[1][1], m[1][2], 0.],
+                    [m[2][0], m[2][1], m[2][2], 0.],
+                ],
+                _ => [[0.; 4]; 3],
+            },
+            color_adjustment: match self.options.color_adjustment {
+                Some(ColorAdjustment { brightness, gamma }) => {
+                    [brightness, 1. / gamma]
+                },
+                None => [1., 1.],
+            },
+            text_gamma: match self.options.text_gamma {
+                Some(gamma) if texture.font_atlas => gamma,
+                _ => 1.,
+            },
+            _padding: 0.,
+        };
+        unsafe {
+            ctx.UpdateSubresource(
+                constant_buffer,
+                0,
+                None,
+                bytemuck::bytes_of(&constants).as_ptr() as _,
+                0,
                 0,
-                1,
-                Some(&Some(vb.clone())),
-                Some(&(mem::size_of::<VertexData>() as _)),
-                Some(&0),
             );
-            device_context.IASetIndexBuffer(&ib.clone(), DXGI_FORMAT_R32_UINT, 0);
-            device_context.RSSetScissorRects(Some(&[RECT {
-                left: mesh.clip_rect.left() as _,
-                top: mesh.clip_rect.top() as _,
-                right: mesh.clip_rect.right() as _,
-                bottom: mesh.clip_rect.bottom() as _,
-            }]));
         }
-        if let Some(srv) = texture_pool.get_srv(mesh.tex) {
-            unsafe {
-                device_context.PSSetShaderResources(0, Some(&[Some(srv.clone())]))
-            };
-        } else {
+    }
+
+    /// Switch to the variant of the pixel shader the options and the
+    /// registered textures require, compiling it if this device has not
+    /// used it yet.
+    fn update_pixel_shader(&mut self) -> Result<()> {
+        let permutation = Permutation::new(
+            &self.options,
+            self.texture_pool.textures().has_non_srgb_textures(),
+        );
+        if permutation == self.pixel_shader_permutation {
+            return Ok(());
+        }
+        self.pixel_shader = self
+            .pixel_shaders
+            .get_or_create(&self.device, permutation)?;
+        if permutation.is_empty() {
+            self.constant_buffer = None;
+        } else if self.constant_buffer.is_none() {
+            self.constant_buffer =
+                Some(Self::create_constant_buffer(&self.device)?);
+        }
+        self.pixel_shader_permutation = permutation;
+        Ok(())
+    }
+
+    /// Create the constant buffer of the compiled pixel shader variants.
+    fn create_constant_buffer(device: &ID3D10Device) -> Result<ID3D10Buffer> {
+        let mut constant_buffer = None;
+        unsafe {
+            device.CreateBuffer(
+                &D3D10_BUFFER_DESC {
+                    ByteWidth: mem::size_of::<PixelConstants>() as _,
+                    Usage: D3D10_USAGE_DEFAULT,
+                    BindFlags: D3D10_BIND_CONSTANT_BUFFER.0 as _,
+                    ..D3D10_BUFFER_DESC::default()
+                },
+                None,
+                Some(&mut constant_buffer),
+            )
+        }?;
+        Ok(constant_buffer.unwrap())
+    }
+
+    /// Draw `mesh` from the `range` of the vertex and index buffers it was
+    /// uploaded to.
+    fn draw_mesh<B: Backend>(
+        device: &B,
+        texture_pool: &TexturePool<B>,
+        (vertex_buffer, index_buffer): (&B::Buffer, &B::Buffer),
+        range: DrawRange,
+        mesh: &MeshData,
+    ) {
+        let srv = texture_pool.get_srv(mesh.tex);
+        if srv.is_none() {
             log::warn!(
                 concat!(
                     "egui wants to sample a non-existing texture {:?}.",
@@ -391,9 +1837,19 @@ impl Renderer {
                 ),
                 mesh.tex
             );
-        };
-        unsafe { device_context.DrawIndexed(mesh.idx.len() as _, 0, 0) };
-        Ok(())
+        }
+        device.draw_indexed(
+            vertex_buffer,
+            index_buffer,
+            range,
+            RECT {
+                left: mesh.clip_rect.left() as _,
+                top: mesh.clip_rect.top() as _,
+                right: mesh.clip_rect.right() as _,
+                bottom: mesh.clip_rect.bottom() as _,
+            },
+            srv.as_ref(),
+        );
     }
 }
 
@@ -457,6 +1913,32 @@ impl Renderer {
         MaxLOD: f32::MAX,
     };
 
+    const SAMPLER_DESC_CLAMP: D3D10_SAMPLER_DESC = D3D10_SAMPLER_DESC {
+        AddressU: D3D10_TEXTURE_ADDRESS_CLAMP,
+        AddressV: D3D10_TEXTURE_ADDRESS_CLAMP,
+        AddressW: D3D10_TEXTURE_ADDRESS_CLAMP,
+        ..Self::SAMPLER_DESC
+    };
+
+    const SAMPLER_DESC_POINT: D3D10_SAMPLER_DESC = D3D10_SAMPLER_DESC {
+        Filter: D3D10_FILTER_MIN_MAG_MIP_POINT,
+        ..Self::SAMPLER_DESC_CLAMP
+    };
+
+    const SAMPLER_DESC_REPEAT: D3D10_SAMPLER_DESC = D3D10_SAMPLER_DESC {
+        AddressU: D3D10_TEXTURE_ADDRESS_WRAP,
+        AddressV: D3D10_TEXTURE_ADDRESS_WRAP,
+        AddressW: D3D10_TEXTURE_ADDRESS_WRAP,
+        ..Self::SAMPLER_DESC
+    };
+
+    const SAMPLER_DESC_MIRROR: D3D10_SAMPLER_DESC = D3D10_SAMPLER_DESC {
+        AddressU: D3D10_TEXTURE_ADDRESS_MIRROR,
+        AddressV: D3D10_TEXTURE_ADDRESS_MIRROR,
+        AddressW: D3D10_TEXTURE_ADDRESS_MIRROR,
+        ..Self::SAMPLER_DESC
+    };
+
     const BLEND_DESC: D3D10_BLEND_DESC = D3D10_BLEND_DESC {
         AlphaToCoverageEnable: BOOL(0),
         BlendEnable: [
@@ -486,61 +1968,110 @@ impl Renderer {
             zeroed(),
         ],
     };
+
+    /// Used for render targets whose alpha channel is too narrow to hold
+    /// meaningful coverage, e.g. the 2-bit alpha of `R10G10B10A2`.
+    const BLEND_DESC_NO_ALPHA: D3D10_BLEND_DESC = {
+        let mut desc = Self::BLEND_DESC;
+        desc.RenderTargetWriteMask[0] = (D3D10_COLOR_WRITE_ENABLE_ALL.0
+            & !D3D10_COLOR_WRITE_ENABLE_ALPHA.0)
+            as _;
+        desc
+    };
+
+    const BLEND_DESC_OPAQUE: D3D10_BLEND_DESC = {
+        let mut desc = Self::BLEND_DESC;
+        desc.BlendEnable[0] = BOOL(0);
+        desc
+    };
+
+    /// Blends each color channel with its own alpha from `SV_TARGET1`, see
+    /// [`RendererOptions::subpixel_text`].
+    const BLEND_DESC_SUBPIXEL: D3D10_BLEND_DESC = {
+        let mut desc = Self::BLEND_DESC;
+        desc.DestBlend = D3D10_BLEND_INV_SRC1_COLOR;
+        desc
+    };
+
+    const BLEND_DESC_SUBPIXEL_NO_ALPHA: D3D10_BLEND_DESC = {
+        let mut desc = Self::BLEND_DESC_NO_ALPHA;
+        desc.DestBlend = D3D10_BLEND_INV_SRC1_COLOR;
+        desc
+    };
+
+    const BLEND_DESC_ADDITIVE: D3D10_BLEND_DESC = {
+        let mut desc = Self::BLEND_DESC;
+        desc.DestBlend = D3D10_BLEND_ONE;
+        desc
+    };
 }
 
 impl Renderer {
-    fn create_vertex_buffer(
-        device: &ID3D10Device,
-        data: &[VertexData],
-    ) -> Result<ID3D10Buffer> {
-        let mut vertex_buffer = None;
-        unsafe {
-            device.CreateBuffer(
-                &D3D10_BUFFER_DESC {
-                    ByteWidth: mem::size_of_val(data) as _,
-                    Usage: D3D10_USAGE_IMMUTABLE,
-                    BindFlags: D3D10_BIND_VERTEX_BUFFER.0 as _,
-                    ..D3D10_BUFFER_DESC::default()
-                },
-                Some(&D3D10_SUBRESOURCE_DATA {
-                    pSysMem: data.as_ptr() as _,
-                    ..D3D10_SUBRESOURCE_DATA::default()
-                }),
-                Some(&mut vertex_buffer),
-            )
-        }?;
-        Ok(vertex_buffer.unwrap())
+    /// The size of the mip level `rtv` views, so that views of mip levels
+    /// other than 0 get the viewport of that level.
+    pub(crate) fn get_render_target_size(
+        rtv: &ID3D10RenderTargetView,
+    ) -> Result<(u32, u32)> {
+        let tex = unsafe { rtv.GetResource() }?.cast::<ID3D10Texture2D>()?;
+        let mut desc = zeroed();
+        unsafe { tex.GetDesc(&mut desc) };
+        let (mip_slice, _) = Self::get_render_target_slice(rtv);
+        Ok((
+            (desc.Width >> mip_slice).max(1),
+            (desc.Height >> mip_slice).max(1),
+        ))
     }
 
-    fn create_index_buffer(
-        device: &ID3D10Device,
-        data: &[u32],
-    ) -> Result<ID3D10Buffer> {
-        let mut index_buffer = None;
+    /// The mip level and first array slice of the texture `rtv` views.
+    pub(crate) fn get_render_target_slice(
+        rtv: &ID3D10RenderTargetView,
+    ) -> (u32, u32) {
+        let mut desc: D3D10_RENDER_TARGET_VIEW_DESC = zeroed();
+        unsafe { rtv.GetDesc(&mut desc) };
         unsafe {
-            device.CreateBuffer(
-                &D3D10_BUFFER_DESC {
-                    ByteWidth: mem::size_of_val(data) as _,
-                    Usage: D3D10_USAGE_IMMUTABLE,
-                    BindFlags: D3D10_BIND_INDEX_BUFFER.0 as _,
-                    ..D3D10_BUFFER_DESC::default()
+            match desc.ViewDimension {
+                D3D10_RTV_DIMENSION_TEXTURE2D => {
+                    (desc.Anonymous.Texture2D.MipSlice, 0)
                 },
-                Some(&D3D10_SUBRESOURCE_DATA {
-                    pSysMem: data.as_ptr() as _,
-                    ..D3D10_SUBRESOURCE_DATA::default()
-                }),
-                Some(&mut index_buffer),
-            )
-        }?;
-        Ok(index_buffer.unwrap())
+                D3D10_RTV_DIMENSION_TEXTURE2DARRAY => (
+                    desc.Anonymous.Texture2DArray.MipSlice,
+                    desc.Anonymous.Texture2DArray.FirstArraySlice,
+                ),
+                D3D10_RTV_DIMENSION_TEXTURE2DMSARRAY => {
+                    (0, desc.Anonymous.Texture2DMSArray.FirstArraySlice)
+                },
+                _ => (0, 0),
+            }
+        }
     }
 
-    fn get_render_target_size(
+    pub(crate) fn get_render_target_format(
         rtv: &ID3D10RenderTargetView,
-    ) -> Result<(u32, u32)> {
-        let tex = unsafe { rtv.GetResource() }?.cast::<ID3D10Texture2D>()?;
+    ) -> DXGI_FORMAT {
         let mut desc = zeroed();
-        unsafe { tex.GetDesc(&mut desc) };
-        Ok((desc.Width, desc.Height))
+        unsafe { rtv.GetDesc(&mut desc) };
+        desc.Format
+    }
+
+    /// The size of one quantization step of the render target, or zero for
+    /// floating-point targets which do not need dithering.
+    fn dither_scale(format: DXGI_FORMAT) -> f32 {
+        match format {
+            DXGI_FORMAT_R10G10B10A2_TYPELESS
+            | DXGI_FORMAT_R10G10B10A2_UNORM => 1. / 1023.,
+            DXGI_FORMAT_R16G16B16A16_TYPELESS
+            | DXGI_FORMAT_R16G16B16A16_UNORM => 1. / 65535.,
+            DXGI_FORMAT_R16G16B16A16_FLOAT
+            | DXGI_FORMAT_R32G32B32A32_FLOAT
+            | DXGI_FORMAT_R11G11B10_FLOAT => 0.,
+            _ => 1. / 255.,
+        }
+    }
+
+    fn has_narrow_alpha(format: DXGI_FORMAT) -> bool {
+        matches!(
+            format,
+            DXGI_FORMAT_R10G10B10A2_TYPELESS | DXGI_FORMAT_R10G10B10A2_UNORM
+        )
     }
 }
@@ -0,0 +1,86 @@
+use crate::{FrameLimits, MeshData, Renderer};
+
+impl FrameLimits {
+    /// Drop the meshes from the first one over the limits on.
+    pub(crate) fn truncate(&self, meshes: &mut Vec<MeshData>) {
+        let mut vertices = 0;
+        let kept = meshes
+            .iter()
+            .take(self.max_meshes)
+            .take_while(|mesh| {
+                vertices += mesh.vtx.len();
+                vertices <= self.max_vertices
+            })
+            .count();
+        if kept < meshes.len() {
+            log::warn!(
+                "egui frame over the limits of {} meshes and {} vertices: \
+                 dropped {} of {} meshes.",
+                self.max_meshes,
+                self.max_vertices,
+                meshes.len() - kept,
+                meshes.len(),
+            );
+            meshes.truncate(kept);
+        }
+    }
+}
+
+impl Renderer {
+    /// Replace the [`RendererOptions::frame_limits`](crate::RendererOptions::frame_limits)
+    /// of this [`Renderer`].
+    pub fn set_frame_limits(&mut self, frame_limits: Option<FrameLimits>) {
+        self.options.frame_limits = frame_limits;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mesh(vertices: usize) -> MeshData {
+        MeshData {
+            vtx: vec![bytemuck::Zeroable::zeroed(); vertices],
+            idx: Vec::new(),
+            tex: egui::TextureId::Managed(0),
+            clip_rect: egui::Rect::EVERYTHING,
+            callback: None,
+        }
+    }
+
+    fn truncate(limits: FrameLimits, vertices: &[usize]) -> Vec<usize> {
+        let mut meshes = vertices.iter().map(|&n| mesh(n)).collect();
+        limits.truncate(&mut meshes);
+        meshes.iter().map(|mesh| mesh.vtx.len()).collect()
+    }
+
+    #[test]
+    fn frames_within_limits_are_kept() {
+        let limits = FrameLimits {
+            max_meshes: 3,
+            max_vertices: 12,
+        };
+        assert_eq!(truncate(limits, &[4, 4, 4]), [4, 4, 4]);
+        assert_eq!(truncate(limits, &[]), [0; 0]);
+    }
+
+    #[test]
+    fn meshes_over_the_mesh_limit_are_dropped() {
+        let limits = FrameLimits {
+            max_meshes: 2,
+            ..Default::default()
+        };
+        assert_eq!(truncate(limits, &[1, 2, 3, 4]), [1, 2]);
+    }
+
+    #[test]
+    fn meshes_from_the_first_over_the_vertex_limit_are_dropped() {
+        let limits = FrameLimits {
+            max_vertices: 10,
+            ..Default::default()
+        };
+        // The third mesh would fit on its own, but follows one that does not.
+        assert_eq!(truncate(limits, &[4, 8, 1]), [4]);
+        assert_eq!(truncate(limits, &[12]), [0; 0]);
+    }
+}
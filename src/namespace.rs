@@ -0,0 +1,213 @@
+use egui::{TextureId, TexturesDelta};
+
+use windows::core::Result;
+
+use crate::Renderer;
+
+/// The bits of a managed [`TextureId`] holding its namespace. `egui`
+/// numbers its textures sequentially, so it never reaches them.
+const NAMESPACE_SHIFT: u32 = 48;
+
+/// The texture namespaces of the `egui` contexts a renderer has drawn.
+///
+/// Every context numbers its managed textures from
+/// `TextureId::Managed(0)`, its font atlas. To keep the textures of several
+/// contexts apart, the first context a renderer sees keeps its ids, and the
+/// managed ids of each further one are moved to a namespace of their own.
+///
+/// Contexts are told apart by identity, which, unlike anything stored in
+/// their [`egui::Memory`], survives resetting it. They are kept alive until
+/// [`Renderer::remove_context`].
+#[derive(Default)]
+pub(crate) struct TextureNamespaces {
+    /// The contexts, by namespace.
+    contexts: Vec<Option<egui::Context>>,
+}
+
+/// A texture namespace, applied to the managed [`TextureId`]s of a context.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Namespace(u64);
+
+impl TextureNamespaces {
+    /// The namespace of `egui_ctx`, assigned on first use.
+    pub fn get(&mut self, egui_ctx: &egui::Context) -> Namespace {
+        if let Some(namespace) = self.position(egui_ctx) {
+            return Namespace(namespace as _);
+        }
+        let namespace = match self.contexts.iter().position(Option::is_none) {
+            Some(namespace) => {
+                self.contexts[namespace] = Some(egui_ctx.clone());
+                namespace
+            },
+            None => {
+                self.contexts.push(Some(egui_ctx.clone()));
+                self.contexts.len() - 1
+            },
+        };
+        Namespace(namespace as _)
+    }
+
+    /// Forget `egui_ctx`, and return its namespace, if it had one, to be
+    /// reused by other contexts.
+    fn remove(&mut self, egui_ctx: &egui::Context) -> Option<Namespace> {
+        let namespace = self.position(egui_ctx)?;
+        self.contexts[namespace] = None;
+        Some(Namespace(namespace as _))
+    }
+
+    /// The index of the namespace of `egui_ctx`, if it has one.
+    fn position(&self, egui_ctx: &egui::Context) -> Option<usize> {
+        self.contexts
+            .iter()
+            .position(|context| context.as_ref() == Some(egui_ctx))
+    }
+}
+
+impl Namespace {
+    /// Move a managed texture id into this namespace. User textures are
+    /// shared by all contexts and keep their ids.
+    pub fn apply(self, texture_id: TextureId) -> TextureId {
+        match texture_id {
+            TextureId::Managed(id) if self.0 != 0 => {
+                TextureId::Managed(id | self.0 << NAMESPACE_SHIFT)
+            },
+            texture_id => texture_id,
+        }
+    }
+
+    /// Move the textures of `delta` into this namespace.
+    pub fn apply_to_delta(self, delta: TexturesDelta) -> TexturesDelta {
+        TexturesDelta {
+            set: delta
+                .set
+                .into_iter()
+                .map(|(texture_id, image)| (self.apply(texture_id), image))
+                .collect(),
+            free: delta
+                .free
+                .into_iter()
+                .map(|texture_id| self.apply(texture_id))
+                .collect(),
+        }
+    }
+
+    /// Whether `texture_id` belongs to this namespace.
+    fn contains(self, texture_id: TextureId) -> bool {
+        matches!(texture_id,
+            TextureId::Managed(id) if id >> NAMESPACE_SHIFT == self.0)
+    }
+}
+
+/// The id `egui` knows a texture by, without the namespace of its
+/// context.
+pub(crate) fn strip(texture_id: TextureId) -> TextureId {
+    match texture_id {
+        TextureId::Managed(id) => {
+            TextureId::Managed(id & ((1 << NAMESPACE_SHIFT) - 1))
+        },
+        texture_id => texture_id,
+    }
+}
+
+/// Whether `texture_id` is the font atlas of a context, which is always its
+/// first managed texture.
+pub(crate) fn is_font_atlas(texture_id: TextureId) -> bool {
+    matches!(texture_id,
+        TextureId::Managed(id) if id & ((1 << NAMESPACE_SHIFT) - 1) == 0)
+}
+
+impl Renderer {
+    /// Apply the [`RendererOutput::textures_delta`](crate::RendererOutput::textures_delta)
+    /// of `egui_ctx` without drawing anything, like
+    /// [`Renderer::update_textures`], for renderers driven by several
+    /// contexts.
+    pub fn update_context_textures(
+        &mut self,
+        egui_ctx: &egui::Context,
+        textures_delta: TexturesDelta,
+    ) -> Result<()> {
+        let namespace = self.texture_pool.namespaces_mut().get(egui_ctx);
+        self.update_textures_in(namespace, textures_delta)
+    }
+
+    /// Release the textures `egui_ctx` left in this renderer, and let other
+    /// contexts reuse its texture namespace. Call this before dropping a
+    /// context that is not the only one drawn by the renderer, which keeps
+    /// a reference to every context it has drawn until then.
+    pub fn remove_context(&mut self, egui_ctx: &egui::Context) {
+        if let Some(namespace) =
+            self.texture_pool.namespaces_mut().remove(egui_ctx)
+        {
+            self.texture_pool
+                .textures_mut()
+                .free_managed(|texture_id| namespace.contains(texture_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contexts_get_their_own_namespaces() {
+        let mut namespaces = TextureNamespaces::default();
+        let [first, second] =
+            [egui::Context::default(), egui::Context::default()];
+        assert_eq!(namespaces.get(&first), Namespace(0));
+        assert_eq!(namespaces.get(&second), Namespace(1));
+        assert_eq!(namespaces.get(&first.clone()), Namespace(0));
+    }
+
+    #[test]
+    fn namespace_survives_memory_reset() {
+        let mut namespaces = TextureNamespaces::default();
+        let [first, second] =
+            [egui::Context::default(), egui::Context::default()];
+        namespaces.get(&first);
+        assert_eq!(namespaces.get(&second), Namespace(1));
+        second.memory_mut(|memory| *memory = Default::default());
+        assert_eq!(namespaces.get(&second), Namespace(1));
+    }
+
+    #[test]
+    fn removed_namespaces_are_reused() {
+        let mut namespaces = TextureNamespaces::default();
+        let contexts: [egui::Context; 3] = Default::default();
+        for context in &contexts {
+            namespaces.get(context);
+        }
+        assert_eq!(namespaces.remove(&contexts[1]), Some(Namespace(1)));
+        assert_eq!(namespaces.remove(&contexts[1]), None);
+        assert_eq!(namespaces.get(&egui::Context::default()), Namespace(1));
+    }
+
+    #[test]
+    fn apply_moves_managed_textures_only() {
+        let namespace = Namespace(2);
+        let texture_id = namespace.apply(TextureId::Managed(5));
+        assert_eq!(texture_id, TextureId::Managed(5 | 2 << NAMESPACE_SHIFT));
+        assert!(namespace.contains(texture_id));
+        assert!(!Namespace(1).contains(texture_id));
+        assert_eq!(namespace.apply(TextureId::User(5)), TextureId::User(5));
+        assert_eq!(
+            Namespace(0).apply(TextureId::Managed(5)),
+            TextureId::Managed(5)
+        );
+    }
+
+    #[test]
+    fn strip_reverts_apply() {
+        for texture_id in [TextureId::Managed(5), TextureId::User(5)] {
+            assert_eq!(strip(Namespace(3).apply(texture_id)), texture_id);
+            assert_eq!(strip(Namespace(0).apply(texture_id)), texture_id);
+        }
+    }
+
+    #[test]
+    fn font_atlas_is_found_in_every_namespace() {
+        assert!(is_font_atlas(Namespace(3).apply(TextureId::Managed(0))));
+        assert!(!is_font_atlas(Namespace(3).apply(TextureId::Managed(1))));
+        assert!(!is_font_atlas(TextureId::User(0)));
+    }
+}
@@ -0,0 +1,729 @@
+use std::fmt;
+
+use windows::Win32::Graphics::Direct3D10::ID3D10ShaderResourceView;
+
+use crate::FrameCallback;
+
+/// Construction-time options of a [`Renderer`](crate::Renderer).
+///
+/// Pass these to [`Renderer::with_options`](crate::Renderer::with_options).
+/// The [`Default`] value matches the behavior of
+/// [`Renderer::new`](crate::Renderer::new).
+#[doc(alias = "RendererConfig")]
+#[derive(Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RendererOptions {
+    /// Dither the output color to hide banding in the subtle gradients drawn
+    /// by `egui`, such as feathered edges and window shadows.
+    ///
+    /// The amplitude of the noise follows the bit depth of the render target,
+    /// so the same setting works for both 8-bit and 10-bit
+    /// (`DXGI_FORMAT_R10G10B10A2_UNORM`) targets, and it never changes flat
+    /// colors. Disabled by default; use `Some(Dithering::default())` to get
+    /// the same behavior as `egui-wgpu`.
+    pub dithering: Option<Dithering>,
+
+    /// Transform the final UI colors, for example to map the sRGB output of
+    /// `egui` to the color profile of the monitor in color-managed
+    /// applications. The transform is applied in the same pass, before
+    /// dithering. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_color_transform`](crate::Renderer::set_color_transform).
+    pub color_transform: Option<ColorTransform>,
+
+    /// Scale the brightness and adjust the gamma of the final UI colors, so
+    /// that overlays stay readable over very dark or very bright content
+    /// without restyling `egui`. Applied after
+    /// [`RendererOptions::color_transform`]. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_color_adjustment`](crate::Renderer::set_color_adjustment).
+    pub color_adjustment: Option<ColorAdjustment>,
+
+    /// Map each channel of the final UI colors through a 1D lookup table,
+    /// for custom display curves such as the gamma slider of a game. The
+    /// view must refer to a `Texture1D` with an RGBA format, whose red,
+    /// green and blue channels hold the curves of the respective channels,
+    /// indexed by the straight (non-premultiplied), gamma-encoded value. It
+    /// is sampled with linear filtering at texel centers and applied after
+    /// [`RendererOptions::color_adjustment`]. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_gamma_lut`](crate::Renderer::set_gamma_lut). As the
+    /// view is a GPU object, it is not serialized with the options.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub gamma_lut: Option<ID3D10ShaderResourceView>,
+
+    /// Raise the coverage sampled from the font atlas to this power, to match
+    /// the text weight of other `egui` backends or the preference of the
+    /// user. Below `1.0` makes text heavier and above `1.0` lighter. This is
+    /// applied on top of the
+    /// [`AlphaFromCoverage`](egui::epaint::AlphaFromCoverage) of the atlas,
+    /// and can be changed without rebuilding it. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_text_gamma`](crate::Renderer::set_text_gamma).
+    pub text_gamma: Option<f32>,
+
+    /// The color space the render target is encoded in. Colors from `egui`
+    /// and textures registered with
+    /// [`Renderer::register_user_texture_with_color_space`](crate::Renderer::register_user_texture_with_color_space)
+    /// are converted to it. Defaults to [`ColorSpace::Srgb`], which performs
+    /// no conversion.
+    pub target_color_space: ColorSpace,
+
+    /// When the render target view has an `_SRGB` format, draw the UI to an
+    /// intermediate texture in gamma space, as `egui` expects, and then
+    /// onto the render target, which converts it to linear for blending and
+    /// back to sRGB. This saves sRGB-aware swap chains the intermediate
+    /// target and blit otherwise needed, at the cost of one more pass. The
+    /// UI reaches the render target through the sRGB decoding of the
+    /// sampler, so a post process shader receives linear colors. Render
+    /// targets of other formats are drawn to directly. Disabled by default.
+    ///
+    /// Views with an 8-bit `_SRGB` format of a `_TYPELESS` or `_UNORM`
+    /// texture, such as the back buffers of sRGB-aware swap chains, are
+    /// drawn to through a `_UNORM` view of the same texture instead, which
+    /// needs neither.
+    pub srgb_intermediate: bool,
+
+    /// Blend the UI in linear space instead of in gamma space, as `egui`
+    /// backends drawing to sRGB-aware framebuffers do. Tessellation, text
+    /// and [`AlphaFromCoverage`](egui::epaint::AlphaFromCoverage) are tuned
+    /// for one of the two, so applications tuning their style for linear
+    /// blending get the same results here. Render target views with an
+    /// `_SRGB` format are drawn to directly, overriding
+    /// [`RendererOptions::target_color_space`] and
+    /// [`RendererOptions::srgb_intermediate`]. The UI is drawn to render
+    /// targets with an 8-bit `_UNORM` format through an intermediate texture
+    /// with an `_SRGB` view, and then composited onto the render target in
+    /// gamma space. Other formats are unaffected. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_linear_blending`](crate::Renderer::set_linear_blending).
+    pub linear_blending: bool,
+
+    /// Render at this fixed pixel density, ignoring
+    /// [`RendererOutput::pixels_per_point`](crate::RendererOutput::pixels_per_point),
+    /// for kiosk and capture scenarios where the UI must look the same
+    /// regardless of the DPI of the window. `None` by default.
+    ///
+    /// The screen rect passed to `egui` must be computed with the same value
+    /// for the UI to fill the render target. It can be changed later with
+    /// [`Renderer::set_pixels_per_point`](crate::Renderer::set_pixels_per_point).
+    pub pixels_per_point: Option<f32>,
+
+    /// Do not scale positions and clip rects by
+    /// [`egui::Context::zoom_factor`], for integrations that already fold the
+    /// zoom into the pixels per point of [`RendererOutput`](crate::RendererOutput),
+    /// such as `egui-winit`, where the zoom would otherwise be applied twice.
+    /// Disabled by default.
+    pub ignore_zoom_factor: bool,
+
+    /// Round vertex positions and clip rects to whole pixels after scaling
+    /// them to the render target, to avoid blurry lines and seams between
+    /// adjacent rects at fractional scale factors such as 125% and 150%.
+    /// This can make the anti-aliased edges of small shapes uneven. Disabled
+    /// by default.
+    pub snap_to_pixels: bool,
+
+    /// Compare the meshes of every frame with those of the previous frame
+    /// and compute the part of the render target that changed, see
+    /// [`Renderer::dirty_rect`](crate::Renderer::dirty_rect). This costs
+    /// hashing the vertices of every frame. Disabled by default.
+    pub track_dirty_rect: bool,
+
+    /// Keep the UI in an intermediate texture across frames, and redraw
+    /// only the part of it that changed before drawing it onto the render
+    /// target, like [`RendererOptions::track_dirty_rect`], which this
+    /// implies. This greatly reduces the GPU work for mostly static UIs,
+    /// such as dashboards that update a single widget every frame, at the
+    /// cost of drawing the whole texture every frame. Disabled by default.
+    pub damage_tracking: bool,
+
+    /// Draw the UI to an intermediate texture at a fraction of the resolution
+    /// of the render target, and scale it up onto the render target, to cut
+    /// the fill cost of large overlays on weak GPUs at the expense of
+    /// sharpness. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_render_scale`](crate::Renderer::set_render_scale).
+    pub render_scale: Option<RenderScale>,
+
+    /// Measure the GPU time of the UI with timestamp queries and lower or
+    /// raise its resolution to keep it within a budget, like
+    /// [`RendererOptions::render_scale`] does with a fixed scale. The filter
+    /// of [`RendererOptions::render_scale`] is used if it is set. Disabled
+    /// by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_dynamic_render_scale`](crate::Renderer::set_dynamic_render_scale),
+    /// and the current scale is returned by
+    /// [`Renderer::render_scale`](crate::Renderer::render_scale).
+    pub dynamic_render_scale: Option<DynamicRenderScale>,
+
+    /// Anti-alias text with a separate coverage for each color channel of
+    /// RGB-striped displays, using dual-source blending, for sharper small
+    /// text. Only applies to the font atlas, and only with
+    /// [`BlendMode::PremultipliedOver`], no
+    /// [`Renderer::set_blend_state`](crate::Renderer::set_blend_state)
+    /// override, no post process shader and no
+    /// [`RendererOptions::render_scale`] or
+    /// [`RendererOptions::dynamic_render_scale`]. Colored fringes appear on
+    /// displays with other subpixel layouts and when the output is scaled.
+    /// Disabled by default.
+    pub subpixel_text: bool,
+
+    /// How the alpha channel of the render target is written. Defaults to
+    /// [`AlphaOutput::Composite`].
+    pub alpha_output: AlphaOutput,
+
+    /// Fill the rendered region with this color before drawing the UI, for
+    /// tools like OBS or hardware mixers that key the UI out of a captured
+    /// frame by color. The color is written as is, so it is in
+    /// [`RendererOptions::target_color_space`], and its alpha is opaque;
+    /// use [`AlphaOutput::Coverage`] to also write the coverage of the UI.
+    /// Anti-aliased edges and translucent widgets are blended with the key,
+    /// so pick one far from the colors of the UI. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_chroma_key`](crate::Renderer::set_chroma_key).
+    pub chroma_key: Option<[f32; 3]>,
+
+    /// Unbind the geometry shader and the stream output targets while
+    /// rendering, and restore them afterwards. Enable this when rendering
+    /// from a hook into another application, which may leave these stages
+    /// active and corrupt the UI. Disabled by default.
+    pub disable_unused_stages: bool,
+
+    /// Capture the input assembler, vertex shader, rasterizer, pixel shader
+    /// and output merger state of the device before rendering, and restore
+    /// it afterwards, with a [`DeviceStateGuard`](crate::DeviceStateGuard),
+    /// so that the pipeline of the host application is untouched by the
+    /// `egui` pass. Enable this when rendering from a hook into another
+    /// application. This costs a state block capture and apply per frame.
+    /// Disabled by default.
+    pub preserve_device_state: bool,
+
+    /// Enable the thread safety layer of the device, and hold its
+    /// [`DeviceLock`](crate::DeviceLock) while rendering and uploading
+    /// textures, so that other threads can use the device at the same time,
+    /// for example to create the textures later registered with
+    /// [`Renderer::register_user_texture`](crate::Renderer::register_user_texture)
+    /// on a worker thread, without their calls interleaving with a render
+    /// pass. The [`Renderer`](crate::Renderer) itself must still be used
+    /// from one thread at a time; see
+    /// [`SyncRenderer`](crate::SyncRenderer) to share it between threads.
+    /// Disabled by default.
+    pub multithread_protected: bool,
+
+    /// In debug builds, check the state of the device before rendering and
+    /// log a warning if a geometry shader, stream output targets or a
+    /// predicate are active, which the renderer does not override and which
+    /// would silently corrupt or skip its output. Ignored in release builds.
+    /// Disabled by default.
+    pub validate_pipeline: bool,
+
+    /// Draw a quad to a 1x1 offscreen target while creating the renderer,
+    /// so that the driver compiles its shaders and states at startup rather
+    /// than with a visible hitch on the first frame of the UI. The state of
+    /// the device is restored afterwards. Disabled by default.
+    pub prewarm: bool,
+
+    /// How the UI is blended onto the render target. Defaults to
+    /// [`BlendMode::PremultipliedOver`].
+    ///
+    /// Blend states not covered by the presets can be set with
+    /// [`Renderer::set_blend_state`](crate::Renderer::set_blend_state).
+    pub blend_mode: BlendMode,
+
+    /// The depth range of the viewport the UI is drawn with, so that the
+    /// depth of the UI falls into the slice of the range reserved for it by
+    /// engines that partition depth between passes. The UI is drawn flat, at
+    /// the minimum depth. The renderer binds no depth-stencil view, so this
+    /// only matters to custom shaders reading the depth of the pixel, see
+    /// [`Renderer::set_vertex_shader`](crate::Renderer::set_vertex_shader).
+    /// Defaults to the whole range.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_depth_range`](crate::Renderer::set_depth_range).
+    pub depth_range: DepthRange,
+
+    /// The filter user textures are sampled with: bilinear filtering with
+    /// the border color outside of the texture by default, or the nearest
+    /// texel clamped to the edges, for pixel art and image viewers showing
+    /// individual pixels. Textures managed by `egui` are not affected.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_user_texture_filter`](crate::Renderer::set_user_texture_filter).
+    pub user_texture_filter: ScaleFilter,
+
+    /// Draw the `egui` cursor on top of the UI, using the images of the
+    /// system cursors, for captured, streamed or overlay windows where the
+    /// hardware cursor is hidden or not composited. The icon is set with
+    /// [`Renderer::set_cursor_icon`](crate::Renderer::set_cursor_icon).
+    /// Disabled by default.
+    #[cfg(feature = "software-cursor")]
+    pub software_cursor: bool,
+
+    /// Capture and blur the render target behind the UI every frame, and
+    /// expose the result as a texture for translucent "glass" panels, see
+    /// [`Renderer::backdrop_texture_id`](crate::Renderer::backdrop_texture_id).
+    /// Disabled by default.
+    pub backdrop_blur: Option<BackdropBlur>,
+
+    /// Log a warning with statistics about the frame, such as the number of
+    /// meshes and the size of texture uploads, whenever rendering it takes
+    /// longer than a threshold of CPU or GPU time, to catch pathological
+    /// frames in the field. The GPU time is measured with timestamp queries
+    /// and reported a few frames late. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_frame_watchdog`](crate::Renderer::set_frame_watchdog).
+    pub frame_watchdog: Option<FrameWatchdog>,
+
+    /// Truncate frames with more meshes or vertices than these limits, with
+    /// a warning, instead of uploading them. This is a safety valve for
+    /// overlays injected into other processes, where a misbehaving UI would
+    /// otherwise allocate unbounded GPU memory in the host. Meshes are kept
+    /// in paint order, so the UI drawn last is dropped first. Disabled by
+    /// default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_frame_limits`](crate::Renderer::set_frame_limits).
+    pub frame_limits: Option<FrameLimits>,
+
+    /// Split the draw calls of large frames into several GPU submissions,
+    /// so that the GPU starts drawing while the rest of the frame is still
+    /// being uploaded, instead of the whole frame stalling in one
+    /// multi-hundred-millisecond submission. This helps one-off frames with
+    /// huge draw lists, such as the preview of a table with tens of
+    /// thousands of rows, and costs some throughput otherwise. The frame is
+    /// still drawn completely by one call to
+    /// [`Renderer::render`](crate::Renderer::render), as `egui` redraws every
+    /// frame from scratch. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_time_slicing`](crate::Renderer::set_time_slicing).
+    pub time_slicing: Option<TimeSlicing>,
+
+    /// Called after every frame with its [`RenderStats`](crate::RenderStats),
+    /// such as the CPU and GPU time and the number of meshes and texture
+    /// uploads, to feed the health of the renderer into the metrics of the
+    /// application. The GPU time is measured with timestamp queries, like
+    /// [`RendererOptions::frame_watchdog`]. Disabled by default.
+    ///
+    /// It can be changed later with
+    /// [`Renderer::set_on_frame`](crate::Renderer::set_on_frame). The
+    /// callback is neither cloned nor serialized with the options: clones
+    /// have none.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub on_frame: Option<FrameCallback>,
+}
+
+impl Clone for RendererOptions {
+    fn clone(&self) -> Self {
+        Self {
+            dithering: self.dithering,
+            color_transform: self.color_transform.clone(),
+            color_adjustment: self.color_adjustment,
+            gamma_lut: self.gamma_lut.clone(),
+            text_gamma: self.text_gamma,
+            target_color_space: self.target_color_space,
+            srgb_intermediate: self.srgb_intermediate,
+            linear_blending: self.linear_blending,
+            pixels_per_point: self.pixels_per_point,
+            ignore_zoom_factor: self.ignore_zoom_factor,
+            snap_to_pixels: self.snap_to_pixels,
+            track_dirty_rect: self.track_dirty_rect,
+            damage_tracking: self.damage_tracking,
+            render_scale: self.render_scale,
+            dynamic_render_scale: self.dynamic_render_scale,
+            subpixel_text: self.subpixel_text,
+            alpha_output: self.alpha_output,
+            chroma_key: self.chroma_key,
+            disable_unused_stages: self.disable_unused_stages,
+            preserve_device_state: self.preserve_device_state,
+            multithread_protected: self.multithread_protected,
+            validate_pipeline: self.validate_pipeline,
+            prewarm: self.prewarm,
+            blend_mode: self.blend_mode,
+            depth_range: self.depth_range,
+            user_texture_filter: self.user_texture_filter,
+            #[cfg(feature = "software-cursor")]
+            software_cursor: self.software_cursor,
+            backdrop_blur: self.backdrop_blur,
+            frame_watchdog: self.frame_watchdog,
+            frame_limits: self.frame_limits,
+            time_slicing: self.time_slicing,
+            on_frame: None,
+        }
+    }
+}
+
+impl fmt::Debug for RendererOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("RendererOptions");
+        debug
+            .field("dithering", &self.dithering)
+            .field("color_transform", &self.color_transform)
+            .field("color_adjustment", &self.color_adjustment)
+            .field("gamma_lut", &self.gamma_lut)
+            .field("text_gamma", &self.text_gamma)
+            .field("target_color_space", &self.target_color_space)
+            .field("srgb_intermediate", &self.srgb_intermediate)
+            .field("linear_blending", &self.linear_blending)
+            .field("pixels_per_point", &self.pixels_per_point)
+            .field("ignore_zoom_factor", &self.ignore_zoom_factor)
+            .field("snap_to_pixels", &self.snap_to_pixels)
+            .field("track_dirty_rect", &self.track_dirty_rect)
+            .field("damage_tracking", &self.damage_tracking)
+            .field("render_scale", &self.render_scale)
+            .field("dynamic_render_scale", &self.dynamic_render_scale)
+            .field("subpixel_text", &self.subpixel_text)
+            .field("alpha_output", &self.alpha_output)
+            .field("chroma_key", &self.chroma_key)
+            .field("disable_unused_stages", &self.disable_unused_stages)
+            .field("preserve_device_state", &self.preserve_device_state)
+            .field("multithread_protected", &self.multithread_protected)
+            .field("validate_pipeline", &self.validate_pipeline)
+            .field("prewarm", &self.prewarm)
+            .field("blend_mode", &self.blend_mode)
+            .field("depth_range", &self.depth_range)
+            .field("user_texture_filter", &self.user_texture_filter);
+        #[cfg(feature = "software-cursor")]
+        debug.field("software_cursor", &self.software_cursor);
+        debug
+            .field("backdrop_blur", &self.backdrop_blur)
+            .field("frame_watchdog", &self.frame_watchdog)
+            .field("frame_limits", &self.frame_limits)
+            .field("time_slicing", &self.time_slicing)
+            .field("on_frame", &self.on_frame.is_some())
+            .finish()
+    }
+}
+
+/// The settings of [`RendererOptions::render_scale`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RenderScale {
+    /// The resolution of the UI relative to the render target, between `0.0`
+    /// and `1.0`. At `1.0` the UI is drawn directly to the render target.
+    /// Defaults to `0.5`.
+    pub scale: f32,
+    /// How the UI is sampled when it is scaled up. Defaults to
+    /// [`ScaleFilter::Linear`].
+    pub filter: ScaleFilter,
+}
+
+impl Default for RenderScale {
+    fn default() -> Self {
+        Self {
+            scale: 0.5,
+            filter: ScaleFilter::default(),
+        }
+    }
+}
+
+/// The settings of [`RendererOptions::dynamic_render_scale`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DynamicRenderScale {
+    /// The GPU time the UI may take per frame, in milliseconds. Defaults to
+    /// `1.0`.
+    pub budget_ms: f32,
+    /// The lowest scale the UI is drawn at. Defaults to `0.5`.
+    pub min_scale: f32,
+    /// The highest scale the UI is drawn at, and the scale of the first
+    /// frames. Defaults to `1.0`.
+    pub max_scale: f32,
+}
+
+impl Default for DynamicRenderScale {
+    fn default() -> Self {
+        Self {
+            budget_ms: 1.,
+            min_scale: 0.5,
+            max_scale: 1.,
+        }
+    }
+}
+
+/// The settings of [`RendererOptions::frame_watchdog`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FrameWatchdog {
+    /// The CPU time [`Renderer::render`](crate::Renderer::render) may take,
+    /// in milliseconds, including texture uploads. Use [`f32::INFINITY`] to
+    /// only watch the GPU time. Defaults to `8.0`.
+    pub cpu_ms: f32,
+    /// The GPU time the UI may take, in milliseconds. Use [`f32::INFINITY`]
+    /// to only watch the CPU time. Defaults to `8.0`.
+    pub gpu_ms: f32,
+}
+
+impl Default for FrameWatchdog {
+    fn default() -> Self {
+        Self {
+            cpu_ms: 8.,
+            gpu_ms: 8.,
+        }
+    }
+}
+
+/// The depth range of [`RendererOptions::depth_range`], between `0.0` and
+/// `1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DepthRange {
+    /// The `MinDepth` of the viewport. Defaults to `0.0`.
+    pub min_depth: f32,
+    /// The `MaxDepth` of the viewport. Defaults to `1.0`.
+    pub max_depth: f32,
+}
+
+impl Default for DepthRange {
+    fn default() -> Self {
+        Self {
+            min_depth: 0.,
+            max_depth: 1.,
+        }
+    }
+}
+
+/// The settings of [`RendererOptions::frame_limits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FrameLimits {
+    /// The number of meshes drawn per frame. Defaults to `65536`.
+    pub max_meshes: usize,
+    /// The total number of vertices of the meshes drawn per frame. Defaults
+    /// to `4194304`, 128 MiB of vertex data.
+    pub max_vertices: usize,
+}
+
+impl Default for FrameLimits {
+    fn default() -> Self {
+        Self {
+            max_meshes: 1 << 16,
+            max_vertices: 1 << 22,
+        }
+    }
+}
+
+/// The settings of [`RendererOptions::time_slicing`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TimeSlicing {
+    /// The number of vertices drawn before the commands issued so far are
+    /// submitted to the GPU with `ID3D10Device::Flush`. Defaults to
+    /// `262144`.
+    pub vertices_per_submission: usize,
+}
+
+impl Default for TimeSlicing {
+    fn default() -> Self {
+        Self {
+            vertices_per_submission: 1 << 18,
+        }
+    }
+}
+
+/// The filter of [`RenderScale::filter`] and
+/// [`RendererOptions::user_texture_filter`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScaleFilter {
+    /// Interpolate between pixels, which is smooth but blurry.
+    #[default]
+    Linear,
+    /// Repeat pixels, which keeps edges hard for pixel-art styles and
+    /// integer scales.
+    Point,
+}
+
+/// The settings of [`RendererOptions::backdrop_blur`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct BackdropBlur {
+    /// The factor the backdrop is downscaled by before blurring. Larger
+    /// factors are cheaper and blur more. Defaults to `4`.
+    pub downscale: u32,
+    /// The distance between the taps of the 9-tap Gaussian kernel, in texels
+    /// of the downscaled backdrop. Defaults to `1.5`.
+    pub spread: f32,
+}
+
+impl Default for BackdropBlur {
+    fn default() -> Self {
+        Self {
+            downscale: 4,
+            spread: 1.5,
+        }
+    }
+}
+
+/// The noise pattern used by [`RendererOptions::dithering`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dithering {
+    /// Interleaved gradient noise, the same pattern `egui-wgpu` uses when its
+    /// `dithering` option is enabled. Pick this to match the look of other
+    /// `egui` backends.
+    #[default]
+    InterleavedGradientNoise,
+    /// A 4x4 ordered (Bayer) pattern.
+    Ordered,
+}
+
+/// A transform applied to the final UI colors, see
+/// [`RendererOptions::color_transform`].
+///
+/// Both kinds of transforms operate on straight (non-premultiplied) colors.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorTransform {
+    /// A 3x3 matrix, in row-major order, applied to linear RGB values. The
+    /// output of `egui` is decoded from sRGB before and encoded back to sRGB
+    /// after the transform, so this is suitable for matrix-based display
+    /// profiles that share the sRGB transfer curve.
+    Matrix([[f32; 3]; 3]),
+    /// A 3D lookup table indexed by the gamma-encoded RGB values, as found in
+    /// `.cube` files. The view must refer to a `Texture3D` with an RGBA
+    /// format; it is sampled with trilinear filtering at texel centers.
+    ///
+    /// With the `serde` feature, options holding a lookup table cannot be
+    /// serialized, since the view is a GPU object.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Lut(ID3D10ShaderResourceView),
+}
+
+/// A brightness and gamma adjustment of the final UI colors, see
+/// [`RendererOptions::color_adjustment`].
+///
+/// Both operate on straight (non-premultiplied), gamma-encoded colors, so
+/// alpha and fully transparent areas are unaffected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ColorAdjustment {
+    /// The factor colors are multiplied by; below `1.0` dims the UI. Defaults
+    /// to `1.0`.
+    pub brightness: f32,
+    /// The gamma colors are raised to the reciprocal of; above `1.0`
+    /// brightens the midtones and below `1.0` darkens them. Defaults to
+    /// `1.0`.
+    pub gamma: f32,
+}
+
+impl Default for ColorAdjustment {
+    fn default() -> Self {
+        Self {
+            brightness: 1.,
+            gamma: 1.,
+        }
+    }
+}
+
+/// The encoding of a render target or texture, see
+/// [`RendererOptions::target_color_space`].
+///
+/// All color spaces share the same primaries and differ only in their
+/// transfer function, so conversion between them is exact up to
+/// quantization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorSpace {
+    /// The sRGB color space `egui` works in.
+    #[default]
+    Srgb = 0,
+    /// Rec.709 video, displayed with the BT.1886 transfer function (a pure
+    /// 2.4 power curve). Use this for video frames and for render targets
+    /// holding such frames.
+    Rec709 = 1,
+    /// Linear light, for engines whose whole pipeline is linear. As a
+    /// [`RendererOptions::target_color_space`], vertex colors and texels are
+    /// converted to linear before blending, which also makes `_SRGB` render
+    /// targets usable. This departs from the gamma-space blending `egui` is
+    /// designed for: anti-aliased edges and translucent fills look lighter
+    /// than with other backends.
+    Linear = 2,
+}
+
+/// Presets for the blending of the UI onto the render target, see
+/// [`RendererOptions::blend_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlendMode {
+    /// Composite the premultiplied output of `egui` over the render target,
+    /// writing alpha according to [`RendererOptions::alpha_output`].
+    #[default]
+    PremultipliedOver,
+    /// Add the premultiplied output of `egui` to the render target, for
+    /// glowing HUD elements.
+    Additive,
+    /// Overwrite the render target without blending, for opaque HUDs.
+    Opaque,
+    /// Composite like [`BlendMode::PremultipliedOver`] and leave the alpha
+    /// channel of the render target untouched, the same as
+    /// [`AlphaOutput::PreserveDestination`].
+    PreserveDstAlpha,
+}
+
+/// How the alpha channel of the render target is written, see
+/// [`RendererOptions::alpha_output`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlphaOutput {
+    /// Composite the alpha of `egui` over the destination alpha, like the
+    /// color channels. Starting from a target cleared to transparent black,
+    /// this produces correctly premultiplied alpha as expected by
+    /// `UpdateLayeredWindow` and by DWM/DirectComposition transparency.
+    #[default]
+    Composite,
+    /// Leave the alpha channel of the render target untouched, for example
+    /// when drawing over a frame whose alpha carries other information.
+    PreserveDestination,
+    /// Write the coverage of the UI alone: the alpha of the rendered region
+    /// is cleared before drawing and the UI is composited over it, whatever
+    /// the alpha of the frame below was. External compositors such as OBS
+    /// can then key the UI out of a captured frame by its alpha. Ignored
+    /// for render targets whose alpha channel is too narrow.
+    Coverage,
+}
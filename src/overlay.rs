@@ -0,0 +1,103 @@
+use windows::{
+    Win32::{
+        Foundation::HWND,
+        Graphics::{Direct3D10::*, Dxgi::IDXGISwapChain},
+    },
+    core::Result,
+};
+
+use crate::{DeviceStateGuard, Renderer, RendererOptions, RendererOutput};
+
+/// Renders `egui` on top of the frames of a swap chain owned by another
+/// application, from a hook of `IDXGISwapChain::Present`.
+///
+/// Create it lazily on the first hooked `Present` with [`Overlay::new`], which
+/// fetches the device from the swap chain. Then, on every `Present`, call
+/// [`Overlay::render`] before calling the original function. From a hook of
+/// `IDXGISwapChain::ResizeBuffers`, call [`Overlay::release_back_buffer`]
+/// before calling the original function; the render target view is
+/// recreated on the next call to [`Overlay::render`].
+///
+/// Unlike [`Renderer::render`], the whole pipeline state of the device is
+/// saved before rendering and restored afterwards with
+/// [`DeviceStateGuard::with_all_states`], so the host application is not
+/// affected.
+pub struct Overlay {
+    swap_chain: IDXGISwapChain,
+    device: ID3D10Device,
+    renderer: Renderer,
+}
+
+impl Overlay {
+    /// Create an overlay for `swap_chain`, with a [`Renderer`] on the device
+    /// the swap chain was created with.
+    pub fn new(swap_chain: &IDXGISwapChain) -> Result<Self> {
+        Self::with_options(swap_chain, RendererOptions::default())
+    }
+
+    /// Like [`Overlay::new`], creating the [`Renderer`] with
+    /// [`Renderer::with_options`].
+    pub fn with_options(
+        swap_chain: &IDXGISwapChain,
+        options: RendererOptions,
+    ) -> Result<Self> {
+        let device: ID3D10Device = unsafe { swap_chain.GetDevice() }?;
+        let renderer = Renderer::with_options(&device, options)?;
+        Ok(Self {
+            swap_chain: swap_chain.clone(),
+            device,
+            renderer,
+        })
+    }
+
+    /// The swap chain passed to [`Overlay::new`]. Hooks that may see several
+    /// swap chains can compare it with the one being presented.
+    pub fn swap_chain(&self) -> &IDXGISwapChain {
+        &self.swap_chain
+    }
+
+    /// The device of the swap chain.
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+
+    /// The [`Renderer`], for example to register user textures.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// The window the swap chain presents to, for example to subclass it and
+    /// receive input.
+    pub fn output_window(&self) -> Result<HWND> {
+        Ok(unsafe { self.swap_chain.GetDesc() }?.OutputWindow)
+    }
+
+    /// The size of the back buffer in pixels, to compute the screen rect of
+    /// [`egui::RawInput`].
+    pub fn back_buffer_size(&self) -> Result<(u32, u32)> {
+        let desc = unsafe { self.swap_chain.GetDesc() }?.BufferDesc;
+        Ok((desc.Width, desc.Height))
+    }
+
+    /// Release the render target view of the back buffer. Must be called
+    /// before the original `IDXGISwapChain::ResizeBuffers`, which fails while
+    /// references to the buffers are alive.
+    pub fn release_back_buffer(&mut self) {
+        self.renderer.release_back_buffer();
+    }
+
+    /// Render `egui_output` onto the current back buffer, saving and
+    /// restoring the state of the device around it.
+    pub fn render(
+        &mut self,
+        egui_ctx: &egui::Context,
+        egui_output: RendererOutput,
+    ) -> Result<()> {
+        let _guard = DeviceStateGuard::with_all_states(&self.device)?;
+        self.renderer.render_to_swapchain(
+            &self.swap_chain,
+            egui_ctx,
+            egui_output,
+        )
+    }
+}
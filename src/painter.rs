@@ -0,0 +1,226 @@
+use egui::{
+    ClippedPrimitive, TextureId,
+    epaint::{Mesh, Primitive},
+};
+
+use windows::{
+    Win32::{
+        Foundation::{E_INVALIDARG, RECT},
+        Graphics::Direct3D10::*,
+    },
+    core::{Error, Result},
+};
+
+use crate::{
+    Backend, DeviceLock, DrawRange, FrameInfo, Renderer, TextureConstants,
+    namespace::Namespace,
+};
+
+/// A mesh uploaded to the GPU with [`Painter::upload_mesh`].
+///
+/// Its vertices are already mapped to the render target of the painter that
+/// uploaded it, so it can be drawn as many times as needed in that frame, but
+/// must not be drawn by painters of other frames or render targets.
+pub struct GpuMesh {
+    vertex_buffer: ID3D10Buffer,
+    index_buffer: ID3D10Buffer,
+    index_count: u32,
+    texture_id: TextureId,
+}
+
+impl GpuMesh {
+    /// The texture the mesh was created with.
+    pub fn texture_id(&self) -> TextureId {
+        self.texture_id
+    }
+}
+
+/// A lower-level interface to the [`Renderer`] for custom render loops.
+///
+/// Where [`Renderer::render`] tessellates, uploads and draws a whole frame
+/// at once, a painter lets you take over these steps: tessellate the shapes
+/// with [`egui::Context::tessellate`], cull or reorder the meshes, upload
+/// them with [`Painter::upload_mesh`], and draw them with
+/// [`Painter::bind_texture`] and [`Painter::draw`], interleaved with your own
+/// passes.
+///
+/// Create one per frame and render target with [`Renderer::painter`]. The
+/// textures of the frame must have been updated with
+/// [`Renderer::update_textures`] beforehand.
+pub struct Painter<'a> {
+    renderer: &'a mut Renderer,
+    device_context: ID3D10Device,
+    render_target: ID3D10RenderTargetView,
+    frame: FrameInfo,
+    texture_constants: TextureConstants,
+    /// The texture namespace of the context of the frame.
+    namespace: Namespace,
+    /// Held for the lifetime of the painter with
+    /// [`RendererOptions::multithread_protected`](crate::RendererOptions::multithread_protected).
+    _device_lock: Option<DeviceLock>,
+}
+
+impl Renderer {
+    /// Create a [`Painter`] drawing into `render_target`, with shapes
+    /// tessellated at `pixels_per_point`, and set up the pipeline state for
+    /// it. The state is overridden as documented in [`Renderer::render`].
+    pub fn painter(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        egui_ctx: &egui::Context,
+        pixels_per_point: f32,
+    ) -> Result<Painter<'_>> {
+        self.check_device(device_context, render_target)?;
+        let frame = FrameInfo {
+            origin: (0, 0),
+            size: Self::get_render_target_size(render_target)?,
+            format: Self::get_render_target_format(render_target),
+            pixels_per_point,
+            zoom_factor: self.zoom_factor(egui_ctx),
+            snap_to_pixels: self.options.snap_to_pixels,
+        };
+        let namespace = self.texture_pool.namespaces_mut().get(egui_ctx);
+        let device_lock = self.lock_device()?;
+        let mut painter = Painter {
+            renderer: self,
+            device_context: device_context.clone(),
+            render_target: render_target.clone(),
+            frame,
+            texture_constants: TextureConstants::default(),
+            namespace,
+            _device_lock: device_lock,
+        };
+        painter.restore_state();
+        Ok(painter)
+    }
+}
+
+impl Painter<'_> {
+    /// Upload the vertices and indices of `mesh`, as tessellated by `egui`.
+    ///
+    /// Returns `E_INVALIDARG` if the mesh has no triangles or an incomplete
+    /// one.
+    pub fn upload_mesh(&self, mesh: &Mesh) -> Result<GpuMesh> {
+        if mesh.indices.is_empty() || !mesh.indices.len().is_multiple_of(3) {
+            log::error!(
+                "cannot upload a mesh with {} indices",
+                mesh.indices.len()
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        let vertices = mesh
+            .vertices
+            .iter()
+            .map(|vertex| self.frame.convert_vertex(*vertex))
+            .collect::<Vec<_>>();
+        let device = &self.renderer.device;
+        Ok(GpuMesh {
+            vertex_buffer: device.create_vertex_buffer(&vertices)?,
+            index_buffer: device.create_index_buffer(&mesh.indices)?,
+            index_count: mesh.indices.len() as _,
+            texture_id: mesh.texture_id,
+        })
+    }
+
+    /// Bind `texture_id` for the following draws. Returns `false`, leaving
+    /// the previous texture bound, if the texture does not exist.
+    pub fn bind_texture(&mut self, texture_id: TextureId) -> bool {
+        let texture_id = self.namespace.apply(texture_id);
+        let renderer = &mut *self.renderer;
+        let Some(srv) = renderer.texture_pool.textures().get_srv(texture_id)
+        else {
+            return false;
+        };
+        let texture_constants = renderer.texture_constants(texture_id);
+        if renderer.constant_buffer.is_some()
+            && texture_constants != self.texture_constants
+        {
+            self.texture_constants = texture_constants;
+            renderer.update_constants(
+                &self.device_context,
+                self.frame.format,
+                texture_constants,
+            );
+        }
+        unsafe {
+            self.device_context
+                .PSSetShaderResources(0, Some(&[Some(srv)]))
+        };
+        renderer
+            .user_constants
+            .bind_texture(&self.device_context, texture_id);
+        renderer.bind_pixel_shader(&self.device_context, texture_id);
+        renderer.bind_sampler(&self.device_context, texture_id);
+        true
+    }
+
+    /// Draw `mesh` with the texture bound last, clipped to `clip_rect` in
+    /// points.
+    pub fn draw(&mut self, mesh: &GpuMesh, clip_rect: egui::Rect) {
+        let clip_rect = self.frame.clip_rect_in_pixels(clip_rect);
+        self.device_context.draw_indexed(
+            &mesh.vertex_buffer,
+            &mesh.index_buffer,
+            DrawRange {
+                index_count: mesh.index_count,
+                start_index: 0,
+                base_vertex: 0,
+            },
+            RECT {
+                left: clip_rect.left() as _,
+                top: clip_rect.top() as _,
+                right: clip_rect.right() as _,
+                bottom: clip_rect.bottom() as _,
+            },
+            None,
+        );
+    }
+
+    /// Draw a single primitive of the output of [`egui::Context::tessellate`]
+    /// with `texture_id` bound, instead of the texture of its mesh, for
+    /// example to draw only some of the layers of a frame between two passes
+    /// of your own.
+    ///
+    /// Empty meshes are skipped and paint callbacks, which only
+    /// [`Renderer::render`] draws, are ignored with a warning. Returns
+    /// `E_INVALIDARG` if `texture_id` does not exist.
+    pub fn draw_primitive(
+        &mut self,
+        primitive: &ClippedPrimitive,
+        texture_id: TextureId,
+    ) -> Result<()> {
+        let mesh = match &primitive.primitive {
+            Primitive::Mesh(mesh) => mesh,
+            Primitive::Callback(..) => {
+                log::warn!(
+                    "paint callbacks are only drawn by Renderer::render."
+                );
+                return Ok(());
+            },
+        };
+        if mesh.indices.is_empty() {
+            return Ok(());
+        }
+        let mesh = self.upload_mesh(mesh)?;
+        if !self.bind_texture(texture_id) {
+            log::error!("cannot draw with missing texture {texture_id:?}");
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        self.draw(&mesh, primitive.clip_rect);
+        Ok(())
+    }
+
+    /// Set up the pipeline state for drawing again, after your own passes
+    /// changed it. The texture has to be bound again afterwards.
+    pub fn restore_state(&mut self) {
+        self.renderer.setup(
+            &self.device_context,
+            &self.render_target,
+            self.frame.origin,
+            self.frame.size,
+            self.frame.format,
+        );
+        self.texture_constants = TextureConstants::default();
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use windows::{
+    Win32::Graphics::Direct3D10::*,
+    core::{PCSTR, Result, s},
+};
+
+use crate::{ColorSpace, ColorTransform, Dithering, Renderer, RendererOptions};
+
+/// A variant of the `egui` pixel shader, as a bitmask of the preprocessor
+/// switches of `shaders/egui.hlsl` it is compiled with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct Permutation(u32);
+
+impl Permutation {
+    const DITHER_INTERLEAVED_GRADIENT_NOISE: Self = Self(1 << 0);
+    const DITHER_ORDERED: Self = Self(1 << 1);
+    const COLOR_MATRIX: Self = Self(1 << 2);
+    const COLOR_LUT: Self = Self(1 << 3);
+    const COLOR_ADJUSTMENT: Self = Self(1 << 4);
+    const TEXT_GAMMA: Self = Self(1 << 5);
+    const SUBPIXEL_TEXT: Self = Self(1 << 6);
+    const COLOR_SPACES: Self = Self(1 << 7);
+    const GAMMA_LUT: Self = Self(1 << 8);
+
+    /// The define of every bit, in bit order.
+    const DEFINES: [PCSTR; 9] = [
+        s!("DITHER_INTERLEAVED_GRADIENT_NOISE"),
+        s!("DITHER_ORDERED"),
+        s!("COLOR_MATRIX"),
+        s!("COLOR_LUT"),
+        s!("COLOR_ADJUSTMENT"),
+        s!("TEXT_GAMMA"),
+        s!("SUBPIXEL_TEXT"),
+        s!("COLOR_SPACES"),
+        s!("GAMMA_LUT"),
+    ];
+
+    /// The variant that implements `options`. `non_srgb_textures` tells
+    /// whether any registered texture is not in sRGB.
+    pub(crate) fn new(
+        options: &RendererOptions,
+        non_srgb_textures: bool,
+    ) -> Self {
+        let mut permutation = Self::default();
+        match options.dithering {
+            Some(Dithering::InterleavedGradientNoise) => {
+                permutation.insert(Self::DITHER_INTERLEAVED_GRADIENT_NOISE)
+            },
+            Some(Dithering::Ordered) => {
+                permutation.insert(Self::DITHER_ORDERED)
+            },
+            None => (),
+        }
+        match options.color_transform {
+            Some(ColorTransform::Matrix(_)) => {
+                permutation.insert(Self::COLOR_MATRIX)
+            },
+            Some(ColorTransform::Lut(_)) => permutation.insert(Self::COLOR_LUT),
+            None => (),
+        }
+        if options.color_adjustment.is_some() {
+            permutation.insert(Self::COLOR_ADJUSTMENT);
+        }
+        if options.gamma_lut.is_some() {
+            permutation.insert(Self::GAMMA_LUT);
+        }
+        if options.text_gamma.is_some() {
+            permutation.insert(Self::TEXT_GAMMA);
+        }
+        if options.subpixel_text {
+            permutation.insert(Self::SUBPIXEL_TEXT);
+        }
+        if options.target_color_space != ColorSpace::Srgb
+            || options.linear_blending
+            || non_srgb_textures
+        {
+            permutation.insert(Self::COLOR_SPACES);
+        }
+        permutation
+    }
+
+    fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Whether this is the precompiled variant, which draws textures
+    /// unchanged and reads no constants.
+    pub(crate) fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn defines(self) -> Vec<PCSTR> {
+        Self::DEFINES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, define)| *define)
+            .collect()
+    }
+}
+
+/// The variants of the `egui` pixel shader created on a device so far, so
+/// that toggling options back and forth, or creating more renderers with the
+/// same [`SharedResources`](crate::SharedResources), compiles every variant
+/// only once. Cloning is cheap; the clones share the cache.
+#[derive(Clone, Default)]
+pub(crate) struct PixelShaderCache(
+    Arc<Mutex<HashMap<Permutation, ID3D10PixelShader>>>,
+);
+
+impl PixelShaderCache {
+    /// The pixel shader of `permutation` on `device`, created if it is not
+    /// in the cache yet.
+    pub(crate) fn get_or_create(
+        &self,
+        device: &ID3D10Device,
+        permutation: Permutation,
+    ) -> Result<ID3D10PixelShader> {
+        let mut shaders = self.0.lock().unwrap();
+        if let Some(pixel_shader) = shaders.get(&permutation) {
+            return Ok(pixel_shader.clone());
+        }
+        let pixel_shader = if permutation.is_empty() {
+            let mut pixel_shader = None;
+            unsafe {
+                device.CreatePixelShader(
+                    Renderer::PS_BLOB,
+                    Some(&mut pixel_shader),
+                )
+            }?;
+            pixel_shader.unwrap()
+        } else {
+            crate::shader::create_pixel_shader(device, &permutation.defines())?
+        };
+        shaders.insert(permutation, pixel_shader.clone());
+        Ok(pixel_shader)
+    }
+}
@@ -0,0 +1,80 @@
+use windows::Win32::Graphics::Direct3D10::*;
+
+/// Warn about state of `ctx` that the renderer does not override and that
+/// would corrupt its output. See
+/// [`RendererOptions::validate_pipeline`](crate::RendererOptions::validate_pipeline).
+pub(crate) fn validate(ctx: &ID3D10Device) {
+    if unsafe { ctx.GSGetShader() }.is_ok() {
+        log::warn!(concat!(
+            "a geometry shader is bound while rendering egui. ",
+            "enable `RendererOptions::disable_unused_stages` to unbind it."
+        ));
+    }
+    let mut so_targets: [Option<ID3D10Buffer>;
+        D3D10_SO_BUFFER_SLOT_COUNT as usize] = Default::default();
+    unsafe {
+        ctx.SOGetTargets(
+            D3D10_SO_BUFFER_SLOT_COUNT,
+            Some(so_targets.as_mut_ptr()),
+            None,
+        )
+    };
+    if so_targets.iter().any(Option::is_some) {
+        log::warn!(concat!(
+            "stream output targets are bound while rendering egui. ",
+            "enable `RendererOptions::disable_unused_stages` to unbind them."
+        ));
+    }
+    let mut predicate = None;
+    unsafe { ctx.GetPredication(Some(&mut predicate), None) };
+    if predicate.is_some() {
+        log::warn!(concat!(
+            "a predicate is set while rendering egui. ",
+            "draw calls may be skipped."
+        ));
+    }
+}
+
+/// The geometry shader and stream output targets of a device, unbound for
+/// the lifetime of this guard and restored when it is dropped. See
+/// [`RendererOptions::disable_unused_stages`](crate::RendererOptions::disable_unused_stages).
+pub(crate) struct UnusedStages {
+    ctx: ID3D10Device,
+    geometry_shader: Option<ID3D10GeometryShader>,
+    so_targets: [Option<ID3D10Buffer>; D3D10_SO_BUFFER_SLOT_COUNT as usize],
+    so_offsets: [u32; D3D10_SO_BUFFER_SLOT_COUNT as usize],
+}
+
+impl UnusedStages {
+    pub fn disable(ctx: &ID3D10Device) -> Self {
+        let mut stages = Self {
+            ctx: ctx.clone(),
+            geometry_shader: unsafe { ctx.GSGetShader() }.ok(),
+            so_targets: Default::default(),
+            so_offsets: Default::default(),
+        };
+        unsafe {
+            ctx.SOGetTargets(
+                D3D10_SO_BUFFER_SLOT_COUNT,
+                Some(stages.so_targets.as_mut_ptr()),
+                Some(stages.so_offsets.as_mut_ptr()),
+            );
+            ctx.GSSetShader(None);
+            ctx.SOSetTargets(0, None, None);
+        }
+        stages
+    }
+}
+
+impl Drop for UnusedStages {
+    fn drop(&mut self) {
+        unsafe {
+            self.ctx.GSSetShader(self.geometry_shader.as_ref());
+            self.ctx.SOSetTargets(
+                D3D10_SO_BUFFER_SLOT_COUNT,
+                Some(self.so_targets.as_ptr()),
+                Some(self.so_offsets.as_ptr()),
+            );
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use windows::{
+    Win32::Graphics::{Direct3D::*, Direct3D10::*, Dxgi::Common::*},
+    core::Result,
+};
+
+use crate::{ColorSpace, RenderScale, Renderer, ScaleFilter};
+
+/// The state of the full-screen pass set with
+/// [`Renderer::set_post_process_shader`].
+pub(crate) struct PostProcess {
+    pixel_shader: ID3D10PixelShader,
+}
+
+/// A texture that is rendered to and then sampled, such as the intermediate
+/// target the UI is drawn to before a post process pass or scaling.
+pub(crate) struct RenderTexture {
+    pub size: (u32, u32),
+    pub kind: IntermediateKind,
+    pub render_target: ID3D10RenderTargetView,
+    pub srv: ID3D10ShaderResourceView,
+}
+
+/// How the intermediate texture of a frame is viewed, which depends on the
+/// format of the render target and the options of the renderer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum IntermediateKind {
+    /// A float texture, see [`RenderTexture::new`].
+    Float,
+    /// Drawn to in gamma space and sampled as sRGB, see
+    /// [`RenderTexture::srgb`].
+    SampledAsSrgb,
+    /// Drawn to as sRGB and sampled in gamma space, see
+    /// [`RenderTexture::linear_blending`].
+    DrawnAsSrgb,
+}
+
+impl RenderTexture {
+    pub fn new(device: &ID3D10Device, size: (u32, u32)) -> Result<Self> {
+        // A float format keeps the precision of 10-bit targets and the full
+        // alpha of targets with narrow alpha channels.
+        Self::with_formats(
+            device,
+            size,
+            DXGI_FORMAT_R16G16B16A16_FLOAT,
+            DXGI_FORMAT_R16G16B16A16_FLOAT,
+            DXGI_FORMAT_R16G16B16A16_FLOAT,
+        )
+    }
+
+    /// A texture the UI is drawn to in gamma space, and which is sampled as
+    /// sRGB, so that it is decoded to linear when drawn onto an sRGB-aware
+    /// render target, which encodes it again after blending.
+    pub fn srgb(device: &ID3D10Device, size: (u32, u32)) -> Result<Self> {
+        Ok(Self {
+            kind: IntermediateKind::SampledAsSrgb,
+            ..Self::with_formats(
+                device,
+                size,
+                DXGI_FORMAT_R8G8B8A8_TYPELESS,
+                DXGI_FORMAT_R8G8B8A8_UNORM,
+                DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
+            )?
+        })
+    }
+
+    /// A texture the UI is drawn to in linear space through an sRGB view, so
+    /// that it is blended in linear space, and which is sampled in gamma
+    /// space to be drawn onto a render target with an 8-bit `_UNORM` format.
+    pub fn linear_blending(
+        device: &ID3D10Device,
+        size: (u32, u32),
+    ) -> Result<Self> {
+        Ok(Self {
+            kind: IntermediateKind::DrawnAsSrgb,
+            ..Self::with_formats(
+                device,
+                size,
+                DXGI_FORMAT_R8G8B8A8_TYPELESS,
+                DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
+                DXGI_FORMAT_R8G8B8A8_UNORM,
+            )?
+        })
+    }
+
+    fn with_formats(
+        device: &ID3D10Device,
+        size: (u32, u32),
+        format: DXGI_FORMAT,
+        render_target_format: DXGI_FORMAT,
+        srv_format: DXGI_FORMAT,
+    ) -> Result<Self> {
+        let desc = D3D10_TEXTURE2D_DESC {
+            Width: size.0,
+            Height: size.1,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: format,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D10_USAGE_DEFAULT,
+            BindFlags: (D3D10_BIND_RENDER_TARGET.0
+                | D3D10_BIND_SHADER_RESOURCE.0) as _,
+            ..Default::default()
+        };
+        let mut render_target = None;
+        let mut srv = None;
+        unsafe {
+            let texture = device.CreateTexture2D(&desc, None)?;
+            device.CreateRenderTargetView(
+                &texture,
+                Some(&D3D10_RENDER_TARGET_VIEW_DESC {
+                    Format: render_target_format,
+                    ViewDimension: D3D10_RTV_DIMENSION_TEXTURE2D,
+                    ..Default::default()
+                }),
+                Some(&mut render_target),
+            )?;
+            device.CreateShaderResourceView(
+                &texture,
+                Some(&D3D10_SHADER_RESOURCE_VIEW_DESC {
+                    Format: srv_format,
+                    ViewDimension: D3D10_SRV_DIMENSION_TEXTURE2D,
+                    Anonymous: D3D10_SHADER_RESOURCE_VIEW_DESC_0 {
+                        Texture2D: D3D10_TEX2D_SRV {
+                            MostDetailedMip: 0,
+                            MipLevels: 1,
+                        },
+                    },
+                }),
+                Some(&mut srv),
+            )?;
+        }
+        Ok(Self {
+            size,
+            kind: IntermediateKind::Float,
+            render_target: render_target.unwrap(),
+            srv: srv.unwrap(),
+        })
+    }
+}
+
+impl Renderer {
+    /// Draw the UI to an intermediate texture, and then onto the render
+    /// target with a full-screen pass of `pixel_shader`, for effects on the
+    /// whole UI such as color grading, a vignette, or fading it in and out.
+    /// Pass `None` to draw directly to the render target again.
+    ///
+    /// The shader receives the outputs of the built-in vertex shader for a
+    /// quad covering the frame: `SV_POSITION`, `TEXCOORD` (a `float2` from
+    /// `(0, 0)` at the top left to `(1, 1)` at the bottom right) and `COLOR`
+    /// (opaque white). The UI is bound at `t0` with premultiplied alpha, and
+    /// the sampler at `s0`. The output is blended onto the render target as
+    /// premultiplied alpha. Parameters such as a fade factor can be passed
+    /// with [`Renderer::set_user_constants`].
+    pub fn set_post_process_shader(
+        &mut self,
+        pixel_shader: Option<ID3D10PixelShader>,
+    ) {
+        self.post_process =
+            pixel_shader.map(|pixel_shader| PostProcess { pixel_shader });
+    }
+
+    /// Replace the [`RendererOptions::render_scale`](crate::RendererOptions::render_scale)
+    /// of this [`Renderer`]. The intermediate texture is recreated on the
+    /// next frame if its size changes. The scale is ignored while
+    /// [`RendererOptions::dynamic_render_scale`](crate::RendererOptions::dynamic_render_scale)
+    /// is enabled, but the filter is not.
+    pub fn set_render_scale(&mut self, render_scale: Option<RenderScale>) {
+        self.options.render_scale = render_scale;
+    }
+
+    /// The size of the intermediate texture the UI is drawn to for a frame
+    /// of `frame_size` and `frame_format`, or `None` if it is drawn directly
+    /// to the render target.
+    pub(crate) fn intermediate_size(
+        &self,
+        frame_size: (u32, u32),
+        frame_format: DXGI_FORMAT,
+    ) -> Option<(u32, u32)> {
+        let scale = self.render_scale();
+        let scaled = |size: u32| ((size as f32 * scale).ceil() as u32).max(1);
+        let size = (scaled(frame_size.0), scaled(frame_size.1));
+        (self.post_process.is_some()
+            || self.options.damage_tracking
+            || self.intermediate_kind(frame_format) != IntermediateKind::Float
+            || size != frame_size)
+            .then_some(size)
+    }
+
+    /// How the intermediate texture is viewed for a frame of
+    /// `frame_format`, if one is used. The sRGB kinds are required by
+    /// [`RendererOptions::srgb_intermediate`](crate::RendererOptions::srgb_intermediate)
+    /// and [`RendererOptions::linear_blending`](crate::RendererOptions::linear_blending).
+    pub(crate) fn intermediate_kind(
+        &self,
+        frame_format: DXGI_FORMAT,
+    ) -> IntermediateKind {
+        if self.options.linear_blending {
+            if is_unorm_8bit(frame_format) {
+                return IntermediateKind::DrawnAsSrgb;
+            }
+        } else if self.options.srgb_intermediate && is_srgb_8bit(frame_format) {
+            return IntermediateKind::SampledAsSrgb;
+        }
+        IntermediateKind::Float
+    }
+
+    /// The color space the pixel shader outputs for a frame of
+    /// `frame_format`, which is linear when the UI is blended in linear
+    /// space, see
+    /// [`RendererOptions::linear_blending`](crate::RendererOptions::linear_blending).
+    pub(crate) fn target_color_space(
+        &self,
+        frame_format: DXGI_FORMAT,
+    ) -> ColorSpace {
+        if self.options.linear_blending
+            && (is_unorm_8bit(frame_format) || is_srgb_8bit(frame_format))
+        {
+            ColorSpace::Linear
+        } else {
+            self.options.target_color_space
+        }
+    }
+
+    /// The render target and view of the intermediate texture of `size` and
+    /// `kind`, recreated when either changes.
+    pub(crate) fn intermediate_target(
+        &mut self,
+        size: (u32, u32),
+        kind: IntermediateKind,
+    ) -> Result<(ID3D10RenderTargetView, ID3D10ShaderResourceView)> {
+        if self
+            .intermediate
+            .as_ref()
+            .is_none_or(|target| target.size != size || target.kind != kind)
+        {
+            self.intermediate = Some(match kind {
+                IntermediateKind::Float => {
+                    RenderTexture::new(&self.device, size)?
+                },
+                IntermediateKind::SampledAsSrgb => {
+                    RenderTexture::srgb(&self.device, size)?
+                },
+                IntermediateKind::DrawnAsSrgb => {
+                    RenderTexture::linear_blending(&self.device, size)?
+                },
+            });
+        }
+        let target = self.intermediate.as_ref().unwrap();
+        Ok((target.render_target.clone(), target.srv.clone()))
+    }
+
+    /// Draw `srv`, the intermediate texture of the frame, over the frame
+    /// with the post process shader, or scale it with the built-in one. The
+    /// pipeline must be set up for the render target.
+    pub(crate) fn draw_intermediate(
+        &self,
+        ctx: &ID3D10Device,
+        srv: &ID3D10ShaderResourceView,
+        frame_origin: (i32, i32),
+        frame_size: (u32, u32),
+    ) -> Result<()> {
+        let pixel_shader = match &self.post_process {
+            Some(post_process) => &post_process.pixel_shader,
+            None => &self.blit_pixel_shader,
+        };
+        let sampler_state = match self.options.render_scale {
+            Some(RenderScale {
+                filter: ScaleFilter::Point,
+                ..
+            }) => &self.sampler_state_point,
+            _ => &self.sampler_state_clamp,
+        };
+        unsafe {
+            ctx.PSSetShader(pixel_shader);
+            ctx.PSSetShaderResources(0, Some(&[Some(srv.clone())]));
+            ctx.PSSetSamplers(0, Some(&[Some(sampler_state.clone())]));
+        }
+        let result = self.draw_quad(ctx, frame_origin, frame_size, [1.; 4]);
+        // Unbind the texture, which is used as a render target next frame.
+        unsafe {
+            ctx.PSSetShaderResources(0, Some(&[None]));
+            ctx.PSSetShader(&self.pixel_shader);
+        }
+        result
+    }
+}
+
+fn is_unorm_8bit(format: DXGI_FORMAT) -> bool {
+    matches!(
+        format,
+        DXGI_FORMAT_R8G8B8A8_UNORM
+            | DXGI_FORMAT_B8G8R8A8_UNORM
+            | DXGI_FORMAT_B8G8R8X8_UNORM
+    )
+}
+
+fn is_srgb_8bit(format: DXGI_FORMAT) -> bool {
+    matches!(
+        format,
+        DXGI_FORMAT_R8G8B8A8_UNORM_SRGB
+            | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB
+            | DXGI_FORMAT_B8G8R8X8_UNORM_SRGB
+    )
+}
@@ -0,0 +1,53 @@
+use windows::{
+    Win32::Graphics::{Direct3D10::*, Dxgi::Common::*},
+    core::Result,
+};
+
+use crate::{DeviceStateGuard, Renderer};
+
+impl Renderer {
+    /// Draw a transparent quad with the pipeline of the renderer to a 1x1
+    /// texture, and submit it, for [`RendererOptions::prewarm`].
+    ///
+    /// [`RendererOptions::prewarm`]: crate::RendererOptions::prewarm
+    pub(crate) fn prewarm(&mut self) -> Result<()> {
+        const FORMAT: DXGI_FORMAT = DXGI_FORMAT_R8G8B8A8_UNORM;
+
+        let device = self.device.clone();
+        let _lock = self.lock_device()?;
+        let texture = unsafe {
+            device.CreateTexture2D(
+                &D3D10_TEXTURE2D_DESC {
+                    Width: 1,
+                    Height: 1,
+                    MipLevels: 1,
+                    ArraySize: 1,
+                    Format: FORMAT,
+                    SampleDesc: DXGI_SAMPLE_DESC {
+                        Count: 1,
+                        Quality: 0,
+                    },
+                    Usage: D3D10_USAGE_DEFAULT,
+                    BindFlags: D3D10_BIND_RENDER_TARGET.0 as _,
+                    ..Default::default()
+                },
+                None,
+            )
+        }?;
+        let mut render_target = None;
+        unsafe {
+            device.CreateRenderTargetView(
+                &texture,
+                None,
+                Some(&mut render_target),
+            )
+        }?;
+        let render_target = render_target.unwrap();
+
+        let _guard = DeviceStateGuard::new(&device)?;
+        self.setup(&device, &render_target, (0, 0), (1, 1), FORMAT);
+        self.draw_quad(&device, (0, 0), (1, 1), [0.; 4])?;
+        unsafe { device.Flush() };
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use std::slice;
+
+use windows::{
+    Win32::{
+        Foundation::E_INVALIDARG,
+        Graphics::{Direct3D10::*, Dxgi::Common::*},
+    },
+    core::{Error, Result},
+};
+
+use crate::compat;
+
+/// Copy `texture` to the CPU through a staging texture.
+///
+/// `texture` must be a single-sampled 2D texture with an 8-bit RGBA or BGRA
+/// format, whose colors are returned unchanged, so premultiplied colors stay
+/// premultiplied. Returns `E_INVALIDARG` for other formats.
+pub(crate) fn read_texture(
+    device: &ID3D10Device,
+    texture: &ID3D10Texture2D,
+) -> Result<egui::ColorImage> {
+    let mut desc = D3D10_TEXTURE2D_DESC::default();
+    unsafe { texture.GetDesc(&mut desc) };
+    let (swap_red_blue, opaque) = match desc.Format {
+        DXGI_FORMAT_R8G8B8A8_UNORM
+        | DXGI_FORMAT_R8G8B8A8_UNORM_SRGB
+        | DXGI_FORMAT_R8G8B8A8_TYPELESS => (false, false),
+        DXGI_FORMAT_B8G8R8A8_UNORM
+        | DXGI_FORMAT_B8G8R8A8_UNORM_SRGB
+        | DXGI_FORMAT_B8G8R8A8_TYPELESS => (true, false),
+        DXGI_FORMAT_B8G8R8X8_UNORM
+        | DXGI_FORMAT_B8G8R8X8_UNORM_SRGB
+        | DXGI_FORMAT_B8G8R8X8_TYPELESS => (true, true),
+        _ => {
+            log::error!(
+                "cannot read back textures of format {:?}.",
+                desc.Format
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        },
+    };
+
+    let staging_desc = D3D10_TEXTURE2D_DESC {
+        MipLevels: 1,
+        ArraySize: 1,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D10_USAGE_STAGING,
+        BindFlags: 0,
+        CPUAccessFlags: D3D10_CPU_ACCESS_READ.0 as _,
+        MiscFlags: 0,
+        ..desc
+    };
+    let staging = unsafe { device.CreateTexture2D(&staging_desc, None) }?;
+    let (width, height) = (desc.Width as usize, desc.Height as usize);
+    let mut pixels = Vec::with_capacity(width * height);
+    unsafe {
+        device.CopySubresourceRegion(&staging, 0, 0, 0, 0, texture, 0, None);
+        let mapped = staging.Map(0, D3D10_MAP_READ, 0)?;
+        for y in 0..height {
+            let row = slice::from_raw_parts(
+                (mapped.pData as *const u8).add(y * mapped.RowPitch as usize),
+                width * 4,
+            );
+            pixels.extend(row.chunks_exact(4).map(|pixel| {
+                let (r, b) = match swap_red_blue {
+                    true => (pixel[2], pixel[0]),
+                    false => (pixel[0], pixel[2]),
+                };
+                let a = if opaque { u8::MAX } else { pixel[3] };
+                egui::Color32::from_rgba_premultiplied(r, pixel[1], b, a)
+            }));
+        }
+        staging.Unmap(0);
+    }
+    Ok(compat::color_image([width, height], pixels))
+}
@@ -0,0 +1,118 @@
+use windows::{
+    Win32::{
+        Foundation::E_INVALIDARG,
+        Graphics::{Direct3D::*, Direct3D10::*, Dxgi::Common::*},
+    },
+    core::{Error, Result},
+};
+
+use crate::Renderer;
+
+/// Check that the inputs of the vertex shader in `blob` are provided by the
+/// input layout of the [`Renderer`], with matching component types and
+/// enough components, before drawing with it through
+/// [`Renderer::set_vertex_shader`].
+///
+/// Every mismatch is logged. Returns `E_INVALIDARG` if there is any, which
+/// `CreateInputLayout` would report for some of them without saying which
+/// input is wrong, and not at all for others.
+pub fn validate_vertex_shader(blob: &[u8]) -> Result<()> {
+    validate_input_layout(blob, &Renderer::INPUT_ELEMENTS_DESC)
+}
+
+/// Check the input signature of the vertex shader in `blob` against
+/// `elements`, see [`validate_vertex_shader`].
+pub(crate) fn validate_input_layout(
+    blob: &[u8],
+    elements: &[D3D10_INPUT_ELEMENT_DESC],
+) -> Result<()> {
+    let reflection =
+        unsafe { D3D10ReflectShader(blob.as_ptr() as _, blob.len()) }?;
+    let mut desc = D3D10_SHADER_DESC::default();
+    unsafe { reflection.GetDesc(&mut desc) }?;
+    let mut valid = true;
+    for index in 0..desc.InputParameters {
+        let mut parameter = D3D10_SIGNATURE_PARAMETER_DESC::default();
+        unsafe { reflection.GetInputParameterDesc(index, &mut parameter) }?;
+        // System values such as `SV_VertexID` are generated by the input
+        // assembler rather than read from the vertex buffer.
+        if parameter.SystemValueType != D3D_NAME_UNDEFINED {
+            continue;
+        }
+        let name = semantic_name(parameter.SemanticName);
+        let semantic = format!("{name}{}", parameter.SemanticIndex);
+        let Some(element) = elements.iter().find(|element| {
+            element.SemanticIndex == parameter.SemanticIndex
+                && semantic_name(element.SemanticName)
+                    .eq_ignore_ascii_case(&name)
+        }) else {
+            log::error!(
+                "the vertex shader reads {semantic}, which is not in the \
+                 input layout"
+            );
+            valid = false;
+            continue;
+        };
+        let Some((component_type, count)) = format_components(element.Format)
+        else {
+            continue;
+        };
+        if component_type != parameter.ComponentType {
+            log::error!(
+                "the vertex shader reads {semantic} as {}, but the input \
+                 layout provides {}",
+                component_type_name(parameter.ComponentType),
+                component_type_name(component_type),
+            );
+            valid = false;
+        }
+        let read = u8::BITS - parameter.Mask.leading_zeros();
+        if count < read {
+            log::error!(
+                "the vertex shader reads {read} components of {semantic}, \
+                 but the input layout only provides {count}"
+            );
+            valid = false;
+        }
+    }
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::from_hresult(E_INVALIDARG))
+    }
+}
+
+fn semantic_name(name: windows::core::PCSTR) -> String {
+    unsafe { name.to_string() }.unwrap_or_default()
+}
+
+/// The component type and count a vertex shader reads from an element of
+/// `format`, for the formats used by vertex layouts.
+fn format_components(
+    format: DXGI_FORMAT,
+) -> Option<(D3D_REGISTER_COMPONENT_TYPE, u32)> {
+    Some(match format {
+        DXGI_FORMAT_R32_FLOAT => (D3D_REGISTER_COMPONENT_FLOAT32, 1),
+        DXGI_FORMAT_R32G32_FLOAT => (D3D_REGISTER_COMPONENT_FLOAT32, 2),
+        DXGI_FORMAT_R32G32B32_FLOAT => (D3D_REGISTER_COMPONENT_FLOAT32, 3),
+        DXGI_FORMAT_R32G32B32A32_FLOAT
+        | DXGI_FORMAT_R8G8B8A8_UNORM
+        | DXGI_FORMAT_B8G8R8A8_UNORM
+        | DXGI_FORMAT_R16G16B16A16_FLOAT => (D3D_REGISTER_COMPONENT_FLOAT32, 4),
+        DXGI_FORMAT_R32_UINT => (D3D_REGISTER_COMPONENT_UINT32, 1),
+        DXGI_FORMAT_R32G32_UINT => (D3D_REGISTER_COMPONENT_UINT32, 2),
+        DXGI_FORMAT_R32G32B32A32_UINT => (D3D_REGISTER_COMPONENT_UINT32, 4),
+        _ => return None,
+    })
+}
+
+fn component_type_name(
+    component_type: D3D_REGISTER_COMPONENT_TYPE,
+) -> &'static str {
+    match component_type {
+        D3D_REGISTER_COMPONENT_FLOAT32 => "float",
+        D3D_REGISTER_COMPONENT_UINT32 => "uint",
+        D3D_REGISTER_COMPONENT_SINT32 => "int",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,103 @@
+use egui::epaint::{ClippedShape, textures::TexturesDelta};
+
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+use crate::{Renderer, RendererOutput};
+
+/// The operations shared by the Direct3D renderers for `egui`.
+///
+/// Applications supporting several Direct3D versions can be generic over
+/// this trait instead of [`Renderer`]. It only uses types from `egui`, so a
+/// renderer for another Direct3D version can implement it with its own
+/// device and render target types. Renderers that take textures owned by
+/// the application also implement [`EguiDxUserTextures`].
+pub trait EguiDxRenderer {
+    /// The object draw calls are issued on: the device for Direct3D10, the
+    /// device context for later versions.
+    type DeviceContext;
+    /// The view of the render target to draw into.
+    type RenderTargetView;
+
+    /// Apply the texture changes of a frame, such as updates of the font
+    /// atlas, without drawing anything.
+    fn update_textures(
+        &mut self,
+        device_context: &Self::DeviceContext,
+        textures_delta: TexturesDelta,
+    ) -> Result<()>;
+
+    /// Draw `shapes`, tessellated at `pixels_per_point`, into
+    /// `render_target`. Texture changes must have been applied with
+    /// [`EguiDxRenderer::update_textures`] first.
+    fn render(
+        &mut self,
+        device_context: &Self::DeviceContext,
+        render_target: &Self::RenderTargetView,
+        egui_ctx: &egui::Context,
+        shapes: Vec<ClippedShape>,
+        pixels_per_point: f32,
+    ) -> Result<()>;
+}
+
+/// The user texture operations of an [`EguiDxRenderer`].
+///
+/// They are kept apart so that the methods of [`EguiDxRenderer`] do not
+/// depend on the `user-textures` feature, which only adds this trait.
+#[cfg(feature = "user-textures")]
+pub trait EguiDxUserTextures: EguiDxRenderer {
+    /// The view of a texture sampled by `egui`.
+    type ShaderResourceView;
+
+    /// Register a texture owned by the application and get the id to use
+    /// for it in `egui`.
+    fn register_user_texture(
+        &mut self,
+        srv: Self::ShaderResourceView,
+    ) -> egui::TextureId;
+}
+
+impl EguiDxRenderer for Renderer {
+    type DeviceContext = ID3D10Device;
+    type RenderTargetView = ID3D10RenderTargetView;
+
+    fn update_textures(
+        &mut self,
+        device_context: &ID3D10Device,
+        textures_delta: TexturesDelta,
+    ) -> Result<()> {
+        Renderer::update_textures(self, device_context, textures_delta)
+    }
+
+    fn render(
+        &mut self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        egui_ctx: &egui::Context,
+        shapes: Vec<ClippedShape>,
+        pixels_per_point: f32,
+    ) -> Result<()> {
+        Renderer::render(
+            self,
+            device_context,
+            render_target,
+            egui_ctx,
+            RendererOutput {
+                textures_delta: TexturesDelta::default(),
+                shapes,
+                pixels_per_point,
+            },
+        )
+    }
+}
+
+#[cfg(feature = "user-textures")]
+impl EguiDxUserTextures for Renderer {
+    type ShaderResourceView = ID3D10ShaderResourceView;
+
+    fn register_user_texture(
+        &mut self,
+        srv: ID3D10ShaderResourceView,
+    ) -> egui::TextureId {
+        Renderer::register_user_texture(self, srv)
+    }
+}
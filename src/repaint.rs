@@ -0,0 +1,217 @@
+use std::time::{Duration, Instant};
+
+use crate::ViewportOutputs;
+
+/// Decides when to run the UI again, from the repaint delays `egui` asks for
+/// with [`egui::Context::request_repaint_after`] and from input, for hosts
+/// that drive their own loop and only want to render when needed.
+///
+/// ```ignore
+/// let mut scheduler = RepaintScheduler::new();
+/// scheduler.run(
+///     |timeout| wait_for_input(timeout),
+///     |scheduler| {
+///         let full_output = egui_ctx.run(take_input(), ui);
+///         let (output, _, viewport_output) = split_output(full_output);
+///         scheduler.on_frame(&viewport_output);
+///         renderer.render(&device, &render_target, &egui_ctx, output).is_ok()
+///     },
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RepaintScheduler {
+    /// When the UI should run next, or `None` to wait for input.
+    next_repaint: Option<Instant>,
+    /// The refresh interval of the display, if known.
+    frame_interval: Option<Duration>,
+}
+
+impl Default for RepaintScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepaintScheduler {
+    /// Create a scheduler with the first frame due right away.
+    pub fn new() -> Self {
+        Self {
+            next_repaint: Some(Instant::now()),
+            frame_interval: None,
+        }
+    }
+
+    /// Set the refresh rate of the display in hertz, such as the one of
+    /// `State::refresh_rate` in the `winit` and `win32` integrations, or
+    /// `None` if it is unknown. Repaint delays shorter than one refresh,
+    /// such as those of running animations, are then stretched to one
+    /// refresh, so animations update once per displayed frame. Without it,
+    /// they run again right away.
+    pub fn set_refresh_rate(&mut self, refresh_rate: Option<f32>) {
+        self.frame_interval = refresh_rate
+            .filter(|refresh_rate| *refresh_rate > 0.)
+            .map(|refresh_rate| Duration::from_secs_f32(1. / refresh_rate));
+    }
+
+    /// Schedule the next frame according to the repaint delay of the root
+    /// viewport in the output of the frame that just ran, but no sooner than
+    /// one refresh of the display if its refresh rate is set. Without a
+    /// delay, the next frame waits for input.
+    pub fn on_frame(&mut self, viewport_output: &ViewportOutputs) {
+        let delay = viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map_or(Duration::MAX, |viewport| viewport.repaint_delay)
+            .max(self.frame_interval.unwrap_or_default());
+        self.next_repaint = Instant::now().checked_add(delay);
+    }
+
+    /// Run the UI as soon as possible, for example because input arrived.
+    pub fn request_repaint(&mut self) {
+        self.request_repaint_after(Duration::ZERO);
+    }
+
+    /// Run the UI after `delay` at the latest, for example when another
+    /// thread called [`egui::Context::request_repaint_after`].
+    pub fn request_repaint_after(&mut self, delay: Duration) {
+        let Some(deadline) = Instant::now().checked_add(delay) else {
+            return;
+        };
+        self.next_repaint = Some(
+            self.next_repaint
+                .map_or(deadline, |next_repaint| next_repaint.min(deadline)),
+        );
+    }
+
+    /// Whether the UI is due to run.
+    pub fn should_repaint(&self) -> bool {
+        self.next_repaint
+            .is_some_and(|next_repaint| next_repaint <= Instant::now())
+    }
+
+    /// How long to wait for input before the UI is due to run.
+    /// [`Duration::ZERO`] means it is due now, and [`Duration::MAX`] means
+    /// only on the next input.
+    pub fn timeout(&self) -> Duration {
+        self.next_repaint.map_or(Duration::MAX, |next_repaint| {
+            next_repaint.saturating_duration_since(Instant::now())
+        })
+    }
+
+    /// Run the UI on input or when it is due, until `frame` returns `false`.
+    ///
+    /// `wait` blocks for at most the given timeout and returns whether input
+    /// arrived. `frame` runs the UI and renders it, and should pass the
+    /// viewport output to [`RepaintScheduler::on_frame`]; otherwise, the
+    /// next frame only runs on input.
+    pub fn run(
+        &mut self,
+        mut wait: impl FnMut(Duration) -> bool,
+        mut frame: impl FnMut(&mut Self) -> bool,
+    ) {
+        loop {
+            if !self.should_repaint() {
+                if wait(self.timeout()) {
+                    self.request_repaint();
+                }
+                continue;
+            }
+            self.next_repaint = None;
+            if !frame(self) {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LONG: Duration = Duration::from_secs(1000);
+
+    /// The viewport output of a frame that asked to run again after `delay`.
+    fn output(delay: Duration) -> ViewportOutputs {
+        let ctx = egui::Context::default();
+        let mut output = ctx.run(Default::default(), |_| {}).viewport_output;
+        output
+            .get_mut(&egui::ViewportId::ROOT)
+            .unwrap()
+            .repaint_delay = delay;
+        output
+    }
+
+    #[test]
+    fn first_frame_is_due() {
+        let scheduler = RepaintScheduler::new();
+        assert!(scheduler.should_repaint());
+        assert_eq!(scheduler.timeout(), Duration::ZERO);
+    }
+
+    #[test]
+    fn frames_wait_for_their_repaint_delay() {
+        let mut scheduler = RepaintScheduler::new();
+        scheduler.on_frame(&output(LONG));
+        assert!(!scheduler.should_repaint());
+        assert!(scheduler.timeout() > LONG / 2);
+        assert!(scheduler.timeout() <= LONG);
+
+        scheduler.on_frame(&output(Duration::ZERO));
+        assert!(scheduler.should_repaint());
+    }
+
+    #[test]
+    fn frames_without_delay_wait_for_input() {
+        let mut scheduler = RepaintScheduler::new();
+        scheduler.on_frame(&output(Duration::MAX));
+        assert!(!scheduler.should_repaint());
+        assert_eq!(scheduler.timeout(), Duration::MAX);
+
+        scheduler.request_repaint();
+        assert!(scheduler.should_repaint());
+    }
+
+    #[test]
+    fn short_delays_are_stretched_to_one_refresh() {
+        let mut scheduler = RepaintScheduler::new();
+        scheduler.set_refresh_rate(Some(1. / LONG.as_secs_f32()));
+        scheduler.on_frame(&output(Duration::ZERO));
+        assert!(!scheduler.should_repaint());
+        assert!(scheduler.timeout() > LONG / 2);
+
+        scheduler.set_refresh_rate(Some(0.));
+        scheduler.on_frame(&output(Duration::ZERO));
+        assert!(scheduler.should_repaint());
+    }
+
+    #[test]
+    fn earliest_requested_repaint_wins() {
+        let mut scheduler = RepaintScheduler::new();
+        scheduler.on_frame(&output(Duration::MAX));
+        scheduler.request_repaint_after(LONG);
+        scheduler.request_repaint_after(LONG * 2);
+        scheduler.request_repaint_after(Duration::MAX);
+        assert!(scheduler.timeout() <= LONG);
+        assert!(!scheduler.should_repaint());
+    }
+
+    #[test]
+    fn run_waits_for_input_between_frames() {
+        let mut scheduler = RepaintScheduler::new();
+        let mut timeouts = Vec::new();
+        let mut frames = 0;
+        scheduler.run(
+            |timeout| {
+                timeouts.push(timeout);
+                // Input arrives on every other wait.
+                timeouts.len() % 2 == 0
+            },
+            |scheduler| {
+                frames += 1;
+                scheduler.on_frame(&output(Duration::MAX));
+                frames < 3
+            },
+        );
+        assert_eq!(frames, 3);
+        assert_eq!(timeouts, [Duration::MAX; 4]);
+    }
+}
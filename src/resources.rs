@@ -0,0 +1,117 @@
+use windows::{
+    Win32::Graphics::{Direct3D10::*, Dxgi::Common::DXGI_FORMAT},
+    core::Result,
+};
+
+use crate::{Renderer, TextureInfo};
+
+/// Access to the device objects of a [`Renderer`], to sample the textures of
+/// `egui` or to reuse its states in custom passes drawn next to the UI. The
+/// renderer keeps using these objects, so they must not be modified, which
+/// Direct3D10 state objects cannot be anyway.
+impl Renderer {
+    /// The device the renderer was created on.
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+
+    /// The input layout of the vertices of `egui` meshes, matching the
+    /// built-in vertex shader.
+    pub fn input_layout(&self) -> &ID3D10InputLayout {
+        &self.input_layout
+    }
+
+    /// The built-in vertex shader, which ignores
+    /// [`Renderer::set_vertex_shader`].
+    pub fn vertex_shader(&self) -> &ID3D10VertexShader {
+        &self.vertex_shader
+    }
+
+    /// The rasterizer state the UI is drawn with: no culling, with scissor
+    /// rects.
+    pub fn rasterizer_state(&self) -> &ID3D10RasterizerState {
+        &self.rasterizer_state
+    }
+
+    /// The bilinear sampler user textures are drawn with by default, which
+    /// reads the border color outside of them. Textures managed by `egui` are
+    /// drawn with the sampler matching their [`egui::TextureWrapMode`].
+    pub fn sampler_state(&self) -> &ID3D10SamplerState {
+        &self.sampler_state
+    }
+
+    /// A bilinear sampler clamping to the edges of textures, as used to
+    /// scale intermediate textures.
+    pub fn sampler_state_clamp(&self) -> &ID3D10SamplerState {
+        &self.sampler_state_clamp
+    }
+
+    /// A nearest-neighbor sampler clamping to the edges of textures.
+    pub fn sampler_state_point(&self) -> &ID3D10SamplerState {
+        &self.sampler_state_point
+    }
+
+    /// A bilinear sampler repeating textures, for textures managed by `egui`
+    /// with [`egui::TextureWrapMode::Repeat`].
+    pub fn sampler_state_repeat(&self) -> &ID3D10SamplerState {
+        &self.sampler_state_repeat
+    }
+
+    /// A bilinear sampler mirroring textures, for textures managed by `egui`
+    /// with [`egui::TextureWrapMode::MirroredRepeat`].
+    pub fn sampler_state_mirror(&self) -> &ID3D10SamplerState {
+        &self.sampler_state_mirror
+    }
+
+    /// The blend state the UI would be drawn with onto a render target of
+    /// `format`, which depends on the [`BlendMode`](crate::BlendMode), the
+    /// [`AlphaOutput`](crate::AlphaOutput) and
+    /// [`Renderer::set_blend_state`].
+    pub fn blend_state(&self, format: DXGI_FORMAT) -> &ID3D10BlendState {
+        self.blend_state_for(format)
+    }
+
+    /// The view of a texture, either managed by the first `egui` context
+    /// drawn by the renderer or registered with
+    /// [`Renderer::register_user_texture`]. `None` if it does not exist, or
+    /// if it was evicted by [`Renderer::trim`] and not drawn since.
+    pub fn get_srv(
+        &self,
+        texture_id: egui::TextureId,
+    ) -> Option<ID3D10ShaderResourceView> {
+        self.texture_pool.textures().get_srv(texture_id)
+    }
+
+    /// The size and format of a texture managed by the first `egui` context
+    /// drawn by the renderer or registered with
+    /// [`Renderer::register_user_texture`], for example to size an
+    /// [`egui::Image`] showing a user texture. `None` if it does not exist.
+    pub fn texture_info(
+        &self,
+        texture_id: egui::TextureId,
+    ) -> Option<TextureInfo> {
+        self.texture_pool.textures().texture_info(texture_id)
+    }
+
+    /// Copy the pixels of a texture, like [`Renderer::get_srv`], back to the
+    /// CPU, for example to dump the font atlas while debugging or to compare
+    /// atlas updates with golden images in tests. Colors are premultiplied.
+    ///
+    /// This stalls until the GPU has drawn everything before, so it is not
+    /// meant to be called every frame. User textures must be 2D textures
+    /// with an 8-bit RGBA or BGRA format. Returns `E_INVALIDARG` if the
+    /// texture does not exist or cannot be read.
+    pub fn read_texture(
+        &self,
+        texture_id: egui::TextureId,
+    ) -> Result<egui::ColorImage> {
+        let _device_lock = self.lock_device()?;
+        self.texture_pool.textures().read_texture(texture_id)
+    }
+
+    /// The view of the font atlas of the first `egui` context drawn by the
+    /// renderer, like [`Renderer::get_srv`].
+    pub fn font_atlas_srv(&self) -> Option<ID3D10ShaderResourceView> {
+        self.get_srv(egui::TextureId::default())
+    }
+}
@@ -0,0 +1,190 @@
+//! A minimal application runner on top of [`winit::State`](crate::winit::State),
+//! for quick tools that do not need control over the window or device.
+
+use std::time::Instant;
+
+use ::winit::{
+    application::ApplicationHandler,
+    dpi::LogicalSize,
+    event::{StartCause, WindowEvent},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    window::{Window, WindowAttributes, WindowId},
+};
+
+use windows::{
+    Win32::Foundation::E_FAIL,
+    core::{Error, Result},
+};
+
+use crate::{DeviceOptions, RendererOptions, create_device, winit::State};
+
+/// Options for [`run_simple`].
+#[derive(Clone, Debug)]
+pub struct SimpleOptions {
+    /// The initial size of the client area of the window, in points. Uses
+    /// the default of `winit` if `None`.
+    pub inner_size: Option<egui::Vec2>,
+    /// Options of the device, see [`create_device`].
+    pub device: DeviceOptions,
+    /// Options of the [`Renderer`](crate::Renderer).
+    pub renderer: RendererOptions,
+    /// The color the window is cleared to before drawing the UI.
+    pub clear_color: [f32; 4],
+}
+
+impl Default for SimpleOptions {
+    fn default() -> Self {
+        Self {
+            inner_size: None,
+            device: DeviceOptions::default(),
+            renderer: RendererOptions::default(),
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+/// Open a window titled `title` and show the UI built by `ui` in it until the
+/// window is closed.
+///
+/// This creates the window, a Direct3D10 device with [`create_device`], the
+/// swap chain and the event loop, and only redraws when `egui` asks for it.
+/// It must be called on the main thread, and can only be called once per
+/// process, as `winit` does not support recreating the event loop.
+///
+/// ```ignore
+/// egui_directx10::run_simple("Hello", Default::default(), |ctx| {
+///     egui::CentralPanel::default().show(ctx, |ui| ui.label("Hello world!"));
+/// })?;
+/// ```
+pub fn run_simple(
+    title: &str,
+    options: SimpleOptions,
+    ui: impl FnMut(&egui::Context),
+) -> Result<()> {
+    let mut window_attributes = WindowAttributes::default().with_title(title);
+    if let Some(size) = options.inner_size {
+        window_attributes =
+            window_attributes.with_inner_size(LogicalSize::new(size.x, size.y));
+    }
+    let mut runner = Runner {
+        window_attributes,
+        options,
+        ui,
+        window: None,
+        error: None,
+    };
+    run_event_loop(&mut runner)?;
+    runner.error.map_or(Ok(()), Err)
+}
+
+pub(crate) fn run_event_loop(app: &mut impl ApplicationHandler) -> Result<()> {
+    EventLoop::new()
+        .and_then(|event_loop| event_loop.run_app(app))
+        .map_err(|err| {
+            log::error!("failed to run the event loop: {err}");
+            Error::from_hresult(E_FAIL)
+        })
+}
+
+/// Schedule the next redraw of `window` according to the repaint delay
+/// requested by `egui`.
+pub(crate) fn schedule_repaint(
+    event_loop: &ActiveEventLoop,
+    window: &Window,
+    state: &State,
+) {
+    let delay = state.repaint_delay();
+    if delay.is_zero() {
+        window.request_redraw();
+    } else if let Some(deadline) = Instant::now().checked_add(delay) {
+        event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+    } else {
+        event_loop.set_control_flow(ControlFlow::Wait);
+    }
+}
+
+struct Runner<F> {
+    window_attributes: WindowAttributes,
+    options: SimpleOptions,
+    ui: F,
+    window: Option<(Window, State)>,
+    error: Option<Error>,
+}
+
+impl<F> Runner<F> {
+    fn create_window(
+        &self,
+        event_loop: &ActiveEventLoop,
+    ) -> Result<(Window, State)> {
+        let window = event_loop.create_window(self.window_attributes.clone());
+        let window = window.map_err(|err| {
+            log::error!("failed to create the window: {err}");
+            Error::from_hresult(E_FAIL)
+        })?;
+        let (device, _) = create_device(&self.options.device)?;
+        let mut state = State::with_options(
+            &window,
+            &device,
+            self.options.renderer.clone(),
+        )?;
+        state.set_clear_color(self.options.clear_color);
+        Ok((window, state))
+    }
+
+    fn fail(&mut self, event_loop: &ActiveEventLoop, error: Error) {
+        self.error = Some(error);
+        event_loop.exit();
+    }
+}
+
+impl<F: FnMut(&egui::Context)> ApplicationHandler for Runner<F> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        match self.create_window(event_loop) {
+            Ok(window) => self.window = Some(window),
+            Err(err) => self.fail(event_loop, err),
+        }
+    }
+
+    fn new_events(&mut self, _: &ActiveEventLoop, cause: StartCause) {
+        if let StartCause::ResumeTimeReached { .. } = cause
+            && let Some((window, _)) = &self.window
+        {
+            window.request_redraw();
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some((window, state)) = &mut self.window else {
+            return;
+        };
+        let result = (|| {
+            if state.on_window_event(window, &event)?.repaint {
+                window.request_redraw();
+            }
+            match event {
+                WindowEvent::CloseRequested => event_loop.exit(),
+                WindowEvent::RedrawRequested => {
+                    state.run_ui(window, &mut self.ui)?;
+                    if state.close_requested() {
+                        event_loop.exit();
+                    } else {
+                        schedule_repaint(event_loop, window, state);
+                    }
+                },
+                _ => {},
+            }
+            Ok(())
+        })();
+        if let Err(err) = result {
+            self.fail(event_loop, err);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Runtime compilation of shader variants.
+//!
+//! The default pipeline uses the precompiled blobs in `shaders/`. Optional
+//! features that need a different pixel shader are implemented as
+//! preprocessor switches in `shaders/egui.hlsl`, and the matching variant is
+//! compiled from the embedded source when the [`Renderer`] is created.
+//!
+//! [`Renderer`]: crate::Renderer
+
+use std::slice;
+
+use windows::{
+    Win32::Graphics::{Direct3D::*, Direct3D10::*},
+    core::{PCSTR, Result, s},
+};
+
+const SOURCE: &str = include_str!("../shaders/egui.hlsl");
+
+/// Compile the entry point `entry` of `shaders/egui.hlsl` against `profile`
+/// with every macro in `defines` defined as `1`.
+///
+/// Compiler diagnostics are forwarded to the `log` crate.
+pub fn compile(
+    entry: PCSTR,
+    profile: PCSTR,
+    defines: &[PCSTR],
+) -> Result<ID3DBlob> {
+    let macros = defines
+        .iter()
+        .map(|&name| D3D_SHADER_MACRO {
+            Name: name,
+            Definition: s!("1"),
+        })
+        .chain([D3D_SHADER_MACRO::default()])
+        .collect::<Vec<_>>();
+
+    let mut blob = None;
+    let mut errors = None;
+    let result = unsafe {
+        D3D10CompileShader(
+            SOURCE.as_bytes(),
+            s!("egui.hlsl"),
+            Some(macros.as_ptr()),
+            None,
+            entry,
+            profile,
+            D3D10_SHADER_OPTIMIZATION_LEVEL3 | D3D10_SHADER_ENABLE_STRICTNESS,
+            &mut blob,
+            Some(&mut errors),
+        )
+    };
+    if let Some(errors) = errors {
+        let message = String::from_utf8_lossy(blob_bytes(&errors));
+        if result.is_err() {
+            log::error!("failed to compile shader variant: {message}");
+        } else {
+            log::warn!("shader variant compiled with warnings: {message}");
+        }
+    }
+    result?;
+    Ok(blob.unwrap())
+}
+
+/// Compile a variant of the `egui` pixel shader.
+pub fn create_pixel_shader(
+    device: &ID3D10Device,
+    defines: &[PCSTR],
+) -> Result<ID3D10PixelShader> {
+    create_pixel_shader_from(device, s!("ps_egui"), defines)
+}
+
+/// Compile the separable blur pixel shader of the backdrop.
+pub fn create_blur_pixel_shader(
+    device: &ID3D10Device,
+) -> Result<ID3D10PixelShader> {
+    create_pixel_shader_from(device, s!("ps_blur"), &[])
+}
+
+fn create_pixel_shader_from(
+    device: &ID3D10Device,
+    entry: PCSTR,
+    defines: &[PCSTR],
+) -> Result<ID3D10PixelShader> {
+    let blob = compile(entry, s!("ps_4_0"), defines)?;
+    let mut pixel_shader = None;
+    unsafe {
+        device.CreatePixelShader(blob_bytes(&blob), Some(&mut pixel_shader))
+    }?;
+    Ok(pixel_shader.unwrap())
+}
+
+/// View the contents of a blob as a byte slice.
+pub fn blob_bytes(blob: &ID3DBlob) -> &[u8] {
+    unsafe {
+        let ptr = blob.GetBufferPointer() as *const u8;
+        if ptr.is_null() {
+            return &[];
+        }
+        slice::from_raw_parts(ptr, blob.GetBufferSize())
+    }
+}
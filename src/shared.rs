@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+use crate::{
+    DefaultTextureAllocator, Renderer, RendererOptions, TextureAllocator,
+    TexturePool, namespace::TextureNamespaces, permutation::PixelShaderCache,
+};
+
+/// The shaders, input layout and state objects of a [`Renderer`] that only
+/// depend on the device, and the variants of its pixel shader compiled so far.
+///
+/// Applications with several renderers on the same device, such as one per
+/// window or per `egui` context, can create these once and pass them to
+/// [`Renderer::with_shared_resources`] instead of creating identical copies
+/// for every renderer. Cloning is cheap; the clones refer to the same
+/// objects.
+#[derive(Clone)]
+pub struct SharedResources {
+    pub(crate) device: ID3D10Device,
+    pub(crate) input_layout: ID3D10InputLayout,
+    pub(crate) vertex_shader: ID3D10VertexShader,
+    pub(crate) blit_pixel_shader: ID3D10PixelShader,
+    pub(crate) rasterizer_state: ID3D10RasterizerState,
+    pub(crate) sampler_state: ID3D10SamplerState,
+    pub(crate) sampler_state_clamp: ID3D10SamplerState,
+    pub(crate) sampler_state_point: ID3D10SamplerState,
+    pub(crate) sampler_state_repeat: ID3D10SamplerState,
+    pub(crate) sampler_state_mirror: ID3D10SamplerState,
+    pub(crate) blend_state: ID3D10BlendState,
+    pub(crate) blend_state_no_alpha: ID3D10BlendState,
+    pub(crate) blend_state_opaque: ID3D10BlendState,
+    pub(crate) blend_state_additive: ID3D10BlendState,
+    pub(crate) pixel_shaders: PixelShaderCache,
+}
+
+impl SharedResources {
+    /// Create the resources on `device`. Errors are handled as in
+    /// [`Renderer::new`].
+    pub fn new(device: &ID3D10Device) -> Result<Self> {
+        let mut input_layout = None;
+        let mut vertex_shader = None;
+        let mut blit_pixel_shader = None;
+        let mut rasterizer_state = None;
+        let mut sampler_state = None;
+        let mut sampler_state_clamp = None;
+        let mut sampler_state_point = None;
+        let mut sampler_state_repeat = None;
+        let mut sampler_state_mirror = None;
+        let mut blend_state = None;
+        let mut blend_state_no_alpha = None;
+        let mut blend_state_opaque = None;
+        let mut blend_state_additive = None;
+        #[cfg(feature = "shader-validation")]
+        crate::reflection::validate_input_layout(
+            Renderer::VS_BLOB,
+            &Renderer::INPUT_ELEMENTS_DESC,
+        )?;
+        unsafe {
+            device.CreateInputLayout(
+                &Renderer::INPUT_ELEMENTS_DESC,
+                Renderer::VS_BLOB,
+                Some(&mut input_layout),
+            )?;
+            device.CreateVertexShader(
+                Renderer::VS_BLOB,
+                Some(&mut vertex_shader),
+            )?;
+            // The precompiled pixel shader draws a texture unchanged.
+            device.CreatePixelShader(
+                Renderer::PS_BLOB,
+                Some(&mut blit_pixel_shader),
+            )?;
+            device.CreateRasterizerState(
+                &Renderer::RASTERIZER_DESC,
+                Some(&mut rasterizer_state),
+            )?;
+            device.CreateSamplerState(
+                &Renderer::SAMPLER_DESC,
+                Some(&mut sampler_state),
+            )?;
+            device.CreateSamplerState(
+                &Renderer::SAMPLER_DESC_CLAMP,
+                Some(&mut sampler_state_clamp),
+            )?;
+            device.CreateSamplerState(
+                &Renderer::SAMPLER_DESC_POINT,
+                Some(&mut sampler_state_point),
+            )?;
+            device.CreateSamplerState(
+                &Renderer::SAMPLER_DESC_REPEAT,
+                Some(&mut sampler_state_repeat),
+            )?;
+            device.CreateSamplerState(
+                &Renderer::SAMPLER_DESC_MIRROR,
+                Some(&mut sampler_state_mirror),
+            )?;
+            device.CreateBlendState(
+                &Renderer::BLEND_DESC,
+                Some(&mut blend_state),
+            )?;
+            device.CreateBlendState(
+                &Renderer::BLEND_DESC_NO_ALPHA,
+                Some(&mut blend_state_no_alpha),
+            )?;
+            device.CreateBlendState(
+                &Renderer::BLEND_DESC_OPAQUE,
+                Some(&mut blend_state_opaque),
+            )?;
+            device.CreateBlendState(
+                &Renderer::BLEND_DESC_ADDITIVE,
+                Some(&mut blend_state_additive),
+            )?;
+        }
+        Ok(Self {
+            device: device.clone(),
+            input_layout: input_layout.unwrap(),
+            vertex_shader: vertex_shader.unwrap(),
+            blit_pixel_shader: blit_pixel_shader.unwrap(),
+            rasterizer_state: rasterizer_state.unwrap(),
+            sampler_state: sampler_state.unwrap(),
+            sampler_state_clamp: sampler_state_clamp.unwrap(),
+            sampler_state_point: sampler_state_point.unwrap(),
+            sampler_state_repeat: sampler_state_repeat.unwrap(),
+            sampler_state_mirror: sampler_state_mirror.unwrap(),
+            blend_state: blend_state.unwrap(),
+            blend_state_no_alpha: blend_state_no_alpha.unwrap(),
+            blend_state_opaque: blend_state_opaque.unwrap(),
+            blend_state_additive: blend_state_additive.unwrap(),
+            pixel_shaders: PixelShaderCache::default(),
+        })
+    }
+
+    /// The device the resources were created on.
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+}
+
+impl Renderer {
+    /// Create a [`Renderer`] like [`Renderer::with_options`], on the device
+    /// of `shared` and with its resources instead of new ones.
+    pub fn with_shared_resources(
+        shared: &SharedResources,
+        options: RendererOptions,
+    ) -> Result<Self> {
+        Self::from_shared_resources(
+            shared,
+            options,
+            SharedTexturePool::new(&shared.device),
+        )
+    }
+
+    /// The shaders, input layout and state objects of this [`Renderer`], to
+    /// create more renderers on the same device with
+    /// [`Renderer::with_shared_resources`].
+    pub fn shared_resources(&self) -> SharedResources {
+        SharedResources {
+            device: self.device.clone(),
+            input_layout: self.input_layout.clone(),
+            vertex_shader: self.vertex_shader.clone(),
+            blit_pixel_shader: self.blit_pixel_shader.clone(),
+            rasterizer_state: self.rasterizer_state.clone(),
+            sampler_state: self.sampler_state.clone(),
+            sampler_state_clamp: self.sampler_state_clamp.clone(),
+            sampler_state_point: self.sampler_state_point.clone(),
+            sampler_state_repeat: self.sampler_state_repeat.clone(),
+            sampler_state_mirror: self.sampler_state_mirror.clone(),
+            blend_state: self.blend_state.clone(),
+            blend_state_no_alpha: self.blend_state_no_alpha.clone(),
+            blend_state_opaque: self.blend_state_opaque.clone(),
+            blend_state_additive: self.blend_state_additive.clone(),
+            pixel_shaders: self.pixel_shaders.clone(),
+        }
+    }
+
+    /// The textures of this [`Renderer`], to create more renderers drawing
+    /// them with [`RendererBuilder::texture_pool`](crate::RendererBuilder::texture_pool).
+    pub fn texture_pool(&self) -> SharedTexturePool {
+        self.texture_pool.clone()
+    }
+}
+
+/// The textures of one or more [`Renderer`]s on the same device: the
+/// textures managed by the `egui` contexts they draw, and the user textures
+/// registered with any of them.
+///
+/// Every renderer owns a pool of its own unless it is created with the pool
+/// of another one, see [`Renderer::texture_pool`]. Renderers sharing a pool,
+/// such as one per window, can draw a user texture registered once with
+/// any of them, and keep a single copy of the textures of an `egui` context
+/// they all draw, whichever of them applies its textures delta. The pixel
+/// shaders and constants set per texture stay with the renderer they were
+/// set on. Cloning is cheap; the clones refer to the same pool.
+///
+/// The pool is reference counted atomically and locked while it is used,
+/// so that a [`SyncRenderer`](crate::SyncRenderer) can use it from any
+/// thread.
+#[derive(Clone)]
+pub struct SharedTexturePool(Arc<SharedTexturePoolInner>);
+
+struct SharedTexturePoolInner {
+    device: ID3D10Device,
+    textures: Mutex<TexturePool>,
+    namespaces: Mutex<TextureNamespaces>,
+}
+
+impl SharedTexturePool {
+    /// Create an empty pool on `device`.
+    pub fn new(device: &ID3D10Device) -> Self {
+        Self::with_texture_allocator(device, Box::new(DefaultTextureAllocator))
+    }
+
+    /// Create an empty pool on `device`, creating and destroying the
+    /// textures managed by `egui` through `texture_allocator`.
+    pub fn with_texture_allocator(
+        device: &ID3D10Device,
+        texture_allocator: Box<dyn TextureAllocator>,
+    ) -> Self {
+        // The pool is only sent to other threads inside a `SyncRenderer`,
+        // which relies on the atomic reference count.
+        #[allow(clippy::arc_with_non_send_sync)]
+        Self(Arc::new(SharedTexturePoolInner {
+            device: device.clone(),
+            textures: Mutex::new(TexturePool::new(
+                device.clone(),
+                texture_allocator,
+            )),
+            namespaces: Mutex::default(),
+        }))
+    }
+
+    /// The device the textures are created on.
+    pub fn device(&self) -> &ID3D10Device {
+        &self.0.device
+    }
+
+    /// Lock the textures. A pool whose lock was poisoned by a panic is
+    /// still used, as its textures stay valid.
+    pub(crate) fn textures(&self) -> MutexGuard<'_, TexturePool> {
+        self.0
+            .textures
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn textures_mut(&self) -> MutexGuard<'_, TexturePool> {
+        self.textures()
+    }
+
+    /// The texture namespaces of the `egui` contexts drawn by all renderers
+    /// sharing the pool, so that their textures are kept apart.
+    pub(crate) fn namespaces_mut(&self) -> MutexGuard<'_, TextureNamespaces> {
+        self.0
+            .namespaces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
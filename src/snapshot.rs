@@ -0,0 +1,159 @@
+use egui::TextureId;
+
+use windows::{
+    Win32::Graphics::{Direct3D::*, Direct3D10::*, Dxgi::Common::*},
+    core::{Interface, Result},
+};
+
+use crate::{ColorSpace, Renderer};
+
+/// A copy of a render target that can be sampled.
+pub(crate) struct Snapshot {
+    /// The size and format of the render target, and the format of its view.
+    key: (u32, u32, DXGI_FORMAT, DXGI_FORMAT),
+    texture: ID3D10Texture2D,
+    pub srv: ID3D10ShaderResourceView,
+}
+
+impl Snapshot {
+    /// Copy the mip level and array slice of the texture `render_target`
+    /// views into `snapshot`, resolving it if it is multisampled. The copy is
+    /// recreated when the size or format of the render target changes.
+    pub fn take<'a>(
+        snapshot: &'a mut Option<Self>,
+        device: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+    ) -> Result<&'a Self> {
+        let source = unsafe { render_target.GetResource() }?
+            .cast::<ID3D10Texture2D>()?;
+        let mut desc = D3D10_TEXTURE2D_DESC::default();
+        unsafe { source.GetDesc(&mut desc) };
+        let view_format = Renderer::get_render_target_format(render_target);
+        let (width, height) = Renderer::get_render_target_size(render_target)?;
+        let (mip_slice, array_slice) =
+            Renderer::get_render_target_slice(render_target);
+        let subresource = mip_slice + array_slice * desc.MipLevels;
+        let key = (width, height, desc.Format, view_format);
+        if snapshot.as_ref().is_none_or(|s| s.key != key) {
+            *snapshot = Some(Self::new(device, key)?);
+        }
+        let snapshot = snapshot.as_ref().unwrap();
+        unsafe {
+            if desc.SampleDesc.Count > 1 {
+                device.ResolveSubresource(
+                    &snapshot.texture,
+                    0,
+                    &source,
+                    subresource,
+                    view_format,
+                );
+            } else {
+                device.CopySubresourceRegion(
+                    &snapshot.texture,
+                    0,
+                    0,
+                    0,
+                    0,
+                    &source,
+                    subresource,
+                    None,
+                );
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// The size of the copy, which is the size of the whole mip level of the
+    /// render target.
+    pub fn size(&self) -> (u32, u32) {
+        (self.key.0, self.key.1)
+    }
+
+    fn new(
+        device: &ID3D10Device,
+        key: (u32, u32, DXGI_FORMAT, DXGI_FORMAT),
+    ) -> Result<Self> {
+        let (width, height, format, view_format) = key;
+        let desc = D3D10_TEXTURE2D_DESC {
+            Width: width,
+            Height: height,
+            MipLevels: 1,
+            ArraySize: 1,
+            Format: format,
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            Usage: D3D10_USAGE_DEFAULT,
+            BindFlags: D3D10_BIND_SHADER_RESOURCE.0 as _,
+            ..Default::default()
+        };
+        let texture = unsafe { device.CreateTexture2D(&desc, None) }?;
+        // Typeless render targets need an explicitly typed view.
+        let view_desc = D3D10_SHADER_RESOURCE_VIEW_DESC {
+            Format: view_format,
+            ViewDimension: D3D10_SRV_DIMENSION_TEXTURE2D,
+            Anonymous: D3D10_SHADER_RESOURCE_VIEW_DESC_0 {
+                Texture2D: D3D10_TEX2D_SRV {
+                    MostDetailedMip: 0,
+                    MipLevels: 1,
+                },
+            },
+        };
+        let mut srv = None;
+        unsafe {
+            device.CreateShaderResourceView(
+                &texture,
+                Some(&view_desc),
+                Some(&mut srv),
+            )
+        }?;
+        Ok(Self {
+            key,
+            texture,
+            srv: srv.unwrap(),
+        })
+    }
+}
+
+impl Renderer {
+    /// Copy the current contents of `render_target` into a texture and get
+    /// a [`egui::TextureId`] for it, for magnifiers, color pickers or
+    /// reflections of the scene behind the UI.
+    ///
+    /// Call this every frame after the scene is drawn and before
+    /// [`Renderer::render`]. The same id is returned every time, and the
+    /// texture always covers the whole render target, so a widget at `rect`
+    /// samples the part of it behind itself with UVs of `rect` divided by
+    /// the size of [`egui::Context::screen_rect`]. Unlike
+    /// [`Renderer::backdrop_texture_id`], the copy is not blurred.
+    pub fn capture_background(
+        &mut self,
+        render_target: &ID3D10RenderTargetView,
+    ) -> Result<TextureId> {
+        let snapshot = Snapshot::take(
+            &mut self.background_snapshot,
+            &self.device,
+            render_target,
+        )?;
+        let srv = snapshot.srv.clone();
+        match self.background_texture_id {
+            Some(tid)
+                if self
+                    .texture_pool
+                    .textures_mut()
+                    .replace_user_texture(tid, srv.clone()) =>
+            {
+                Ok(tid)
+            },
+            _ => {
+                let tid = self
+                    .texture_pool
+                    .textures_mut()
+                    .register_user_texture(srv, ColorSpace::Srgb);
+                self.background_texture_id = Some(tid);
+                Ok(tid)
+            },
+        }
+    }
+}
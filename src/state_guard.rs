@@ -0,0 +1,111 @@
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+/// The input assembler, vertex shader, rasterizer, pixel shader and output
+/// merger state of a device, captured when this guard is created and
+/// restored when it is dropped.
+///
+/// This protects the state of a host application around passes it does not
+/// expect, such as drawing `egui` or any other pass from a hook:
+///
+/// ```no_run
+/// # fn f(device: &windows::Win32::Graphics::Direct3D10::ID3D10Device) -> windows::core::Result<()> {
+/// let _guard = egui_directx10::DeviceStateGuard::new(device)?;
+/// // Bind and draw anything here.
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`RendererOptions::preserve_device_state`](crate::RendererOptions::preserve_device_state)
+/// wraps every frame of a [`Renderer`](crate::Renderer) in one. The geometry
+/// shader, stream output and predication are left alone; see
+/// [`RendererOptions::disable_unused_stages`](crate::RendererOptions::disable_unused_stages)
+/// for those, or [`DeviceStateGuard::with_all_states`], which
+/// [`Overlay`](crate::Overlay) uses, to capture the whole state of the
+/// device.
+#[doc(alias = "StateGuard", alias = "ScopedD3D10State")]
+pub struct DeviceStateGuard {
+    state_block: ID3D10StateBlock,
+}
+
+impl DeviceStateGuard {
+    /// The state types captured, with the number of slots of each.
+    const STATES: [(D3D10_DEVICE_STATE_TYPES, u32); 18] = [
+        (
+            D3D10_DST_IA_VERTEX_BUFFERS,
+            D3D10_IA_VERTEX_INPUT_RESOURCE_SLOT_COUNT,
+        ),
+        (D3D10_DST_IA_INDEX_BUFFER, 1),
+        (D3D10_DST_IA_INPUT_LAYOUT, 1),
+        (D3D10_DST_IA_PRIMITIVE_TOPOLOGY, 1),
+        (D3D10_DST_VS, 1),
+        (D3D10_DST_VS_SAMPLERS, D3D10_COMMONSHADER_SAMPLER_SLOT_COUNT),
+        (
+            D3D10_DST_VS_SHADER_RESOURCES,
+            D3D10_COMMONSHADER_INPUT_RESOURCE_SLOT_COUNT,
+        ),
+        (
+            D3D10_DST_VS_CONSTANT_BUFFERS,
+            D3D10_COMMONSHADER_CONSTANT_BUFFER_API_SLOT_COUNT,
+        ),
+        (D3D10_DST_RS_VIEWPORTS, 1),
+        (D3D10_DST_RS_SCISSOR_RECTS, 1),
+        (D3D10_DST_RS_RASTERIZER_STATE, 1),
+        (D3D10_DST_PS, 1),
+        (D3D10_DST_PS_SAMPLERS, D3D10_COMMONSHADER_SAMPLER_SLOT_COUNT),
+        (
+            D3D10_DST_PS_SHADER_RESOURCES,
+            D3D10_COMMONSHADER_INPUT_RESOURCE_SLOT_COUNT,
+        ),
+        (
+            D3D10_DST_PS_CONSTANT_BUFFERS,
+            D3D10_COMMONSHADER_CONSTANT_BUFFER_API_SLOT_COUNT,
+        ),
+        (D3D10_DST_OM_RENDER_TARGETS, 1),
+        (D3D10_DST_OM_DEPTH_STENCIL_STATE, 1),
+        (D3D10_DST_OM_BLEND_STATE, 1),
+    ];
+
+    /// Capture the state of `device`.
+    pub fn new(device: &ID3D10Device) -> Result<Self> {
+        let mut mask = D3D10_STATE_BLOCK_MASK::default();
+        for (state, slots) in Self::STATES {
+            unsafe {
+                D3D10StateBlockMaskEnableCapture(&mut mask, state, 0, slots)
+            }?;
+        }
+        Self::with_mask(device, &mask)
+    }
+
+    /// Capture the whole state of `device`, including the stages the
+    /// renderer does not use, for passes that run inside applications
+    /// which may use any of them.
+    pub fn with_all_states(device: &ID3D10Device) -> Result<Self> {
+        let mut mask = D3D10_STATE_BLOCK_MASK::default();
+        unsafe { D3D10StateBlockMaskEnableAll(&mut mask) }?;
+        Self::with_mask(device, &mask)
+    }
+
+    fn with_mask(
+        device: &ID3D10Device,
+        mask: &D3D10_STATE_BLOCK_MASK,
+    ) -> Result<Self> {
+        let state_block = unsafe { D3D10CreateStateBlock(device, mask) }?;
+        unsafe { state_block.Capture() }?;
+        Ok(Self { state_block })
+    }
+}
+
+impl Drop for DeviceStateGuard {
+    fn drop(&mut self) {
+        let result = unsafe {
+            self.state_block.Apply().and_then(|()| {
+                // Do not keep the render targets of the host alive, which
+                // would make its next `ResizeBuffers` fail.
+                self.state_block.ReleaseAllDeviceObjects()
+            })
+        };
+        if let Err(err) = result {
+            log::error!("failed to restore the state of the device: {err}");
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use std::{fmt, mem, time::Instant};
+
+use windows::{Win32::Graphics::Direct3D10::*, core::Result};
+
+use crate::{
+    MeshData, Renderer, VertexData,
+    gpu_timer::{FrameTimer, GpuTimer},
+};
+
+/// A function called after every frame with its statistics, see
+/// [`RendererOptions::on_frame`](crate::RendererOptions::on_frame).
+pub type FrameCallback = Box<dyn FnMut(&RenderStats)>;
+
+/// What was rendered in a frame and how long it took, as passed to
+/// [`RendererOptions::on_frame`](crate::RendererOptions::on_frame).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderStats {
+    /// The size of the rendered region, in pixels.
+    pub frame_size: (u32, u32),
+    /// The number of textures created or updated.
+    pub textures_set: usize,
+    /// The size of the texture updates, in bytes.
+    pub texture_bytes: usize,
+    /// The number of meshes drawn.
+    pub meshes: usize,
+    /// The number of vertices drawn.
+    pub vertices: usize,
+    /// The number of indices drawn.
+    pub indices: usize,
+    /// The CPU time of the frame, in milliseconds, including texture
+    /// uploads.
+    pub cpu_ms: f32,
+    /// The GPU time of the latest earlier frame whose measurement became
+    /// available while rendering this one, in milliseconds. Timestamp
+    /// queries are read a few frames late so that the CPU never waits for
+    /// the GPU; this is `None` when no measurement was available.
+    pub gpu_ms: Option<f32>,
+}
+
+impl fmt::Display for RenderStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} pixels, {} meshes with {} vertices and {} indices, \
+             {} textures set with {} bytes",
+            self.frame_size.0,
+            self.frame_size.1,
+            self.meshes,
+            self.vertices,
+            self.indices,
+            self.textures_set,
+            self.texture_bytes,
+        )
+    }
+}
+
+/// The size of the vertex, index and texture data uploaded in a frame, see
+/// [`BufferStats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufferUsage {
+    /// The size of the vertices of all meshes, in bytes.
+    pub vertex_bytes: usize,
+    /// The size of the indices of all meshes, in bytes.
+    pub index_bytes: usize,
+    /// The size of the vertices of the largest mesh, in bytes.
+    pub largest_mesh_vertex_bytes: usize,
+    /// The size of the indices of the largest mesh, in bytes.
+    pub largest_mesh_index_bytes: usize,
+    /// The size of the texture updates in the
+    /// [`RendererOutput::textures_delta`](crate::RendererOutput::textures_delta)
+    /// of the frame, in bytes.
+    pub texture_bytes: usize,
+}
+
+impl BufferUsage {
+    fn of_meshes(meshes: &[MeshData]) -> Self {
+        let vertex_bytes =
+            |mesh: &MeshData| mesh.vtx.len() * mem::size_of::<VertexData>();
+        let index_bytes =
+            |mesh: &MeshData| mesh.idx.len() * mem::size_of::<u32>();
+        Self {
+            vertex_bytes: meshes.iter().map(vertex_bytes).sum(),
+            index_bytes: meshes.iter().map(index_bytes).sum(),
+            largest_mesh_vertex_bytes: meshes
+                .iter()
+                .map(vertex_bytes)
+                .max()
+                .unwrap_or(0),
+            largest_mesh_index_bytes: meshes
+                .iter()
+                .map(index_bytes)
+                .max()
+                .unwrap_or(0),
+            texture_bytes: 0,
+        }
+    }
+
+    /// The larger of each size of `self` and `other`.
+    fn max(self, other: Self) -> Self {
+        Self {
+            vertex_bytes: self.vertex_bytes.max(other.vertex_bytes),
+            index_bytes: self.index_bytes.max(other.index_bytes),
+            largest_mesh_vertex_bytes: self
+                .largest_mesh_vertex_bytes
+                .max(other.largest_mesh_vertex_bytes),
+            largest_mesh_index_bytes: self
+                .largest_mesh_index_bytes
+                .max(other.largest_mesh_index_bytes),
+            texture_bytes: self.texture_bytes.max(other.texture_bytes),
+        }
+    }
+}
+
+/// The [`BufferUsage`] of the last frame and its high-water marks, as
+/// returned by [`Renderer::buffer_stats`], to pick initial buffer capacities
+/// that a session does not outgrow.
+///
+/// The high-water marks are tracked separately for every size, so they may
+/// come from different frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufferStats {
+    /// The usage of the last frame.
+    pub last_frame: BufferUsage,
+    /// The largest usage of any frame since the renderer was created or
+    /// [`Renderer::reset_buffer_stats`] was called.
+    pub high_water: BufferUsage,
+    /// The number of frames the high-water marks were taken from.
+    pub frames: u64,
+}
+
+/// Collects the [`RenderStats`] of every frame, for
+/// [`RendererOptions::frame_watchdog`](crate::RendererOptions::frame_watchdog)
+/// and [`RendererOptions::on_frame`](crate::RendererOptions::on_frame).
+pub(crate) struct StatsCollector {
+    timer: GpuTimer<RenderStats>,
+    /// The statistics of the frame being rendered.
+    stats: RenderStats,
+    /// When the frame being rendered started.
+    start: Option<Instant>,
+    /// Whether the GPU time of the frame being rendered is measured.
+    measuring: bool,
+}
+
+impl StatsCollector {
+    pub fn new(device: &ID3D10Device) -> Result<Self> {
+        Ok(Self {
+            timer: GpuTimer::new(device)?,
+            stats: RenderStats::default(),
+            start: None,
+            measuring: false,
+        })
+    }
+
+    /// Start a frame of `frame_size` that sets the textures in
+    /// `textures_deltas`. Returns the timer of the frame, if its GPU time
+    /// can be measured, and the GPU time and statistics of an earlier frame
+    /// that became available.
+    fn begin<'a>(
+        &mut self,
+        frame_size: (u32, u32),
+        textures_deltas: impl IntoIterator<Item = &'a egui::TexturesDelta>,
+    ) -> (Option<FrameTimer>, Option<(f32, RenderStats)>) {
+        self.start = Some(Instant::now());
+        self.stats = RenderStats {
+            frame_size,
+            ..RenderStats::default()
+        };
+        for (_, delta) in textures_deltas
+            .into_iter()
+            .flat_map(|textures_delta| &textures_delta.set)
+        {
+            self.stats.textures_set += 1;
+            self.stats.texture_bytes += delta.image.width()
+                * delta.image.height()
+                * delta.image.bytes_per_pixel();
+        }
+        let Some((timer, measured)) = self.timer.begin(self.stats) else {
+            return (None, None);
+        };
+        self.measuring = true;
+        if let Some((gpu_time, _)) = measured {
+            self.stats.gpu_ms = Some(gpu_time);
+        }
+        (Some(timer), measured)
+    }
+
+    /// Count the meshes drawn in the frame being rendered.
+    fn count_meshes(&mut self, meshes: &[MeshData]) {
+        self.stats.meshes = meshes.len();
+        self.stats.vertices = meshes.iter().map(|mesh| mesh.vtx.len()).sum();
+        self.stats.indices = meshes.iter().map(|mesh| mesh.idx.len()).sum();
+    }
+
+    /// Finish the frame being rendered, and return its statistics.
+    fn end(&mut self) -> Option<RenderStats> {
+        let start = self.start.take()?;
+        self.stats.cpu_ms = start.elapsed().as_secs_f32() * 1000.;
+        let stats = self.stats;
+        if self.measuring
+            && let Some(pending) = self.timer.last_mut()
+        {
+            *pending = stats;
+        }
+        self.measuring = false;
+        Some(stats)
+    }
+}
+
+impl Renderer {
+    /// The sizes of the vertex, index and texture data uploaded in the last
+    /// frame, and their high-water marks.
+    pub fn buffer_stats(&self) -> BufferStats {
+        self.buffer_stats
+    }
+
+    /// Reset the high-water marks of [`Renderer::buffer_stats`], for example
+    /// after loading screens whose UI is not representative.
+    pub fn reset_buffer_stats(&mut self) {
+        self.buffer_stats = BufferStats::default();
+    }
+
+    /// Replace the [`RendererOptions::on_frame`](crate::RendererOptions::on_frame)
+    /// callback of this [`Renderer`].
+    pub fn set_on_frame(
+        &mut self,
+        on_frame: Option<FrameCallback>,
+    ) -> Result<()> {
+        self.options.on_frame = on_frame;
+        self.update_stats_collector()
+    }
+
+    /// Create or drop the [`StatsCollector`], depending on whether any
+    /// option needs it.
+    pub(crate) fn update_stats_collector(&mut self) -> Result<()> {
+        let needed = self.options.frame_watchdog.is_some()
+            || self.options.on_frame.is_some();
+        if !needed {
+            self.stats = None;
+        } else if self.stats.is_none() {
+            self.stats = Some(StatsCollector::new(&self.device)?);
+        }
+        Ok(())
+    }
+
+    /// Start collecting the statistics of a frame, if any option needs
+    /// them. The GPU time is measured until the timer is dropped.
+    pub(crate) fn begin_stats<'a>(
+        &mut self,
+        frame_size: (u32, u32),
+        textures_deltas: impl IntoIterator<Item = &'a egui::TexturesDelta>,
+    ) -> Option<FrameTimer> {
+        let textures_deltas = textures_deltas.into_iter().collect::<Vec<_>>();
+        self.buffer_stats.last_frame = BufferUsage {
+            texture_bytes: textures_deltas
+                .iter()
+                .flat_map(|textures_delta| &textures_delta.set)
+                .map(|(_, delta)| {
+                    delta.image.width()
+                        * delta.image.height()
+                        * delta.image.bytes_per_pixel()
+                })
+                .sum(),
+            ..BufferUsage::default()
+        };
+        let (timer, measured) =
+            self.stats.as_mut()?.begin(frame_size, textures_deltas);
+        if let (Some(watchdog), Some((gpu_time, stats))) =
+            (&self.options.frame_watchdog, measured)
+        {
+            watchdog.check_gpu_time(gpu_time, &stats);
+        }
+        timer
+    }
+
+    pub(crate) fn count_meshes(&mut self, meshes: &[MeshData]) {
+        self.buffer_stats.last_frame = BufferUsage {
+            texture_bytes: self.buffer_stats.last_frame.texture_bytes,
+            ..BufferUsage::of_meshes(meshes)
+        };
+        if let Some(stats) = &mut self.stats {
+            stats.count_meshes(meshes);
+        }
+    }
+
+    /// Finish collecting the statistics of a frame, and report them.
+    pub(crate) fn end_stats(&mut self) {
+        let buffer_stats = &mut self.buffer_stats;
+        buffer_stats.high_water =
+            buffer_stats.high_water.max(buffer_stats.last_frame);
+        buffer_stats.frames += 1;
+        let Some(stats) = self.stats.as_mut().and_then(StatsCollector::end)
+        else {
+            return;
+        };
+        if let Some(watchdog) = &self.options.frame_watchdog {
+            watchdog.check_cpu_time(&stats);
+        }
+        if let Some(on_frame) = &mut self.options.on_frame {
+            on_frame(&stats);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! The swap chain of a window, shared by the `winit` and Win32 integrations.
+
+use std::mem;
+
+use windows::{
+    Win32::{
+        Foundation::{HWND, RECT},
+        Graphics::{
+            Direct3D10::*,
+            Dxgi::{Common::*, *},
+            Gdi::{DEVMODEW, ENUM_CURRENT_SETTINGS, EnumDisplaySettingsW},
+        },
+    },
+    core::{Interface, PCWSTR, Result},
+};
+
+const FRAME_FORMAT: DXGI_FORMAT = DXGI_FORMAT_R8G8B8A8_UNORM;
+const BUFFER_COUNT: u32 = 2;
+
+/// A swap chain presenting to a window, and a render target view of its
+/// back buffer.
+pub struct WindowSwapChain {
+    device: ID3D10Device,
+    swap_chain: IDXGISwapChain,
+    render_target: Option<ID3D10RenderTargetView>,
+    size: (u32, u32),
+    /// Whether the buffers were recreated since the last present, so that
+    /// the whole window has to be presented.
+    resized: bool,
+}
+
+impl WindowSwapChain {
+    pub fn new(
+        device: &ID3D10Device,
+        window: HWND,
+        width: u32,
+        height: u32,
+    ) -> Result<Self> {
+        let dxgi_factory: IDXGIFactory =
+            unsafe { device.cast::<IDXGIDevice>()?.GetAdapter()?.GetParent() }?;
+        let swap_chain_desc = DXGI_SWAP_CHAIN_DESC {
+            BufferDesc: DXGI_MODE_DESC {
+                Width: width,
+                Height: height,
+                Format: FRAME_FORMAT,
+                ..DXGI_MODE_DESC::default()
+            },
+            SampleDesc: DXGI_SAMPLE_DESC {
+                Count: 1,
+                Quality: 0,
+            },
+            BufferUsage: DXGI_USAGE_RENDER_TARGET_OUTPUT,
+            BufferCount: BUFFER_COUNT,
+            OutputWindow: window,
+            Windowed: true.into(),
+            // Unlike `DISCARD`, this allows presenting with dirty rects.
+            SwapEffect: DXGI_SWAP_EFFECT_SEQUENTIAL,
+            Flags: 0,
+        };
+        let mut swap_chain = None;
+        unsafe {
+            dxgi_factory.CreateSwapChain(
+                device,
+                &swap_chain_desc,
+                &mut swap_chain,
+            )
+        }
+        .ok()?;
+        let swap_chain = swap_chain.unwrap();
+        unsafe {
+            dxgi_factory.MakeWindowAssociation(window, DXGI_MWA_NO_ALT_ENTER)
+        }?;
+        let render_target = create_render_target(device, &swap_chain)?;
+        Ok(Self {
+            device: device.clone(),
+            swap_chain,
+            render_target: Some(render_target),
+            size: (width, height),
+            resized: false,
+        })
+    }
+
+    pub fn swap_chain(&self) -> &IDXGISwapChain {
+        &self.swap_chain
+    }
+
+    /// The render target view of the back buffer, or `None` if the last
+    /// resize failed.
+    pub fn render_target(&self) -> Option<&ID3D10RenderTargetView> {
+        self.render_target.as_ref()
+    }
+
+    /// The refresh rate of the monitor the window is mostly displayed on, in
+    /// hertz, or `None` if it is unknown.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        let output = unsafe { self.swap_chain.GetContainingOutput() }.ok()?;
+        let desc = unsafe { output.GetDesc() }.ok()?;
+        let mut mode = DEVMODEW {
+            dmSize: mem::size_of::<DEVMODEW>() as _,
+            ..Default::default()
+        };
+        unsafe {
+            EnumDisplaySettingsW(
+                PCWSTR(desc.DeviceName.as_ptr()),
+                ENUM_CURRENT_SETTINGS,
+                &mut mode,
+            )
+        }
+        .ok()
+        .ok()?;
+        // 0 and 1 stand for the default rate of the hardware.
+        (mode.dmDisplayFrequency > 1).then_some(mode.dmDisplayFrequency as f32)
+    }
+
+    /// Resize the buffers of the swap chain. Sizes of zero, as reported for
+    /// minimized windows, are ignored, as are resizes to the current size.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        if width == 0
+            || height == 0
+            || (width, height) == self.size && self.render_target.is_some()
+        {
+            return Ok(());
+        }
+        self.size = (width, height);
+        self.render_target = None;
+        self.resized = true;
+        unsafe {
+            self.swap_chain.ResizeBuffers(
+                BUFFER_COUNT,
+                width,
+                height,
+                FRAME_FORMAT,
+                DXGI_SWAP_CHAIN_FLAG(0),
+            )
+        }?;
+        self.render_target =
+            Some(create_render_target(&self.device, &self.swap_chain)?);
+        Ok(())
+    }
+
+    /// Present the back buffer. With a `dirty_rect` from
+    /// [`Renderer::dirty_rect`](crate::Renderer::dirty_rect), only that part
+    /// of the window is updated if `IDXGISwapChain1` is available. If it is
+    /// empty, the last frame is presented again, which still waits for the
+    /// vertical blank, so that idle applications that repaint continuously
+    /// stay throttled to the refresh rate.
+    ///
+    /// The first present after [`WindowSwapChain::resize`] always updates
+    /// the whole window, as the contents of the new buffers are undefined
+    /// outside of what was drawn to them.
+    pub fn present(&mut self, dirty_rect: Option<RECT>) -> Result<()> {
+        let resized = mem::take(&mut self.resized);
+        let dirty_rect = dirty_rect.filter(|_| !resized);
+        let swap_chain = dirty_rect.and_then(|dirty_rect| {
+            Some((self.swap_chain.cast::<IDXGISwapChain1>().ok()?, dirty_rect))
+        });
+        let Some((swap_chain, mut dirty_rect)) = swap_chain else {
+            return unsafe { self.swap_chain.Present(1, DXGI_PRESENT(0)) }.ok();
+        };
+        if dirty_rect.right <= dirty_rect.left
+            || dirty_rect.bottom <= dirty_rect.top
+        {
+            return unsafe {
+                self.swap_chain.Present(1, DXGI_PRESENT_DO_NOT_SEQUENCE)
+            }
+            .ok();
+        }
+        let parameters = DXGI_PRESENT_PARAMETERS {
+            DirtyRectsCount: 1,
+            pDirtyRects: &mut dirty_rect,
+            ..Default::default()
+        };
+        unsafe { swap_chain.Present1(1, DXGI_PRESENT(0), &parameters) }.ok()
+    }
+}
+
+fn create_render_target(
+    device: &ID3D10Device,
+    swap_chain: &IDXGISwapChain,
+) -> Result<ID3D10RenderTargetView> {
+    let texture = unsafe { swap_chain.GetBuffer::<ID3D10Texture2D>(0) }?;
+    let mut render_target = None;
+    unsafe {
+        device.CreateRenderTargetView(&texture, None, Some(&mut render_target))
+    }?;
+    Ok(render_target.unwrap())
+}
@@ -0,0 +1,144 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use windows::{
+    Win32::{Foundation::E_INVALIDARG, Graphics::Direct3D10::*},
+    core::{Error, Result},
+};
+
+#[cfg(feature = "user-textures")]
+use crate::ColorSpace;
+use crate::{Renderer, RendererOptions, RendererOutput};
+
+/// A [`Renderer`] that can be shared between threads, for engines that keep
+/// it in shared state and render or upload textures from whichever thread
+/// is current.
+///
+/// ## Threading Model
+///
+/// Calls are serialized by a mutex around the renderer, and every call
+/// additionally holds the [`DeviceLock`](crate::DeviceLock) of the device,
+/// as with [`RendererOptions::multithread_protected`], which is forced on.
+/// Other threads may keep using the device, and their calls never
+/// interleave with a render pass, as long as they also take the
+/// `DeviceLock` around sequences of calls.
+///
+/// Direct3D10 objects may be used from any thread unless the device was
+/// created with `D3D10_CREATE_DEVICE_SINGLETHREADED`, which is rejected.
+///
+/// The renderer is only reachable through the methods of this type, so that
+/// nothing bound to one thread, such as callbacks or a
+/// [`SharedTexturePool`](crate::SharedTexturePool), can be shared with it.
+/// For the same reason, [`RendererOptions::on_frame`] must be `None`. Use
+/// [`SyncRenderer::into_inner`] to get the renderer back for the rest of
+/// its API.
+pub struct SyncRenderer {
+    renderer: Mutex<Renderer>,
+}
+
+// SAFETY: The `Renderer` is `!Send` because of its COM pointers, its texture
+// allocator and its callbacks. COM pointers of devices without
+// `D3D10_CREATE_DEVICE_SINGLETHREADED` are free threaded. The allocator is
+// the default one and no callbacks can be set. The texture pool is reference
+// counted atomically and locked while it is used, so it stays consistent
+// even if another clone of it is used at the same time.
+unsafe impl Send for SyncRenderer {}
+unsafe impl Sync for SyncRenderer {}
+
+impl SyncRenderer {
+    /// Create a [`Renderer`] on `device` with `options` like
+    /// [`Renderer::with_options`], with
+    /// [`RendererOptions::multithread_protected`] enabled.
+    ///
+    /// Returns `E_INVALIDARG` if `device` was created with
+    /// `D3D10_CREATE_DEVICE_SINGLETHREADED` or [`RendererOptions::on_frame`]
+    /// is set.
+    pub fn new(
+        device: &ID3D10Device,
+        mut options: RendererOptions,
+    ) -> Result<Self> {
+        let flags = unsafe { device.GetCreationFlags() };
+        if flags & D3D10_CREATE_DEVICE_SINGLETHREADED.0 as u32 != 0 {
+            log::error!(
+                "cannot share a renderer between threads on a device created \
+                 with D3D10_CREATE_DEVICE_SINGLETHREADED."
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        if options.on_frame.is_some() {
+            log::error!(
+                "cannot share a renderer with an on_frame callback between \
+                 threads."
+            );
+            return Err(Error::from_hresult(E_INVALIDARG));
+        }
+        options.multithread_protected = true;
+        Ok(Self {
+            renderer: Mutex::new(Renderer::with_options(device, options)?),
+        })
+    }
+
+    /// See [`Renderer::render`].
+    pub fn render(
+        &self,
+        device_context: &ID3D10Device,
+        render_target: &ID3D10RenderTargetView,
+        egui_ctx: &egui::Context,
+        egui_output: RendererOutput,
+    ) -> Result<()> {
+        self.lock()
+            .render(device_context, render_target, egui_ctx, egui_output)
+    }
+
+    /// See [`Renderer::update_textures`].
+    pub fn update_textures(
+        &self,
+        device_context: &ID3D10Device,
+        textures_delta: egui::TexturesDelta,
+    ) -> Result<()> {
+        self.lock().update_textures(device_context, textures_delta)
+    }
+
+    /// See [`Renderer::register_user_texture`].
+    #[cfg(feature = "user-textures")]
+    pub fn register_user_texture(
+        &self,
+        srv: ID3D10ShaderResourceView,
+    ) -> egui::TextureId {
+        self.lock().register_user_texture(srv)
+    }
+
+    /// See [`Renderer::register_user_texture_with_color_space`].
+    #[cfg(feature = "user-textures")]
+    pub fn register_user_texture_with_color_space(
+        &self,
+        srv: ID3D10ShaderResourceView,
+        color_space: ColorSpace,
+    ) -> egui::TextureId {
+        self.lock()
+            .register_user_texture_with_color_space(srv, color_space)
+    }
+
+    /// See [`Renderer::unregister_user_texture`].
+    #[cfg(feature = "user-textures")]
+    pub fn unregister_user_texture(&self, tid: egui::TextureId) -> bool {
+        self.lock().unregister_user_texture(tid)
+    }
+
+    /// See [`Renderer::trim`].
+    pub fn trim(&self, evict_textures: bool) -> Result<()> {
+        self.lock().trim(evict_textures)
+    }
+
+    /// The [`Renderer`], to be used from the current thread again.
+    pub fn into_inner(self) -> Renderer {
+        self.renderer
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the renderer. A renderer whose lock was poisoned by a panic
+    /// while rendering is still used, as its state is rebuilt every frame.
+    fn lock(&self) -> MutexGuard<'_, Renderer> {
+        self.renderer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,36 @@
+use egui::CursorIcon;
+
+use windows::{Win32::UI::WindowsAndMessaging::*, core::PCWSTR};
+
+/// The resource id of the system cursor closest to `icon`, for
+/// `LoadCursorW`, or `None` for [`CursorIcon::None`].
+pub fn system_cursor(icon: CursorIcon) -> Option<PCWSTR> {
+    Some(match icon {
+        CursorIcon::None => return None,
+        CursorIcon::Help => IDC_HELP,
+        CursorIcon::PointingHand | CursorIcon::Grab => IDC_HAND,
+        CursorIcon::Progress => IDC_APPSTARTING,
+        CursorIcon::Wait => IDC_WAIT,
+        CursorIcon::Cell | CursorIcon::Crosshair => IDC_CROSS,
+        CursorIcon::Text | CursorIcon::VerticalText => IDC_IBEAM,
+        CursorIcon::Move | CursorIcon::Grabbing | CursorIcon::AllScroll => {
+            IDC_SIZEALL
+        },
+        CursorIcon::NoDrop | CursorIcon::NotAllowed => IDC_NO,
+        CursorIcon::ResizeHorizontal
+        | CursorIcon::ResizeEast
+        | CursorIcon::ResizeWest
+        | CursorIcon::ResizeColumn => IDC_SIZEWE,
+        CursorIcon::ResizeNeSw
+        | CursorIcon::ResizeNorthEast
+        | CursorIcon::ResizeSouthWest => IDC_SIZENESW,
+        CursorIcon::ResizeNwSe
+        | CursorIcon::ResizeNorthWest
+        | CursorIcon::ResizeSouthEast => IDC_SIZENWSE,
+        CursorIcon::ResizeVertical
+        | CursorIcon::ResizeNorth
+        | CursorIcon::ResizeSouth
+        | CursorIcon::ResizeRow => IDC_SIZENS,
+        _ => IDC_ARROW,
+    })
+}
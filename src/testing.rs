@@ -0,0 +1,67 @@
+use egui::TextureId;
+
+use crate::Renderer;
+
+/// A change the texture pool of a [`Renderer`] made to a texture managed by
+/// `egui`, as returned by [`Renderer::take_texture_events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureEvent {
+    /// The texture that changed.
+    pub id: TextureId,
+    /// What happened to it.
+    pub kind: TextureEventKind,
+}
+
+/// What happened to a texture in a [`TextureEvent`]. Sizes and positions
+/// are in pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureEventKind {
+    /// A texture of the given size was created for a whole image.
+    Created([usize; 2]),
+    /// The region of the given size at the given position was overwritten.
+    Updated {
+        /// The top left corner of the region.
+        pos: [usize; 2],
+        /// The size of the region.
+        size: [usize; 2],
+    },
+    /// The texture was released, because it was freed, replaced by a whole
+    /// image or its context was removed.
+    Freed,
+    /// The GPU texture was released by [`Renderer::trim`], keeping its
+    /// pixels.
+    Evicted,
+    /// The GPU texture of an evicted texture of the given size was
+    /// recreated.
+    Restored([usize; 2]),
+    /// A set of the texture was ignored, because its image was malformed,
+    /// or because it updated a region of a texture that does not exist or
+    /// out of its bounds.
+    Ignored,
+}
+
+/// The state of a texture managed by `egui`, as returned by
+/// [`Renderer::managed_textures`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureState {
+    /// The texture.
+    pub id: TextureId,
+    /// The size of the texture, in pixels.
+    pub size: [usize; 2],
+    /// Whether the texture is on the GPU, rather than evicted.
+    pub resident: bool,
+}
+
+impl Renderer {
+    /// The changes made to the textures managed by `egui` since the last
+    /// call, in order, for tests asserting the texture updates of a
+    /// sequence of frames, for example on a WARP device.
+    pub fn take_texture_events(&mut self) -> Vec<TextureEvent> {
+        self.texture_pool.textures_mut().take_events()
+    }
+
+    /// The textures managed by `egui` this renderer holds, sorted by id.
+    pub fn managed_textures(&self) -> Vec<TextureState> {
+        self.texture_pool.textures().managed_textures()
+    }
+}
@@ -10,56 +10,144 @@
 
 use std::{collections::HashMap, mem};
 
-use egui::{Color32, ImageData, TextureId, TexturesDelta};
+use egui::{Color32, ImageData, TextureId, TextureWrapMode, TexturesDelta};
+
+use crate::{ColorSpace, TextureAllocator, backend::Backend, compat, readback};
+#[cfg(feature = "testing")]
+use crate::{TextureEvent, TextureEventKind, TextureState};
 
 use windows::{
-    core::Result,
-    Win32::Graphics::{Direct3D10::*, Dxgi::Common::*},
+    Win32::{
+        Foundation::E_INVALIDARG,
+        Graphics::{Direct3D::*, Direct3D10::*, Dxgi::Common::*},
+    },
+    core::{Error, Interface, Result},
 };
 
-struct ManagedTexture {
-    tex: ID3D10Texture2D,
-    srv: ID3D10ShaderResourceView,
+struct ManagedTexture<B: Backend> {
+    /// The GPU texture and its view, or `None` if it was evicted.
+    gpu: Option<(B::Texture, B::ShaderResourceView)>,
     pixels: Vec<Color32>,
     width: usize,
+    wrap_mode: TextureWrapMode,
 }
 
-enum Texture {
+enum Texture<B: Backend> {
     /// A texture managed by egui (created from ImageData)
-    Managed(ManagedTexture),
+    Managed(ManagedTexture<B>),
     /// A user-provided texture (registered from an existing shader resource view)
-    User { srv: ID3D10ShaderResourceView },
+    User {
+        srv: B::ShaderResourceView,
+        color_space: ColorSpace,
+    },
 }
 
-impl Texture {
+impl<B: Backend> Texture<B> {
     pub fn is_managed(&self) -> bool {
         matches!(self, Texture::Managed(_))
     }
 
+    #[cfg(feature = "user-textures")]
     pub fn is_user(&self) -> bool {
         matches!(self, Texture::User { .. })
     }
+
+    /// Whether the region of `size` at `pos` lies within this texture,
+    /// which must be managed.
+    fn contains_region(&self, [x, y]: [usize; 2], [w, h]: [usize; 2]) -> bool {
+        let Texture::Managed(managed) = self else {
+            return false;
+        };
+        let height = managed.pixels.len() / managed.width;
+        x.checked_add(w).is_some_and(|right| right <= managed.width)
+            && y.checked_add(h).is_some_and(|bottom| bottom <= height)
+    }
 }
 
-pub struct TexturePool {
-    device: ID3D10Device,
-    pool: HashMap<TextureId, Texture>,
+/// The size and format of a texture, as returned by
+/// [`Renderer::texture_info`](crate::Renderer::texture_info).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureInfo {
+    /// The size of the texture in pixels, as seen through its view.
+    pub size: [usize; 2],
+    /// The format of the view of the texture.
+    pub format: DXGI_FORMAT,
+    /// Who owns the texture.
+    pub kind: TextureKind,
+}
+
+/// Who owns a texture described by a [`TextureInfo`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextureKind {
+    /// A texture managed by `egui`, such as the font atlas or images loaded
+    /// through `egui`. Its pixels are kept on the CPU, so it is described
+    /// even while evicted by [`Renderer::trim`](crate::Renderer::trim).
+    Managed,
+    /// A texture registered with
+    /// [`Renderer::register_user_texture`](crate::Renderer::register_user_texture).
+    User,
+}
+
+pub struct TexturePool<B: Backend = ID3D10Device> {
+    device: B,
+    allocator: Box<dyn TextureAllocator>,
+    pool: HashMap<TextureId, Texture<B>>,
     next_user_texture_id: u64,
+    #[cfg(feature = "testing")]
+    events: Vec<TextureEvent>,
 }
 
-impl TexturePool {
-    pub fn new(device: &ID3D10Device) -> Self {
+impl<B: Backend> Drop for TexturePool<B> {
+    fn drop(&mut self) {
+        for (_, texture) in mem::take(&mut self.pool) {
+            self.release(texture);
+        }
+    }
+}
+
+impl<B: Backend> TexturePool<B> {
+    pub fn new(device: B, allocator: Box<dyn TextureAllocator>) -> Self {
         Self {
-            device: device.clone(),
+            device,
+            allocator,
             pool: HashMap::new(),
             next_user_texture_id: 0,
+            #[cfg(feature = "testing")]
+            events: Vec::new(),
         }
     }
 
-    pub fn get_srv(&self, tid: TextureId) -> Option<ID3D10ShaderResourceView> {
-        self.pool.get(&tid).map(|t| match t {
-            Texture::Managed(managed) => managed.srv.clone(),
-            Texture::User { srv } => srv.clone(),
+    pub fn get_srv(&self, tid: TextureId) -> Option<B::ShaderResourceView> {
+        self.pool.get(&tid).and_then(|t| match t {
+            Texture::Managed(managed) => {
+                managed.gpu.as_ref().map(|(_, srv)| srv.clone())
+            },
+            Texture::User { srv, .. } => Some(srv.clone()),
+        })
+    }
+
+    /// The color space a texture is encoded in. Textures managed by egui
+    /// are always sRGB.
+    pub fn color_space(&self, tid: TextureId) -> ColorSpace {
+        match self.pool.get(&tid) {
+            Some(Texture::User { color_space, .. }) => *color_space,
+            _ => ColorSpace::Srgb,
+        }
+    }
+
+    /// How a texture managed by egui is sampled outside of its bounds, or
+    /// `None` for user textures.
+    pub fn wrap_mode(&self, tid: TextureId) -> Option<TextureWrapMode> {
+        match self.pool.get(&tid) {
+            Some(Texture::Managed(managed)) => Some(managed.wrap_mode),
+            _ => None,
+        }
+    }
+
+    pub fn has_non_srgb_textures(&self) -> bool {
+        self.pool.values().any(|t| {
+            matches!(t, Texture::User { color_space, .. }
+                if *color_space != ColorSpace::Srgb)
         })
     }
 
@@ -69,16 +157,50 @@ impl TexturePool {
     /// The returned TextureId will be unique and won't conflict with egui's managed textures.
     pub fn register_user_texture(
         &mut self,
-        srv: ID3D10ShaderResourceView,
+        srv: B::ShaderResourceView,
+        color_space: ColorSpace,
     ) -> TextureId {
         let id = TextureId::User(self.next_user_texture_id);
         self.next_user_texture_id += 1;
-        self.pool.insert(id, Texture::User { srv });
+        self.pool.insert(id, Texture::User { srv, color_space });
         id
     }
 
+    /// Upload an image owned by the renderer itself and get a TextureId for
+    /// it. Unlike user textures, it cannot be unregistered and lives as long
+    /// as the pool.
+    #[cfg(feature = "software-cursor")]
+    pub fn register_internal_image(
+        &mut self,
+        image: ImageData,
+    ) -> Result<TextureId> {
+        let id = TextureId::User(self.next_user_texture_id);
+        self.next_user_texture_id += 1;
+        let texture =
+            self.create_managed_texture(image, TextureWrapMode::ClampToEdge)?;
+        self.pool.insert(id, texture);
+        Ok(id)
+    }
+
+    /// Replace the view of a user texture, keeping its TextureId and color
+    /// space. Returns false if it is not a user texture.
+    pub fn replace_user_texture(
+        &mut self,
+        tid: TextureId,
+        new_srv: B::ShaderResourceView,
+    ) -> bool {
+        match self.pool.get_mut(&tid) {
+            Some(Texture::User { srv, .. }) => {
+                *srv = new_srv;
+                true
+            },
+            _ => false,
+        }
+    }
+
     /// Unregister a user texture by its TextureId.
     /// Returns true if the texture was found and removed, false otherwise.
+    #[cfg(feature = "user-textures")]
     pub fn unregister_user_texture(&mut self, tid: TextureId) -> bool {
         if self.pool.get(&tid).is_some_and(|t| t.is_user()) {
             self.pool.remove(&tid);
@@ -88,48 +210,163 @@ impl TexturePool {
         }
     }
 
-    pub fn update(
-        &mut self,
-        ctx: &ID3D10Device,
-        delta: TexturesDelta,
-    ) -> Result<()> {
+    /// Release the managed textures for which `predicate` returns true.
+    pub fn free_managed(&mut self, predicate: impl Fn(TextureId) -> bool) {
+        let freed = self
+            .pool
+            .iter()
+            .filter(|(tid, t)| t.is_managed() && predicate(**tid))
+            .map(|(tid, _)| *tid)
+            .collect::<Vec<_>>();
+        for tid in freed {
+            let texture = self.pool.remove(&tid).unwrap();
+            self.release(texture);
+            #[cfg(feature = "testing")]
+            self.record(tid, TextureEventKind::Freed);
+        }
+    }
+
+    pub fn update(&mut self, delta: TexturesDelta) -> Result<()> {
+        self.restore()?;
         for (tid, delta) in delta.set {
-            if delta.is_whole()
-                && delta.image.width() > 0
-                && delta.image.height() > 0
-            {
-                self.pool.insert(
-                    tid,
-                    Self::create_managed_texture(&self.device, delta.image)?,
+            if !is_valid_image(&delta.image) {
+                log::warn!(
+                    "egui wants to set texture {tid:?} to an empty or malformed image. this request will be ignored."
                 );
-                // the old texture is returned and dropped here, freeing
-                // all its gpu resource.
+                #[cfg(feature = "testing")]
+                self.record(tid, TextureEventKind::Ignored);
+                continue;
+            }
+            if delta.is_whole() {
+                #[cfg(feature = "testing")]
+                let size = delta.image.size();
+                let texture = self.create_managed_texture(
+                    delta.image,
+                    delta.options.wrap_mode,
+                )?;
+                if let Some(old) = self.pool.insert(tid, texture) {
+                    self.release(old);
+                    #[cfg(feature = "testing")]
+                    self.record(tid, TextureEventKind::Freed);
+                }
+                #[cfg(feature = "testing")]
+                self.record(tid, TextureEventKind::Created(size));
             } else if let Some(tex) =
                 self.pool.get_mut(&tid).filter(|t| t.is_managed())
             {
-                Self::update_partial(
-                    ctx,
-                    tex,
-                    delta.image,
-                    delta.pos.unwrap(),
-                )?;
+                let pos = delta.pos.unwrap();
+                if !tex.contains_region(pos, delta.image.size()) {
+                    log::warn!(
+                        "egui wants to update texture {tid:?} out of its bounds. this request will be ignored."
+                    );
+                    #[cfg(feature = "testing")]
+                    self.record(tid, TextureEventKind::Ignored);
+                    continue;
+                }
+                #[cfg(feature = "testing")]
+                self.events.push(TextureEvent {
+                    id: tid,
+                    kind: TextureEventKind::Updated {
+                        pos,
+                        size: delta.image.size(),
+                    },
+                });
+                Self::update_partial(&self.device, tex, delta.image, pos)?;
             } else {
                 log::warn!(
                     "egui wants to update a non-existing texture {tid:?}. this request will be ignored."
                 );
+                #[cfg(feature = "testing")]
+                self.record(tid, TextureEventKind::Ignored);
             }
         }
         for tid in delta.free {
             if self.pool.get(&tid).is_some_and(|t| t.is_managed()) {
-                self.pool.remove(&tid);
+                let texture = self.pool.remove(&tid).unwrap();
+                self.release(texture);
+                #[cfg(feature = "testing")]
+                self.record(tid, TextureEventKind::Freed);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "testing")]
+    fn record(&mut self, id: TextureId, kind: TextureEventKind) {
+        self.events.push(TextureEvent { id, kind });
+    }
+
+    /// The changes made to managed textures since the last call.
+    #[cfg(feature = "testing")]
+    pub fn take_events(&mut self) -> Vec<TextureEvent> {
+        mem::take(&mut self.events)
+    }
+
+    /// The managed textures, sorted by id.
+    #[cfg(feature = "testing")]
+    pub fn managed_textures(&self) -> Vec<TextureState> {
+        let mut textures = self
+            .pool
+            .iter()
+            .filter_map(|(tid, texture)| match texture {
+                Texture::Managed(managed) => Some(TextureState {
+                    id: *tid,
+                    size: [managed.width, managed.pixels.len() / managed.width],
+                    resident: managed.gpu.is_some(),
+                }),
+                Texture::User { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        textures.sort_by_key(|texture| texture.id);
+        textures
+    }
+
+    /// Release the GPU textures of the textures managed by egui, keeping
+    /// their pixels to recreate them on the next [`TexturePool::update`].
+    pub fn evict(&mut self) {
+        #[cfg_attr(not(feature = "testing"), expect(unused_variables))]
+        for (tid, texture) in &mut self.pool {
+            if let Texture::Managed(managed) = texture
+                && let Some((tex, srv)) = managed.gpu.take()
+            {
+                drop(srv);
+                self.device.free_texture(self.allocator.as_mut(), tex);
+                #[cfg(feature = "testing")]
+                self.events.push(TextureEvent {
+                    id: *tid,
+                    kind: TextureEventKind::Evicted,
+                });
+            }
+        }
+    }
+
+    /// Recreate the GPU textures released by [`TexturePool::evict`].
+    fn restore(&mut self) -> Result<()> {
+        #[cfg_attr(not(feature = "testing"), expect(unused_variables))]
+        for (tid, texture) in &mut self.pool {
+            if let Texture::Managed(managed) = texture
+                && managed.gpu.is_none()
+            {
+                let height = managed.pixels.len() / managed.width;
+                managed.gpu = Some(self.device.create_texture(
+                    self.allocator.as_mut(),
+                    managed.width,
+                    height,
+                    &managed.pixels,
+                )?);
+                #[cfg(feature = "testing")]
+                self.events.push(TextureEvent {
+                    id: *tid,
+                    kind: TextureEventKind::Restored([managed.width, height]),
+                });
             }
         }
         Ok(())
     }
 
     fn update_partial(
-        ctx: &ID3D10Device,
-        old: &mut Texture,
+        device: &B,
+        old: &mut Texture<B>,
         image: ImageData,
         [nx, ny]: [usize; 2],
     ) -> Result<()> {
@@ -140,95 +377,321 @@ impl TexturePool {
             return Ok(());
         };
 
-        match image {
-            ImageData::Color(f) => {
-                let row_pitch = f.width() * 4; // 4 bytes per pixel
-                let mut update_data = vec![0u8; f.height() * row_pitch];
-
-                for y in 0..f.height() {
-                    for x in 0..f.width() {
-                        let frac = y * f.width() + x;
-                        let whole = (ny + y) * old.width + nx + x;
-                        let dst_idx = y * row_pitch + x * 4;
-
-                        // Update old.pixels
-                        old.pixels[whole] = f.pixels[frac];
-
-                        // Update update_data
-                        let color_array = f.pixels[frac].to_array();
-                        update_data[dst_idx..dst_idx + 4]
-                            .copy_from_slice(&color_array);
-                    }
-                }
+        let pixels = compat::image_pixels(&image);
+        for (y, row) in pixels.chunks_exact(image.width()).enumerate() {
+            let whole = (ny + y) * old.width + nx;
+            old.pixels[whole..whole + row.len()].copy_from_slice(row);
+        }
 
-                let subresource_data = D3D10_BOX {
-                    left: nx as u32,
-                    top: ny as u32,
-                    front: 0,
-                    right: (nx + f.width()) as u32,
-                    bottom: (ny + f.height()) as u32,
-                    back: 1,
-                };
+        let region = D3D10_BOX {
+            left: nx as u32,
+            top: ny as u32,
+            front: 0,
+            right: (nx + image.width()) as u32,
+            bottom: (ny + image.height()) as u32,
+            back: 1,
+        };
 
-                unsafe {
-                    ctx.UpdateSubresource(
-                        &old.tex,
-                        0,
-                        Some(&subresource_data),
-                        update_data.as_ptr() as _,
-                        row_pitch as u32,
-                        0,
-                    );
-                }
-            },
+        if let Some((tex, _)) = &old.gpu {
+            device.update_texture(tex, region, bytemuck::cast_slice(&pixels));
         }
         Ok(())
     }
 
+    /// Return the GPU texture of a managed texture to the allocator. User
+    /// textures are owned by the application and simply dropped.
+    fn release(&mut self, texture: Texture<B>) {
+        if let Texture::Managed(ManagedTexture {
+            gpu: Some((tex, srv)),
+            ..
+        }) = texture
+        {
+            drop(srv);
+            self.device.free_texture(self.allocator.as_mut(), tex);
+        }
+    }
+
     fn create_managed_texture(
-        device: &ID3D10Device,
+        &mut self,
         data: ImageData,
-    ) -> Result<Texture> {
+        wrap_mode: TextureWrapMode,
+    ) -> Result<Texture<B>> {
         let width = data.width();
 
-        let pixels = match &data {
-            ImageData::Color(c) => c.pixels.clone(),
-        };
+        let pixels = compat::image_pixels(&data).into_owned();
+
+        let (tex, srv) = self.device.create_texture(
+            self.allocator.as_mut(),
+            width,
+            data.height(),
+            &pixels,
+        )?;
 
-        let desc = D3D10_TEXTURE2D_DESC {
-            Width: data.width() as _,
-            Height: data.height() as _,
-            MipLevels: 1,
-            ArraySize: 1,
-            Format: DXGI_FORMAT_R8G8B8A8_UNORM,
-            SampleDesc: DXGI_SAMPLE_DESC {
-                Count: 1,
-                Quality: 0,
+        Ok(Texture::Managed(ManagedTexture {
+            gpu: Some((tex, srv)),
+            width,
+            pixels,
+            wrap_mode,
+        }))
+    }
+}
+
+impl TexturePool {
+    /// The size and format of a texture, or `None` if it does not exist or
+    /// is a user texture whose view is not of a 2D texture.
+    pub fn texture_info(&self, tid: TextureId) -> Option<TextureInfo> {
+        match self.pool.get(&tid)? {
+            Texture::Managed(managed) => Some(TextureInfo {
+                size: [managed.width, managed.pixels.len() / managed.width],
+                format: DXGI_FORMAT_R8G8B8A8_UNORM,
+                kind: TextureKind::Managed,
+            }),
+            Texture::User { srv, .. } => {
+                let mut view_desc = D3D10_SHADER_RESOURCE_VIEW_DESC::default();
+                unsafe { srv.GetDesc(&mut view_desc) };
+                let texture = unsafe { srv.GetResource() }
+                    .ok()?
+                    .cast::<ID3D10Texture2D>()
+                    .ok()?;
+                let mut desc = D3D10_TEXTURE2D_DESC::default();
+                unsafe { texture.GetDesc(&mut desc) };
+                // Views may skip the most detailed mip levels.
+                let mip = match view_desc.ViewDimension {
+                    D3D10_SRV_DIMENSION_TEXTURE2D => unsafe {
+                        view_desc.Anonymous.Texture2D.MostDetailedMip
+                    },
+                    D3D10_SRV_DIMENSION_TEXTURE2DARRAY => unsafe {
+                        view_desc.Anonymous.Texture2DArray.MostDetailedMip
+                    },
+                    _ => 0,
+                };
+                let mip_size = |size: u32| (size >> mip).max(1) as usize;
+                Some(TextureInfo {
+                    size: [mip_size(desc.Width), mip_size(desc.Height)],
+                    format: view_desc.Format,
+                    kind: TextureKind::User,
+                })
+            },
+        }
+    }
+
+    /// Copy the pixels of a texture back from the GPU through a staging
+    /// texture, for example to dump the font atlas or to compare it with a
+    /// golden image in tests. Managed textures evicted by
+    /// [`TexturePool::evict`] are read from their copy on the CPU instead.
+    ///
+    /// User textures must be 2D textures with an 8-bit RGBA or BGRA format,
+    /// and the first mip level of the whole resource is read. Returns
+    /// `E_INVALIDARG` if the texture does not exist or cannot be read.
+    pub fn read_texture(&self, tid: TextureId) -> Result<egui::ColorImage> {
+        let texture = match self.pool.get(&tid) {
+            Some(Texture::Managed(ManagedTexture {
+                gpu: Some((texture, _)),
+                ..
+            })) => texture.clone(),
+            Some(Texture::Managed(managed)) => {
+                let height = managed.pixels.len() / managed.width;
+                return Ok(compat::color_image(
+                    [managed.width, height],
+                    managed.pixels.clone(),
+                ));
+            },
+            Some(Texture::User { srv, .. }) => unsafe { srv.GetResource() }?
+                .cast::<ID3D10Texture2D>()
+                .map_err(|_| {
+                    log::error!("cannot read back {tid:?}, which is not 2D.");
+                    Error::from_hresult(E_INVALIDARG)
+                })?,
+            None => {
+                log::error!("cannot read back non-existing texture {tid:?}.");
+                return Err(Error::from_hresult(E_INVALIDARG));
             },
-            Usage: D3D10_USAGE_DYNAMIC,
-            BindFlags: D3D10_BIND_SHADER_RESOURCE.0 as _,
-            CPUAccessFlags: D3D10_CPU_ACCESS_WRITE.0 as _,
-            ..Default::default()
         };
+        readback::read_texture(&self.device, &texture)
+    }
+}
+
+/// Whether `image` has pixels, and as many as its size says, since the
+/// device reads `width * height` pixels from it.
+fn is_valid_image(image: &ImageData) -> bool {
+    image.width() > 0
+        && image.height() > 0
+        && image.width().checked_mul(image.height())
+            == Some(compat::image_pixel_count(image))
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{TextureOptions, epaint::ImageDelta};
+
+    use super::*;
+    use crate::{
+        DefaultTextureAllocator,
+        backend::recording::{Call, RecordingBackend},
+    };
+
+    fn pool() -> (TexturePool<RecordingBackend>, RecordingBackend) {
+        let backend = RecordingBackend::default();
+        let pool = TexturePool::new(
+            backend.clone(),
+            Box::new(DefaultTextureAllocator),
+        );
+        (pool, backend)
+    }
+
+    fn image(size: [usize; 2], color: Color32) -> egui::ColorImage {
+        compat::color_image(size, vec![color; size[0] * size[1]])
+    }
+
+    fn set(tid: TextureId, delta: ImageDelta) -> TexturesDelta {
+        TexturesDelta {
+            set: vec![(tid, delta)],
+            free: Vec::new(),
+        }
+    }
+
+    fn full(size: [usize; 2]) -> ImageDelta {
+        ImageDelta::full(image(size, Color32::WHITE), TextureOptions::LINEAR)
+    }
+
+    fn partial(pos: [usize; 2], size: [usize; 2]) -> ImageDelta {
+        ImageDelta::partial(
+            pos,
+            image(size, Color32::RED),
+            TextureOptions::LINEAR,
+        )
+    }
 
-        let subresource_data = D3D10_SUBRESOURCE_DATA {
-            pSysMem: pixels.as_ptr() as _,
-            SysMemPitch: (width * mem::size_of::<Color32>()) as u32,
-            SysMemSlicePitch: 0,
+    #[test]
+    fn whole_images_create_textures() {
+        let (mut pool, backend) = pool();
+        let tid = TextureId::Managed(0);
+        pool.update(set(tid, full([4, 2]))).unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [Call::CreateTexture {
+                texture: 0,
+                size: [4, 2]
+            }]
+        );
+        assert_eq!(pool.get_srv(tid), Some(0));
+
+        // Replacing the image releases the previous texture.
+        pool.update(set(tid, full([8, 8]))).unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [
+                Call::CreateTexture {
+                    texture: 1,
+                    size: [8, 8]
+                },
+                Call::FreeTexture(0),
+            ]
+        );
+        assert_eq!(pool.get_srv(tid), Some(1));
+    }
+
+    #[test]
+    fn partial_updates_overwrite_regions() {
+        let (mut pool, backend) = pool();
+        let tid = TextureId::Managed(0);
+        pool.update(set(tid, full([4, 4]))).unwrap();
+        backend.take_calls();
+        pool.update(set(tid, partial([1, 2], [3, 2]))).unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [Call::UpdateTexture {
+                texture: 0,
+                region: D3D10_BOX {
+                    left: 1,
+                    top: 2,
+                    front: 0,
+                    right: 4,
+                    bottom: 4,
+                    back: 1,
+                },
+            }]
+        );
+        let Some(Texture::Managed(managed)) = pool.pool.get(&tid) else {
+            panic!("the texture is not managed");
         };
+        assert_eq!(managed.pixels[2 * 4], Color32::WHITE);
+        assert_eq!(managed.pixels[2 * 4 + 1], Color32::RED);
+        assert_eq!(managed.pixels[3 * 4 + 3], Color32::RED);
+    }
+
+    #[test]
+    fn invalid_updates_are_ignored() {
+        let (mut pool, backend) = pool();
+        let tid = TextureId::Managed(0);
+        pool.update(set(tid, full([0, 0]))).unwrap();
+        pool.update(set(tid, partial([0, 0], [1, 1]))).unwrap();
+        pool.update(set(tid, full([4, 4]))).unwrap();
+        pool.update(set(tid, partial([3, 0], [2, 1]))).unwrap();
+        pool.update(set(tid, partial([usize::MAX, 0], [2, 1])))
+            .unwrap();
+        assert_eq!(
+            backend.take_calls(),
+            [Call::CreateTexture {
+                texture: 0,
+                size: [4, 4]
+            }]
+        );
+    }
 
-        let tex =
-            unsafe { device.CreateTexture2D(&desc, Some(&subresource_data)) }?;
+    #[test]
+    fn freed_textures_are_released() {
+        let (mut pool, backend) = pool();
+        let tid = TextureId::Managed(3);
+        pool.update(set(tid, full([2, 2]))).unwrap();
+        pool.update(TexturesDelta {
+            set: Vec::new(),
+            free: vec![tid],
+        })
+        .unwrap();
+        assert_eq!(backend.take_calls()[1..], [Call::FreeTexture(0)]);
+        assert_eq!(pool.get_srv(tid), None);
+    }
 
-        let mut srv = None;
-        unsafe { device.CreateShaderResourceView(&tex, None, Some(&mut srv)) }?;
-        let srv = srv.unwrap();
+    #[test]
+    fn evicted_textures_are_restored_on_update() {
+        let (mut pool, backend) = pool();
+        let tid = TextureId::Managed(0);
+        pool.update(set(tid, full([2, 3]))).unwrap();
+        pool.evict();
+        assert_eq!(pool.get_srv(tid), None);
+        pool.update(TexturesDelta::default()).unwrap();
+        assert_eq!(
+            backend.take_calls()[1..],
+            [
+                Call::FreeTexture(0),
+                Call::CreateTexture {
+                    texture: 1,
+                    size: [2, 3]
+                },
+            ]
+        );
+        assert_eq!(pool.get_srv(tid), Some(1));
+    }
 
-        Ok(Texture::Managed(ManagedTexture {
-            tex,
-            srv,
-            width,
-            pixels,
-        }))
+    #[test]
+    fn user_textures_are_owned_by_the_application() {
+        let (mut pool, backend) = pool();
+        let tid = pool.register_user_texture(7, ColorSpace::Linear);
+        assert_eq!(pool.get_srv(tid), Some(7));
+        assert_eq!(pool.color_space(tid), ColorSpace::Linear);
+        assert!(pool.has_non_srgb_textures());
+        // egui cannot update or free them.
+        pool.update(set(tid, partial([0, 0], [1, 1]))).unwrap();
+        pool.update(TexturesDelta {
+            set: Vec::new(),
+            free: vec![tid],
+        })
+        .unwrap();
+        assert_eq!(pool.get_srv(tid), Some(7));
+        assert!(pool.replace_user_texture(tid, 8));
+        assert_eq!(pool.get_srv(tid), Some(8));
+        drop(pool);
+        assert_eq!(backend.take_calls(), []);
     }
 }
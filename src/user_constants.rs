@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use egui::TextureId;
+
+use windows::{
+    Win32::{Foundation::E_INVALIDARG, Graphics::Direct3D10::*},
+    core::{Error, Result},
+};
+
+use crate::Renderer;
+
+/// The largest constant buffer a shader can bind, 4096 vectors of 16 bytes.
+const MAX_SIZE: usize = 4096 * 16;
+
+/// Constant buffers provided by the application for custom shaders.
+#[derive(Default)]
+pub(crate) struct UserConstants {
+    per_texture: HashMap<TextureId, ID3D10Buffer>,
+    global: Option<(ID3D10Buffer, usize)>,
+}
+
+impl UserConstants {
+    /// Bind the constants set with [`Renderer::set_user_constants`] to the
+    /// vertex and pixel shaders, if any.
+    pub fn bind(&self, ctx: &ID3D10Device) {
+        let Some((buffer, _)) = &self.global else {
+            return;
+        };
+        let buffers = [Some(buffer.clone())];
+        unsafe {
+            ctx.VSSetConstantBuffers(
+                Renderer::USER_CONSTANTS_SLOT,
+                Some(&buffers),
+            );
+            ctx.PSSetConstantBuffers(
+                Renderer::USER_CONSTANTS_SLOT,
+                Some(&buffers),
+            );
+        }
+    }
+
+    /// Bind the constants of `texture_id`, or unbind the slot if it has
+    /// none. Does nothing unless some texture has constants, so that the
+    /// slot is left alone by default.
+    pub fn bind_texture(&self, ctx: &ID3D10Device, texture_id: TextureId) {
+        if self.per_texture.is_empty() {
+            return;
+        }
+        let buffer = self.per_texture.get(&texture_id).cloned();
+        unsafe {
+            ctx.PSSetConstantBuffers(
+                Renderer::TEXTURE_CONSTANTS_SLOT,
+                Some(&[buffer]),
+            )
+        };
+    }
+
+    pub fn remove_texture(&mut self, texture_id: TextureId) {
+        self.per_texture.remove(&texture_id);
+    }
+}
+
+impl Renderer {
+    /// The pixel shader constant buffer slot, `register(b1)`, the constants
+    /// set with [`Renderer::set_texture_constants`] are bound to.
+    pub const TEXTURE_CONSTANTS_SLOT: u32 = 1;
+
+    /// Attach `constants` to `texture_id`, to be bound at
+    /// [`Renderer::TEXTURE_CONSTANTS_SLOT`] of the pixel shader whenever a
+    /// mesh with that texture is drawn, or detach them with `None`. Meshes
+    /// with textures without constants are drawn with the slot unbound.
+    ///
+    /// This lets custom pixel shaders parameterize their effects per image,
+    /// for example with a hue shift for a single widget. The built-in shader
+    /// ignores the slot. `constants` is padded with zeros to a multiple of 16
+    /// bytes; returns `E_INVALIDARG` if it is empty or larger than 64 KiB.
+    /// The constants of user textures are dropped when they are
+    /// unregistered.
+    pub fn set_texture_constants(
+        &mut self,
+        texture_id: TextureId,
+        constants: Option<&[u8]>,
+    ) -> Result<()> {
+        match constants {
+            Some(constants) => {
+                let buffer = create_constant_buffer(
+                    &self.device,
+                    constants,
+                    D3D10_USAGE_IMMUTABLE,
+                )?;
+                self.user_constants.per_texture.insert(texture_id, buffer);
+            },
+            None => self.user_constants.remove_texture(texture_id),
+        }
+        Ok(())
+    }
+
+    /// The vertex and pixel shader constant buffer slot, `register(b2)`, the
+    /// constants set with [`Renderer::set_user_constants`] are bound to.
+    pub const USER_CONSTANTS_SLOT: u32 = 2;
+
+    /// Set the constants bound at [`Renderer::USER_CONSTANTS_SLOT`] of both
+    /// the vertex and pixel shaders for every draw, for custom shaders that
+    /// need application data such as time or animation parameters. The
+    /// built-in shaders ignore the slot, and it is left alone while no
+    /// constants are set. Pass an empty slice to remove them.
+    ///
+    /// `constants` is padded with zeros to a multiple of 16 bytes; returns
+    /// `E_INVALIDARG` if it is larger than 64 KiB. Updating constants of the
+    /// same size every frame reuses the buffer.
+    pub fn set_user_constants(&mut self, constants: &[u8]) -> Result<()> {
+        let global = &mut self.user_constants.global;
+        if constants.is_empty() {
+            *global = None;
+            return Ok(());
+        }
+        let size = constants.len().next_multiple_of(16);
+        match global {
+            Some((buffer, buffer_size)) if *buffer_size == size => {
+                let mut padded = constants.to_vec();
+                padded.resize(size, 0);
+                unsafe {
+                    self.device.UpdateSubresource(
+                        &*buffer,
+                        0,
+                        None,
+                        padded.as_ptr() as _,
+                        0,
+                        0,
+                    )
+                };
+            },
+            _ => {
+                let buffer = create_constant_buffer(
+                    &self.device,
+                    constants,
+                    D3D10_USAGE_DEFAULT,
+                )?;
+                *global = Some((buffer, size));
+            },
+        }
+        Ok(())
+    }
+}
+
+/// Create a constant buffer holding `data`, padded to a multiple of 16
+/// bytes.
+fn create_constant_buffer(
+    device: &ID3D10Device,
+    data: &[u8],
+    usage: D3D10_USAGE,
+) -> Result<ID3D10Buffer> {
+    if data.is_empty() || data.len() > MAX_SIZE {
+        log::error!("invalid size of user constants: {} bytes", data.len());
+        return Err(Error::from_hresult(E_INVALIDARG));
+    }
+    let mut padded = data.to_vec();
+    padded.resize(data.len().next_multiple_of(16), 0);
+    let mut buffer = None;
+    unsafe {
+        device.CreateBuffer(
+            &D3D10_BUFFER_DESC {
+                ByteWidth: padded.len() as _,
+                Usage: usage,
+                BindFlags: D3D10_BIND_CONSTANT_BUFFER.0 as _,
+                ..D3D10_BUFFER_DESC::default()
+            },
+            Some(&D3D10_SUBRESOURCE_DATA {
+                pSysMem: padded.as_ptr() as _,
+                ..D3D10_SUBRESOURCE_DATA::default()
+            }),
+            Some(&mut buffer),
+        )
+    }?;
+    Ok(buffer.unwrap())
+}
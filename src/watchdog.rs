@@ -0,0 +1,40 @@
+use windows::core::Result;
+
+use crate::{FrameWatchdog, RenderStats, Renderer};
+
+impl FrameWatchdog {
+    /// Warn if `gpu_time` of the frame with `stats` is over the threshold.
+    pub(crate) fn check_gpu_time(&self, gpu_time: f32, stats: &RenderStats) {
+        if gpu_time > self.gpu_ms {
+            log::warn!(
+                "slow egui frame: {gpu_time:.2} ms of GPU time, over the \
+                 threshold of {:.2} ms: {stats}",
+                self.gpu_ms,
+            );
+        }
+    }
+
+    /// Warn if the CPU time of the frame with `stats` is over the threshold.
+    pub(crate) fn check_cpu_time(&self, stats: &RenderStats) {
+        if stats.cpu_ms > self.cpu_ms {
+            log::warn!(
+                "slow egui frame: {:.2} ms of CPU time, over the threshold \
+                 of {:.2} ms: {stats}",
+                stats.cpu_ms,
+                self.cpu_ms,
+            );
+        }
+    }
+}
+
+impl Renderer {
+    /// Replace the [`RendererOptions::frame_watchdog`](crate::RendererOptions::frame_watchdog)
+    /// of this [`Renderer`].
+    pub fn set_frame_watchdog(
+        &mut self,
+        frame_watchdog: Option<FrameWatchdog>,
+    ) -> Result<()> {
+        self.options.frame_watchdog = frame_watchdog;
+        self.update_stats_collector()
+    }
+}
@@ -0,0 +1,344 @@
+//! Bootstrap helpers for plain Win32 applications that do not use `winit`.
+//!
+//! [`register_window_class`] and [`create_window`] set up a window,
+//! [`State`] pairs its swap chain with a [`Renderer`], a [`Win32Input`] and
+//! a [`DpiTracker`], and [`run_message_loop`] pumps messages and redraws
+//! when `egui` asks for it. See `examples/win32.rs` for a complete
+//! application.
+
+use std::{mem, time::Duration};
+
+use egui::CursorIcon;
+
+use windows::{
+    Win32::{
+        Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM},
+        Graphics::{Direct3D10::*, Dxgi::IDXGISwapChain},
+        System::LibraryLoader::GetModuleHandleW,
+        UI::{
+            HiDpi::{AdjustWindowRectExForDpi, GetDpiForWindow},
+            WindowsAndMessaging::*,
+        },
+    },
+    core::{Error, PCWSTR, Result},
+};
+
+use crate::{
+    DpiTracker, Renderer, RendererOptions, Win32Input,
+    swap_chain::WindowSwapChain, system_cursor::system_cursor,
+};
+
+/// Register a window class named `class_name` for the current module, with
+/// the arrow cursor and no background brush.
+pub fn register_window_class(
+    class_name: PCWSTR,
+    wnd_proc: WNDPROC,
+) -> Result<()> {
+    unsafe {
+        let class = WNDCLASSEXW {
+            cbSize: size_of::<WNDCLASSEXW>() as _,
+            style: CS_HREDRAW | CS_VREDRAW,
+            lpfnWndProc: wnd_proc,
+            hInstance: GetModuleHandleW(None)?.into(),
+            hCursor: LoadCursorW(None, IDC_ARROW)?,
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        if RegisterClassExW(&class) == 0 {
+            return Err(Error::from_thread());
+        }
+    }
+    Ok(())
+}
+
+/// Create and show an overlapped window of a class registered with
+/// [`register_window_class`].
+///
+/// `width` and `height` give the size of the client area in points; it is
+/// scaled to the DPI of the monitor the window is created on.
+pub fn create_window(
+    class_name: PCWSTR,
+    title: PCWSTR,
+    width: u32,
+    height: u32,
+) -> Result<HWND> {
+    unsafe {
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE(0),
+            class_name,
+            title,
+            WS_OVERLAPPEDWINDOW,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            CW_USEDEFAULT,
+            None,
+            None,
+            Some(GetModuleHandleW(None)?.into()),
+            None,
+        )?;
+        let dpi = GetDpiForWindow(hwnd).max(USER_DEFAULT_SCREEN_DPI);
+        let mut rect = RECT {
+            left: 0,
+            top: 0,
+            right: (width * dpi / USER_DEFAULT_SCREEN_DPI) as _,
+            bottom: (height * dpi / USER_DEFAULT_SCREEN_DPI) as _,
+        };
+        AdjustWindowRectExForDpi(
+            &mut rect,
+            WS_OVERLAPPEDWINDOW,
+            false,
+            WINDOW_EX_STYLE(0),
+            dpi,
+        )?;
+        SetWindowPos(
+            hwnd,
+            None,
+            0,
+            0,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+            SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE,
+        )?;
+        let _ = ShowWindow(hwnd, SW_SHOW);
+        Ok(hwnd)
+    }
+}
+
+const INFINITE: u32 = u32::MAX;
+
+/// Pump the message queue of the current thread until `WM_QUIT`, and return
+/// its exit code.
+///
+/// `idle` is called whenever the queue is empty and returns how long to wait
+/// for new messages before calling it again: [`Duration::ZERO`] to call it
+/// again right away, or [`Duration::MAX`] to wait for the next message.
+/// Return [`State::repaint_delay`] after drawing a frame to redraw exactly
+/// when `egui` asks for it.
+pub fn run_message_loop(mut idle: impl FnMut() -> Duration) -> i32 {
+    let mut msg = MSG::default();
+    let mut timeout = Duration::ZERO;
+    loop {
+        unsafe {
+            if !timeout.is_zero() {
+                let millis = u32::try_from(timeout.as_millis())
+                    .map_or(INFINITE, |millis| millis.min(INFINITE - 1));
+                MsgWaitForMultipleObjects(None, false, millis, QS_ALLINPUT);
+            }
+            while PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
+                if msg.message == WM_QUIT {
+                    return msg.wParam.0 as i32;
+                }
+                let _ = TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+        timeout = idle();
+    }
+}
+
+/// Shows `egui` in a plain Win32 window.
+///
+/// Forward the messages of the window to [`State::handle_message`] and draw
+/// the UI with [`State::run_ui`], usually from the `idle` callback of
+/// [`run_message_loop`].
+///
+/// Some messages, such as `WM_SIZE` during a DPI change, are sent while
+/// [`State::handle_message`] is running. A `WndProc` that keeps the state in
+/// a `RefCell` must pass such reentrant messages to `DefWindowProcW`; the
+/// swap chain is resized lazily in [`State::run_ui`], so nothing is lost.
+pub struct State {
+    device: ID3D10Device,
+    swap_chain: WindowSwapChain,
+    renderer: Renderer,
+    egui_ctx: egui::Context,
+    input: Win32Input,
+    dpi: DpiTracker,
+    clear_color: [f32; 4],
+    /// Whether the clear color changed since the last frame, which makes the
+    /// whole window dirty.
+    clear_color_changed: bool,
+    cursor_icon: CursorIcon,
+    repaint_delay: Duration,
+    close_requested: bool,
+}
+
+impl State {
+    /// Create a swap chain for `hwnd` on `device`, along with a
+    /// [`Renderer`] and the `egui` context.
+    pub fn new(hwnd: HWND, device: &ID3D10Device) -> Result<Self> {
+        Self::with_options(hwnd, device, RendererOptions::default())
+    }
+
+    /// Like [`State::new`], creating the [`Renderer`] with
+    /// [`Renderer::with_options`].
+    pub fn with_options(
+        hwnd: HWND,
+        device: &ID3D10Device,
+        options: RendererOptions,
+    ) -> Result<Self> {
+        let (width, height) = client_size(hwnd)?;
+        let swap_chain = WindowSwapChain::new(device, hwnd, width, height)?;
+        let renderer = Renderer::with_options(device, options)?;
+        let egui_ctx = egui::Context::default();
+        let dpi = DpiTracker::new(hwnd);
+        let mut input = Win32Input::new(egui_ctx.clone());
+        input.set_native_pixels_per_point(dpi.pixels_per_point());
+        Ok(Self {
+            device: device.clone(),
+            swap_chain,
+            renderer,
+            egui_ctx,
+            input,
+            dpi,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            clear_color_changed: false,
+            cursor_icon: CursorIcon::Default,
+            repaint_delay: Duration::ZERO,
+            close_requested: false,
+        })
+    }
+
+    /// The `egui` context shown in the window.
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// The [`Renderer`], for example to register user textures.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// The input translator, for example to push [`egui::Event::Paste`].
+    pub fn input_mut(&mut self) -> &mut Win32Input {
+        &mut self.input
+    }
+
+    /// The device passed to [`State::new`].
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+
+    /// The swap chain of the window.
+    pub fn swap_chain(&self) -> &IDXGISwapChain {
+        self.swap_chain.swap_chain()
+    }
+
+    /// The refresh rate of the monitor the window is mostly displayed on, in
+    /// hertz, or `None` if it is unknown. Pass it to
+    /// [`RepaintScheduler::set_refresh_rate`](crate::RepaintScheduler::set_refresh_rate)
+    /// to pace animations to the display.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        self.swap_chain.refresh_rate()
+    }
+
+    /// Set the color the window is cleared to before drawing the UI. Defaults
+    /// to opaque black.
+    pub fn set_clear_color(&mut self, clear_color: [f32; 4]) {
+        self.clear_color_changed |= self.clear_color != clear_color;
+        self.clear_color = clear_color;
+    }
+
+    /// How long `egui` asked to wait before the next frame, as of the last
+    /// call to [`State::run_ui`]. [`Duration::ZERO`] means as soon as
+    /// possible, and [`Duration::MAX`] means only on the next input event.
+    pub fn repaint_delay(&self) -> Duration {
+        self.repaint_delay
+    }
+
+    /// Whether the UI sent [`egui::ViewportCommand::Close`] during the last
+    /// call to [`State::run_ui`].
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// Handle a window message. Returns the result the `WndProc` should
+    /// return, or `None` if the message should be passed on to
+    /// `DefWindowProcW`.
+    ///
+    /// Besides input, this follows DPI changes and sets the cursor requested
+    /// by `egui`. Any message may make `egui` want to repaint; the next call
+    /// to the `idle` callback of [`run_message_loop`] takes care of that.
+    pub fn handle_message(
+        &mut self,
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> Option<LRESULT> {
+        if let Some(change) = self.dpi.handle_message(msg, wparam, lparam) {
+            self.input
+                .set_native_pixels_per_point(change.pixels_per_point);
+            if let Err(err) = change.resize_window(hwnd) {
+                log::error!("failed to resize the window: {err}");
+            }
+            return Some(LRESULT(0));
+        }
+        if msg == WM_SETCURSOR && (lparam.0 & 0xffff) as u32 == HTCLIENT {
+            let cursor = system_cursor(self.cursor_icon)
+                .and_then(|name| unsafe { LoadCursorW(None, name) }.ok());
+            unsafe { SetCursor(cursor) };
+            return Some(LRESULT(1));
+        }
+        self.input
+            .handle_message(hwnd, msg, wparam, lparam)
+            .then_some(LRESULT(0))
+    }
+
+    /// Run `egui` with `run_ui`, then clear the window, render the output
+    /// and present it. The swap chain is resized first if the size of the
+    /// client area changed.
+    ///
+    /// Of the platform output, only the cursor is handled; clipboard
+    /// commands and URLs to open are left to the application.
+    pub fn run_ui(
+        &mut self,
+        hwnd: HWND,
+        run_ui: impl FnMut(&egui::Context),
+    ) -> Result<()> {
+        let (width, height) = client_size(hwnd)?;
+        self.swap_chain.resize(width, height)?;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let Some(render_target) = self.swap_chain.render_target() else {
+            return Ok(());
+        };
+
+        let egui_input = self.input.take_raw_input(hwnd);
+        let egui_output = self.egui_ctx.run(egui_input, run_ui);
+        let (renderer_output, platform_output, viewport_output) =
+            crate::split_output(egui_output);
+        if let Some(viewport) = viewport_output.get(&egui::ViewportId::ROOT) {
+            self.repaint_delay = viewport.repaint_delay;
+            self.close_requested =
+                viewport.commands.contains(&egui::ViewportCommand::Close);
+        }
+        self.cursor_icon = platform_output.cursor_icon;
+        #[cfg(feature = "software-cursor")]
+        self.renderer.set_cursor_icon(platform_output.cursor_icon);
+
+        self.renderer.render_with_clear(
+            &self.device,
+            render_target,
+            self.clear_color,
+            &self.egui_ctx,
+            renderer_output,
+        )?;
+        let dirty_rect = if mem::take(&mut self.clear_color_changed) {
+            None
+        } else {
+            self.renderer.dirty_rect()
+        };
+        self.swap_chain.present(dirty_rect)
+    }
+}
+
+fn client_size(hwnd: HWND) -> Result<(u32, u32)> {
+    let mut rect = RECT::default();
+    unsafe { GetClientRect(hwnd, &mut rect) }?;
+    Ok((
+        (rect.right - rect.left).max(0) as u32,
+        (rect.bottom - rect.top).max(0) as u32,
+    ))
+}
@@ -0,0 +1,442 @@
+use std::time::Instant;
+
+use egui::{Event, Key, Modifiers, PointerButton, Pos2, Rect, Vec2};
+
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, RECT, WPARAM},
+    UI::{
+        Input::{Ime::*, KeyboardAndMouse::*},
+        WindowsAndMessaging::*,
+    },
+};
+
+// Defined in `Win32_UI_Controls`, which is too heavy to pull in for a single
+// constant.
+const WM_MOUSELEAVE: u32 = 0x02a3;
+
+// `D3D10_REQ_TEXTURE2D_U_OR_V_DIMENSION`.
+const MAX_TEXTURE_SIDE: usize = 8192;
+
+/// Translates Win32 window messages into [`egui::RawInput`], for applications
+/// that run their own `WndProc` instead of using `winit`.
+///
+/// Forward every message of the window to [`Win32Input::handle_message`],
+/// and call [`Win32Input::take_raw_input`] once per frame before running
+/// `egui`:
+///
+/// ```ignore
+/// fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+///     let input: &mut Win32Input = /* ... */;
+///     if input.handle_message(hwnd, msg, wparam, lparam) {
+///         return LRESULT(0);
+///     }
+///     unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+/// }
+///
+/// let raw_input = input.take_raw_input(hwnd);
+/// let full_output = egui_ctx.run(raw_input, |ctx| { /* ... */ });
+/// ```
+///
+/// Mouse, keyboard, text and IME composition messages are handled. The mouse
+/// is captured while a button is held, so drags keep working outside the
+/// window. `Ctrl+C` and `Ctrl+X` are translated into [`Event::Copy`] and
+/// [`Event::Cut`]; clipboard access itself is left to the application, which
+/// can push [`Event::Paste`] with [`Win32Input::push_event`].
+pub struct Win32Input {
+    egui_ctx: egui::Context,
+    start_time: Instant,
+    native_pixels_per_point: f32,
+    events: Vec<Event>,
+    modifiers: Modifiers,
+    pointer_pos: Option<Pos2>,
+    tracking_mouse_leave: bool,
+    captured: bool,
+    high_surrogate: Option<u16>,
+    focused: bool,
+}
+
+impl Win32Input {
+    /// Create a translator for a window showing `egui_ctx`.
+    pub fn new(egui_ctx: egui::Context) -> Self {
+        Self {
+            egui_ctx,
+            start_time: Instant::now(),
+            native_pixels_per_point: 1.0,
+            events: Vec::new(),
+            modifiers: Modifiers::default(),
+            pointer_pos: None,
+            tracking_mouse_leave: false,
+            captured: false,
+            high_surrogate: None,
+            focused: true,
+        }
+    }
+
+    /// The scale factor of the monitor the window is on, for example `1.5`
+    /// at 144 DPI. Defaults to `1.0`.
+    pub fn native_pixels_per_point(&self) -> f32 {
+        self.native_pixels_per_point
+    }
+
+    /// Set the scale factor of the monitor the window is on. Mouse positions
+    /// are divided by this value multiplied by the zoom factor of `egui`.
+    ///
+    /// Use [`DpiTracker`](crate::DpiTracker) to keep it up to date when the
+    /// window moves between monitors.
+    pub fn set_native_pixels_per_point(
+        &mut self,
+        native_pixels_per_point: f32,
+    ) {
+        self.native_pixels_per_point = native_pixels_per_point;
+    }
+
+    /// Queue an event that is not produced by window messages, such as
+    /// [`Event::Paste`]. It is delivered with the next
+    /// [`Win32Input::take_raw_input`].
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    /// Translate a window message. Returns `true` if the message was consumed
+    /// and the `WndProc` should return `0` without calling `DefWindowProcW`.
+    ///
+    /// Most input messages are recorded but not consumed, so that default
+    /// processing such as `Alt+F4` and window activation keeps working. IME
+    /// composition messages are consumed, because `egui` draws the
+    /// composition string itself and default processing would deliver the
+    /// result a second time as `WM_CHAR`.
+    pub fn handle_message(
+        &mut self,
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> bool {
+        match msg {
+            WM_MOUSEMOVE => {
+                if !self.tracking_mouse_leave {
+                    let mut track = TRACKMOUSEEVENT {
+                        cbSize: size_of::<TRACKMOUSEEVENT>() as _,
+                        dwFlags: TME_LEAVE,
+                        hwndTrack: hwnd,
+                        dwHoverTime: 0,
+                    };
+                    self.tracking_mouse_leave =
+                        unsafe { TrackMouseEvent(&mut track) }.is_ok();
+                }
+                let pos = self.pos_from_lparam(lparam);
+                self.pointer_pos = Some(pos);
+                self.events.push(Event::PointerMoved(pos));
+            },
+            WM_MOUSELEAVE => {
+                self.tracking_mouse_leave = false;
+                self.pointer_pos = None;
+                self.events.push(Event::PointerGone);
+            },
+            WM_LBUTTONDOWN | WM_LBUTTONDBLCLK => {
+                self.on_button(hwnd, PointerButton::Primary, true, lparam)
+            },
+            WM_LBUTTONUP => {
+                self.on_button(hwnd, PointerButton::Primary, false, lparam)
+            },
+            WM_RBUTTONDOWN | WM_RBUTTONDBLCLK => {
+                self.on_button(hwnd, PointerButton::Secondary, true, lparam)
+            },
+            WM_RBUTTONUP => {
+                self.on_button(hwnd, PointerButton::Secondary, false, lparam)
+            },
+            WM_MBUTTONDOWN | WM_MBUTTONDBLCLK => {
+                self.on_button(hwnd, PointerButton::Middle, true, lparam)
+            },
+            WM_MBUTTONUP => {
+                self.on_button(hwnd, PointerButton::Middle, false, lparam)
+            },
+            WM_XBUTTONDOWN | WM_XBUTTONDBLCLK | WM_XBUTTONUP => {
+                let button = if hiword(wparam.0) == XBUTTON1 {
+                    PointerButton::Extra1
+                } else {
+                    PointerButton::Extra2
+                };
+                let pressed = msg != WM_XBUTTONUP;
+                self.on_button(hwnd, button, pressed, lparam);
+            },
+            WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+                let delta = hiword(wparam.0) as i16 as f32 / WHEEL_DELTA as f32;
+                // Like `winit`, positive horizontal deltas scroll to the left.
+                let delta = if msg == WM_MOUSEWHEEL {
+                    Vec2::new(0.0, delta)
+                } else {
+                    Vec2::new(-delta, 0.0)
+                };
+                self.events.push(Event::MouseWheel {
+                    unit: egui::MouseWheelUnit::Line,
+                    delta,
+                    modifiers: self.modifiers,
+                });
+            },
+            WM_KEYDOWN | WM_SYSKEYDOWN | WM_KEYUP | WM_SYSKEYUP => {
+                self.modifiers = current_modifiers();
+                let pressed = msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN;
+                let Some(key) = key_from_vk(VIRTUAL_KEY(wparam.0 as u16))
+                else {
+                    return false;
+                };
+                if pressed && self.modifiers.command {
+                    match key {
+                        Key::C => self.events.push(Event::Copy),
+                        Key::X => self.events.push(Event::Cut),
+                        _ => {},
+                    }
+                }
+                self.events.push(Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    // Bit 30 is set if the key was already down.
+                    repeat: pressed && lparam.0 & (1 << 30) != 0,
+                    modifiers: self.modifiers,
+                });
+            },
+            WM_CHAR => {
+                let unit = wparam.0 as u16;
+                let chr = if (0xd800..0xdc00).contains(&unit) {
+                    self.high_surrogate = Some(unit);
+                    return false;
+                } else if let Some(high) = self.high_surrogate.take() {
+                    char::decode_utf16([high, unit]).next().and_then(Result::ok)
+                } else {
+                    char::from_u32(unit as u32)
+                };
+                if let Some(chr) = chr
+                    && is_printable_char(chr)
+                {
+                    self.events.push(Event::Text(chr.to_string()));
+                }
+            },
+            WM_IME_STARTCOMPOSITION => {
+                self.events.push(Event::Ime(egui::ImeEvent::Enabled));
+                return true;
+            },
+            WM_IME_COMPOSITION => {
+                let flags = lparam.0 as u32;
+                if flags & GCS_RESULTSTR.0 != 0
+                    && let Some(text) = composition_string(hwnd, GCS_RESULTSTR)
+                {
+                    self.events.push(Event::Ime(egui::ImeEvent::Commit(text)));
+                }
+                if flags & GCS_COMPSTR.0 != 0
+                    && let Some(text) = composition_string(hwnd, GCS_COMPSTR)
+                {
+                    self.events.push(Event::Ime(egui::ImeEvent::Preedit(text)));
+                }
+                return true;
+            },
+            WM_IME_ENDCOMPOSITION => {
+                self.events
+                    .push(Event::Ime(egui::ImeEvent::Preedit(String::new())));
+                self.events.push(Event::Ime(egui::ImeEvent::Disabled));
+                return true;
+            },
+            WM_SETFOCUS | WM_KILLFOCUS => {
+                self.focused = msg == WM_SETFOCUS;
+                // Key releases are not delivered while the window is not
+                // focused.
+                self.modifiers = Modifiers::default();
+                self.events.push(Event::WindowFocused(self.focused));
+            },
+            WM_CAPTURECHANGED if self.captured => {
+                // Capture was taken away mid-drag: release all buttons.
+                self.captured = false;
+                if let Some(pos) = self.pointer_pos {
+                    for button in [
+                        PointerButton::Primary,
+                        PointerButton::Secondary,
+                        PointerButton::Middle,
+                    ] {
+                        self.events.push(Event::PointerButton {
+                            pos,
+                            button,
+                            pressed: false,
+                            modifiers: self.modifiers,
+                        });
+                    }
+                }
+            },
+            _ => {},
+        }
+        false
+    }
+
+    /// Take the input gathered since the last call, for
+    /// [`egui::Context::run`]. The screen rect is the client area of `hwnd`.
+    pub fn take_raw_input(&mut self, hwnd: HWND) -> egui::RawInput {
+        let pixels_per_point = self.pixels_per_point();
+        let mut client_rect = RECT::default();
+        let screen_rect = unsafe { GetClientRect(hwnd, &mut client_rect) }
+            .ok()
+            .map(|_| {
+                Rect::from_min_size(
+                    Pos2::ZERO,
+                    Vec2::new(
+                        (client_rect.right - client_rect.left) as f32,
+                        (client_rect.bottom - client_rect.top) as f32,
+                    ) / pixels_per_point,
+                )
+            });
+
+        let mut raw_input = egui::RawInput {
+            screen_rect,
+            max_texture_side: Some(MAX_TEXTURE_SIDE),
+            time: Some(self.start_time.elapsed().as_secs_f64()),
+            modifiers: self.modifiers,
+            events: std::mem::take(&mut self.events),
+            focused: self.focused,
+            ..Default::default()
+        };
+        raw_input
+            .viewports
+            .entry(raw_input.viewport_id)
+            .or_default()
+            .native_pixels_per_point = Some(self.native_pixels_per_point);
+        raw_input
+    }
+
+    fn pixels_per_point(&self) -> f32 {
+        self.native_pixels_per_point * self.egui_ctx.zoom_factor()
+    }
+
+    fn pos_from_lparam(&self, lparam: LPARAM) -> Pos2 {
+        let x = loword(lparam.0 as usize) as i16 as f32;
+        let y = hiword(lparam.0 as usize) as i16 as f32;
+        Pos2::new(x, y) / self.pixels_per_point()
+    }
+
+    fn on_button(
+        &mut self,
+        hwnd: HWND,
+        button: PointerButton,
+        pressed: bool,
+        lparam: LPARAM,
+    ) {
+        let pos = self.pos_from_lparam(lparam);
+        self.pointer_pos = Some(pos);
+        self.events.push(Event::PointerButton {
+            pos,
+            button,
+            pressed,
+            modifiers: self.modifiers,
+        });
+        unsafe {
+            if pressed {
+                SetCapture(hwnd);
+                self.captured = true;
+            } else if self.captured && !any_mouse_button_down() {
+                self.captured = false;
+                let _ = ReleaseCapture();
+            }
+        }
+    }
+}
+
+fn loword(value: usize) -> u16 {
+    value as u16
+}
+
+fn hiword(value: usize) -> u16 {
+    (value >> 16) as u16
+}
+
+fn is_key_down(vk: VIRTUAL_KEY) -> bool {
+    unsafe { GetKeyState(vk.0 as i32) < 0 }
+}
+
+fn any_mouse_button_down() -> bool {
+    [VK_LBUTTON, VK_RBUTTON, VK_MBUTTON, VK_XBUTTON1, VK_XBUTTON2]
+        .into_iter()
+        .any(is_key_down)
+}
+
+fn current_modifiers() -> Modifiers {
+    let ctrl = is_key_down(VK_CONTROL);
+    Modifiers {
+        alt: is_key_down(VK_MENU),
+        ctrl,
+        shift: is_key_down(VK_SHIFT),
+        mac_cmd: false,
+        command: ctrl,
+    }
+}
+
+fn composition_string(
+    hwnd: HWND,
+    kind: IME_COMPOSITION_STRING,
+) -> Option<String> {
+    unsafe {
+        let himc = ImmGetContext(hwnd);
+        if himc.is_invalid() {
+            return None;
+        }
+        let len = ImmGetCompositionStringW(himc, kind, None, 0);
+        let text = (len >= 0).then(|| {
+            let mut buf = vec![0u16; len as usize / 2];
+            ImmGetCompositionStringW(
+                himc,
+                kind,
+                Some(buf.as_mut_ptr().cast()),
+                len as u32,
+            );
+            String::from_utf16_lossy(&buf)
+        });
+        let _ = ImmReleaseContext(hwnd, himc);
+        text
+    }
+}
+
+fn is_printable_char(chr: char) -> bool {
+    let is_in_private_use_area = ('\u{e000}'..='\u{f8ff}').contains(&chr)
+        || ('\u{f0000}'..='\u{ffffd}').contains(&chr)
+        || ('\u{100000}'..='\u{10fffd}').contains(&chr);
+    !is_in_private_use_area && !chr.is_ascii_control()
+}
+
+fn key_from_vk(vk: VIRTUAL_KEY) -> Option<Key> {
+    Some(match vk {
+        VK_DOWN => Key::ArrowDown,
+        VK_LEFT => Key::ArrowLeft,
+        VK_RIGHT => Key::ArrowRight,
+        VK_UP => Key::ArrowUp,
+        VK_ESCAPE => Key::Escape,
+        VK_TAB => Key::Tab,
+        VK_BACK => Key::Backspace,
+        VK_RETURN => Key::Enter,
+        VK_SPACE => Key::Space,
+        VK_INSERT => Key::Insert,
+        VK_DELETE => Key::Delete,
+        VK_HOME => Key::Home,
+        VK_END => Key::End,
+        VK_PRIOR => Key::PageUp,
+        VK_NEXT => Key::PageDown,
+        VK_OEM_COMMA => Key::Comma,
+        VK_OEM_PERIOD | VK_DECIMAL => Key::Period,
+        VK_OEM_MINUS | VK_SUBTRACT => Key::Minus,
+        VK_OEM_PLUS => Key::Equals,
+        VK_ADD => Key::Plus,
+        VK_OEM_1 => Key::Semicolon,
+        VK_OEM_2 | VK_DIVIDE => Key::Slash,
+        VK_OEM_3 => Key::Backtick,
+        VK_OEM_4 => Key::OpenBracket,
+        VK_OEM_5 => Key::Backslash,
+        VK_OEM_6 => Key::CloseBracket,
+        VK_OEM_7 => Key::Quote,
+        VIRTUAL_KEY(vk @ 0x30..=0x39) | VIRTUAL_KEY(vk @ 0x41..=0x5a) => {
+            return Key::from_name(&char::from(vk as u8).to_string());
+        },
+        VIRTUAL_KEY(vk @ 0x60..=0x69) => {
+            return Key::from_name(&char::from(vk as u8 - 0x30).to_string());
+        },
+        VIRTUAL_KEY(vk @ 0x70..=0x87) => {
+            return Key::from_name(&format!("F{}", vk - 0x6f));
+        },
+        _ => return None,
+    })
+}
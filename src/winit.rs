@@ -0,0 +1,219 @@
+//! Integration with [`winit`](https://crates.io/crates/winit) and
+//! [`egui-winit`](https://crates.io/crates/egui-winit).
+//!
+//! [`State`] owns everything needed to show `egui` in a `winit` window: the
+//! swap chain of the window, a [`Renderer`] and an [`egui_winit::State`].
+//! Forward window events to [`State::on_window_event`] and draw the UI on
+//! [`WindowEvent::RedrawRequested`] with [`State::run_ui`]:
+//!
+//! ```ignore
+//! fn window_event(&mut self, window: &Window, event: WindowEvent) {
+//!     let response = self.egui.on_window_event(window, &event)?;
+//!     if response.repaint {
+//!         window.request_redraw();
+//!     }
+//!     if event == WindowEvent::RedrawRequested {
+//!         self.egui.run_ui(window, |ctx| {
+//!             egui::CentralPanel::default().show(ctx, |ui| ui.label("Hello"));
+//!         })?;
+//!     }
+//! }
+//! ```
+
+use std::{mem, time::Duration};
+
+use ::winit::{
+    dpi::PhysicalSize,
+    event::WindowEvent,
+    raw_window_handle::{HasWindowHandle, RawWindowHandle},
+    window::Window,
+};
+
+use windows::{
+    Win32::{
+        Foundation::{E_INVALIDARG, HWND},
+        Graphics::{Direct3D10::*, Dxgi::IDXGISwapChain},
+    },
+    core::{Error, Result},
+};
+
+use crate::{Renderer, RendererOptions, swap_chain::WindowSwapChain};
+
+/// Shows `egui` in a `winit` window. See the [module-level
+/// documentation](self) for an example.
+pub struct State {
+    device: ID3D10Device,
+    swap_chain: WindowSwapChain,
+    renderer: Renderer,
+    egui_ctx: egui::Context,
+    egui_winit: egui_winit::State,
+    clear_color: [f32; 4],
+    /// Whether the clear color changed since the last frame, which makes the
+    /// whole window dirty.
+    clear_color_changed: bool,
+    repaint_delay: Duration,
+    close_requested: bool,
+}
+
+impl State {
+    /// Create a swap chain for `window` on `device`, along with a
+    /// [`Renderer`] and the `egui` context.
+    pub fn new(window: &Window, device: &ID3D10Device) -> Result<Self> {
+        Self::with_options(window, device, RendererOptions::default())
+    }
+
+    /// Like [`State::new`], creating the [`Renderer`] with
+    /// [`Renderer::with_options`].
+    pub fn with_options(
+        window: &Window,
+        device: &ID3D10Device,
+        options: RendererOptions,
+    ) -> Result<Self> {
+        let hwnd = window_handle(window)?;
+        let PhysicalSize { width, height } = window.inner_size();
+        let swap_chain = WindowSwapChain::new(device, hwnd, width, height)?;
+        let renderer = Renderer::with_options(device, options)?;
+
+        let egui_ctx = egui::Context::default();
+        let egui_winit = egui_winit::State::new(
+            egui_ctx.clone(),
+            egui_ctx.viewport_id(),
+            window,
+            Some(window.scale_factor() as f32),
+            window.theme(),
+            Some(D3D10_REQ_TEXTURE2D_U_OR_V_DIMENSION as usize),
+        );
+
+        Ok(Self {
+            device: device.clone(),
+            swap_chain,
+            renderer,
+            egui_ctx,
+            egui_winit,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            clear_color_changed: false,
+            repaint_delay: Duration::ZERO,
+            close_requested: false,
+        })
+    }
+
+    /// The `egui` context shown in the window.
+    pub fn egui_ctx(&self) -> &egui::Context {
+        &self.egui_ctx
+    }
+
+    /// The [`Renderer`], for example to register user textures.
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+
+    /// The device passed to [`State::new`].
+    pub fn device(&self) -> &ID3D10Device {
+        &self.device
+    }
+
+    /// The swap chain of the window.
+    pub fn swap_chain(&self) -> &IDXGISwapChain {
+        self.swap_chain.swap_chain()
+    }
+
+    /// The refresh rate of the monitor the window is mostly displayed on, in
+    /// hertz, or `None` if it is unknown. Pass it to
+    /// [`RepaintScheduler::set_refresh_rate`](crate::RepaintScheduler::set_refresh_rate)
+    /// to pace animations to the display.
+    pub fn refresh_rate(&self) -> Option<f32> {
+        self.swap_chain.refresh_rate()
+    }
+
+    /// Set the color the window is cleared to before drawing the UI. Defaults
+    /// to opaque black.
+    pub fn set_clear_color(&mut self, clear_color: [f32; 4]) {
+        self.clear_color_changed |= self.clear_color != clear_color;
+        self.clear_color = clear_color;
+    }
+
+    /// How long `egui` asked to wait before the next frame, as of the last
+    /// call to [`State::run_ui`]. [`Duration::ZERO`] means as soon as
+    /// possible, and [`Duration::MAX`] means only on the next input event.
+    pub fn repaint_delay(&self) -> Duration {
+        self.repaint_delay
+    }
+
+    /// Whether the UI sent [`egui::ViewportCommand::Close`] during the last
+    /// call to [`State::run_ui`].
+    pub fn close_requested(&self) -> bool {
+        self.close_requested
+    }
+
+    /// Pass a window event to `egui`. [`WindowEvent::Resized`] also resizes
+    /// the swap chain.
+    ///
+    /// Request a redraw of the window if the returned response asks for a
+    /// repaint, and only handle the event yourself if it was not consumed.
+    pub fn on_window_event(
+        &mut self,
+        window: &Window,
+        event: &WindowEvent,
+    ) -> Result<egui_winit::EventResponse> {
+        if let WindowEvent::Resized(PhysicalSize { width, height }) = event {
+            self.resize(*width, *height)?;
+        }
+        Ok(self.egui_winit.on_window_event(window, event))
+    }
+
+    /// Resize the swap chain. Sizes of zero, as reported for minimized
+    /// windows, are ignored.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<()> {
+        self.swap_chain.resize(width, height)
+    }
+
+    /// Run `egui` with `run_ui`, then clear the window, render the output
+    /// and present it. The platform output (cursor, clipboard, IME) is
+    /// handed to `egui-winit`.
+    pub fn run_ui(
+        &mut self,
+        window: &Window,
+        run_ui: impl FnMut(&egui::Context),
+    ) -> Result<()> {
+        let Some(render_target) = self.swap_chain.render_target() else {
+            return Ok(());
+        };
+        let egui_input = self.egui_winit.take_egui_input(window);
+        let egui_output = self.egui_ctx.run(egui_input, run_ui);
+        let (renderer_output, platform_output, viewport_output) =
+            crate::split_output(egui_output);
+        if let Some(viewport) = viewport_output.get(&egui::ViewportId::ROOT) {
+            self.repaint_delay = viewport.repaint_delay;
+            self.close_requested =
+                viewport.commands.contains(&egui::ViewportCommand::Close);
+        }
+        #[cfg(feature = "software-cursor")]
+        self.renderer.set_cursor_icon(platform_output.cursor_icon);
+        self.egui_winit
+            .handle_platform_output(window, platform_output);
+
+        self.renderer.render_with_clear(
+            &self.device,
+            render_target,
+            self.clear_color,
+            &self.egui_ctx,
+            renderer_output,
+        )?;
+        let dirty_rect = if mem::take(&mut self.clear_color_changed) {
+            None
+        } else {
+            self.renderer.dirty_rect()
+        };
+        self.swap_chain.present(dirty_rect)
+    }
+}
+
+fn window_handle(window: &Window) -> Result<HWND> {
+    match window.window_handle().map(|handle| handle.as_raw()) {
+        Ok(RawWindowHandle::Win32(handle)) => Ok(HWND(handle.hwnd.get() as _)),
+        _ => {
+            log::error!("the window does not have a Win32 window handle.");
+            Err(Error::from_hresult(E_INVALIDARG))
+        },
+    }
+}
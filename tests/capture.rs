@@ -0,0 +1,99 @@
+//! Copying rendered frames to capture textures, on a WARP device when there
+//! is no GPU.
+
+use egui::Color32;
+use egui_directx10::{DeviceOptions, Renderer, create_device, split_output};
+use windows::Win32::{
+    Foundation::E_INVALIDARG,
+    Graphics::{Direct3D10::*, Dxgi::Common::*},
+};
+
+fn texture(
+    device: &ID3D10Device,
+    size: u32,
+    format: DXGI_FORMAT,
+    bind_flags: D3D10_BIND_FLAG,
+) -> ID3D10Texture2D {
+    let desc = D3D10_TEXTURE2D_DESC {
+        Width: size,
+        Height: size,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: format,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D10_USAGE_DEFAULT,
+        BindFlags: bind_flags.0 as _,
+        CPUAccessFlags: 0,
+        MiscFlags: 0,
+    };
+    unsafe { device.CreateTexture2D(&desc, None) }.unwrap()
+}
+
+/// Render an empty frame of `egui` to a render target of `size` cleared to
+/// red.
+fn render(
+    device: &ID3D10Device,
+    renderer: &mut Renderer,
+    size: u32,
+) -> windows::core::Result<()> {
+    let target = texture(
+        device,
+        size,
+        DXGI_FORMAT_R8G8B8A8_UNORM,
+        D3D10_BIND_RENDER_TARGET,
+    );
+    let mut render_target = None;
+    unsafe {
+        device
+            .CreateRenderTargetView(&target, None, Some(&mut render_target))
+            .unwrap()
+    };
+    let render_target = render_target.unwrap();
+    unsafe { device.ClearRenderTargetView(&render_target, &[1., 0., 0., 1.]) };
+    let ctx = egui::Context::default();
+    let (output, _, _) = split_output(ctx.run(Default::default(), |_| {}));
+    renderer.render(device, &render_target, &ctx, output)
+}
+
+#[test]
+fn frames_are_copied_within_smaller_captures() {
+    let (device, _) = create_device(&DeviceOptions::default()).unwrap();
+    let mut renderer = Renderer::new(&device).unwrap();
+    let capture = texture(
+        &device,
+        16,
+        DXGI_FORMAT_R8G8B8A8_UNORM,
+        D3D10_BIND_SHADER_RESOURCE,
+    );
+    renderer.set_capture_texture(Some(capture.clone())).unwrap();
+    render(&device, &mut renderer, 64).unwrap();
+
+    let mut srv = None;
+    unsafe {
+        device
+            .CreateShaderResourceView(&capture, None, Some(&mut srv))
+            .unwrap()
+    };
+    let texture_id = renderer.register_user_texture(srv.unwrap());
+    let image = renderer.read_texture(texture_id).unwrap();
+    assert_eq!(image.size, [16, 16]);
+    assert!(image.pixels.iter().all(|pixel| *pixel == Color32::RED));
+}
+
+#[test]
+fn captures_of_another_format_are_rejected() {
+    let (device, _) = create_device(&DeviceOptions::default()).unwrap();
+    let mut renderer = Renderer::new(&device).unwrap();
+    let capture = texture(
+        &device,
+        64,
+        DXGI_FORMAT_B8G8R8A8_UNORM,
+        D3D10_BIND_SHADER_RESOURCE,
+    );
+    renderer.set_capture_texture(Some(capture)).unwrap();
+    let err = render(&device, &mut renderer, 64).unwrap_err();
+    assert_eq!(err.code(), E_INVALIDARG);
+}
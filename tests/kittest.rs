@@ -0,0 +1,20 @@
+//! Rendering `egui_kittest` harnesses with `HeadlessRenderer`, on a WARP
+//! device when there is no GPU.
+
+use egui_directx10::HeadlessRenderer;
+use egui_kittest::{Harness, kittest::Queryable};
+
+#[test]
+fn harness_renders_label() {
+    let mut harness = Harness::builder()
+        .with_size(egui::vec2(200., 100.))
+        .renderer(HeadlessRenderer::new().unwrap())
+        .build_ui(|ui| {
+            ui.label("Hello, world");
+        });
+    harness.get_by_label("Hello, world");
+
+    let image = harness.render().unwrap();
+    assert_eq!(image.dimensions(), (200, 100));
+    assert!(image.pixels().any(|pixel| pixel[3] != 0));
+}
@@ -0,0 +1,50 @@
+//! Texture namespaces of several `egui` contexts drawn by one renderer, on
+//! a WARP device when there is no GPU.
+
+use egui_directx10::HeadlessRenderer;
+
+/// Run a frame of `ctx` showing a label, and count the pixels it covers.
+fn render_label(renderer: &mut HeadlessRenderer, ctx: &egui::Context) -> usize {
+    let input = egui::RawInput {
+        screen_rect: Some(egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(200., 100.),
+        )),
+        ..Default::default()
+    };
+    let output = ctx.run(input, |ctx| {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::NONE)
+            .show(ctx, |ui| ui.label("Hello, world"));
+    });
+    renderer.handle_delta(&output.textures_delta).unwrap();
+    let image = renderer.render(ctx, &output).unwrap();
+    image.pixels.iter().filter(|pixel| pixel.a() != 0).count()
+}
+
+#[test]
+fn text_renders_after_memory_reset() {
+    let mut renderer = HeadlessRenderer::new().unwrap();
+    let [first, second] = [egui::Context::default(), egui::Context::default()];
+    assert_ne!(render_label(&mut renderer, &first), 0);
+    let covered = render_label(&mut renderer, &second);
+    assert_ne!(covered, 0);
+
+    // As the "Reset all" button of `Context::memory_ui` does.
+    second.memory_mut(|memory| *memory = Default::default());
+    render_label(&mut renderer, &second);
+    assert_eq!(render_label(&mut renderer, &second), covered);
+}
+
+#[test]
+fn removed_contexts_release_their_textures() {
+    let mut renderer = HeadlessRenderer::new().unwrap();
+    let [first, second] = [egui::Context::default(), egui::Context::default()];
+    render_label(&mut renderer, &first);
+    render_label(&mut renderer, &second);
+    renderer.renderer_mut().remove_context(&second);
+
+    let third = egui::Context::default();
+    assert_ne!(render_label(&mut renderer, &third), 0);
+    assert_ne!(render_label(&mut renderer, &first), 0);
+}
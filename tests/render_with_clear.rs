@@ -0,0 +1,77 @@
+//! Clearing render targets as part of a frame, on a WARP device when there
+//! is no GPU.
+
+use egui::Color32;
+use egui_directx10::{DeviceOptions, Renderer, create_device, split_output};
+use windows::Win32::Graphics::{
+    Direct3D::D3D10_SRV_DIMENSION_TEXTURE2D, Direct3D10::*, Dxgi::Common::*,
+};
+
+#[test]
+fn clear_color_is_written_as_is_through_srgb_views() {
+    let (device, _) = create_device(&DeviceOptions::default()).unwrap();
+    let mut renderer = Renderer::new(&device).unwrap();
+    let desc = D3D10_TEXTURE2D_DESC {
+        Width: 16,
+        Height: 16,
+        MipLevels: 1,
+        ArraySize: 1,
+        Format: DXGI_FORMAT_R8G8B8A8_TYPELESS,
+        SampleDesc: DXGI_SAMPLE_DESC {
+            Count: 1,
+            Quality: 0,
+        },
+        Usage: D3D10_USAGE_DEFAULT,
+        BindFlags: (D3D10_BIND_RENDER_TARGET.0 | D3D10_BIND_SHADER_RESOURCE.0)
+            as _,
+        CPUAccessFlags: 0,
+        MiscFlags: 0,
+    };
+    let texture = unsafe { device.CreateTexture2D(&desc, None) }.unwrap();
+    let mut render_target = None;
+    let mut srv = None;
+    unsafe {
+        let desc = D3D10_RENDER_TARGET_VIEW_DESC {
+            Format: DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
+            ViewDimension: D3D10_RTV_DIMENSION_TEXTURE2D,
+            ..Default::default()
+        };
+        device
+            .CreateRenderTargetView(
+                &texture,
+                Some(&desc),
+                Some(&mut render_target),
+            )
+            .unwrap();
+        let desc = D3D10_SHADER_RESOURCE_VIEW_DESC {
+            Format: DXGI_FORMAT_R8G8B8A8_UNORM,
+            ViewDimension: D3D10_SRV_DIMENSION_TEXTURE2D,
+            Anonymous: D3D10_SHADER_RESOURCE_VIEW_DESC_0 {
+                Texture2D: D3D10_TEX2D_SRV {
+                    MostDetailedMip: 0,
+                    MipLevels: 1,
+                },
+            },
+        };
+        device
+            .CreateShaderResourceView(&texture, Some(&desc), Some(&mut srv))
+            .unwrap();
+    }
+
+    let ctx = egui::Context::default();
+    let (output, _, _) = split_output(ctx.run(Default::default(), |_| {}));
+    renderer
+        .render_with_clear(
+            &device,
+            &render_target.unwrap(),
+            [0.5, 0.5, 0.5, 1.],
+            &ctx,
+            output,
+        )
+        .unwrap();
+
+    let texture_id = renderer.register_user_texture(srv.unwrap());
+    let image = renderer.read_texture(texture_id).unwrap();
+    let gray = Color32::from_rgba_premultiplied(128, 128, 128, 255);
+    assert!(image.pixels.iter().all(|pixel| *pixel == gray));
+}
@@ -0,0 +1,132 @@
+//! The texture updates of a sequence of frames, as recorded with the
+//! `testing` feature, on a WARP device when there is no GPU.
+
+use egui::{
+    Color32, ColorImage, TextureId, TextureOptions, TexturesDelta,
+    epaint::ImageDelta,
+};
+use egui_directx10::{
+    DeviceOptions, Renderer, TextureEvent, TextureEventKind, TextureState,
+    create_device,
+};
+
+fn set(id: u64, delta: ImageDelta) -> TexturesDelta {
+    TexturesDelta {
+        set: vec![(TextureId::Managed(id), delta)],
+        free: Vec::new(),
+    }
+}
+
+fn image(size: [usize; 2]) -> ColorImage {
+    ColorImage::filled(size, Color32::WHITE)
+}
+
+fn event(id: u64, kind: TextureEventKind) -> TextureEvent {
+    TextureEvent {
+        id: TextureId::Managed(id),
+        kind,
+    }
+}
+
+#[test]
+fn textures_are_created_updated_and_freed() {
+    let (device, _) = create_device(&DeviceOptions::default()).unwrap();
+    let mut renderer = Renderer::new(&device).unwrap();
+    let options = TextureOptions::default();
+
+    let full = ImageDelta::full(image([64, 32]), options);
+    renderer.update_textures(&device, set(1, full)).unwrap();
+    let partial = ImageDelta::partial([8, 4], image([16, 8]), options);
+    renderer.update_textures(&device, set(1, partial)).unwrap();
+    assert_eq!(
+        renderer.take_texture_events(),
+        [
+            event(1, TextureEventKind::Created([64, 32])),
+            event(
+                1,
+                TextureEventKind::Updated {
+                    pos: [8, 4],
+                    size: [16, 8],
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        renderer.managed_textures(),
+        [TextureState {
+            id: TextureId::Managed(1),
+            size: [64, 32],
+            resident: true,
+        }]
+    );
+
+    // Replacing a texture by a whole image of another size.
+    let full = ImageDelta::full(image([128, 16]), options);
+    renderer.update_textures(&device, set(1, full)).unwrap();
+    assert_eq!(
+        renderer.take_texture_events(),
+        [
+            event(1, TextureEventKind::Freed),
+            event(1, TextureEventKind::Created([128, 16])),
+        ]
+    );
+
+    let free = TexturesDelta {
+        set: Vec::new(),
+        free: vec![TextureId::Managed(1)],
+    };
+    renderer.update_textures(&device, free).unwrap();
+    assert_eq!(
+        renderer.take_texture_events(),
+        [event(1, TextureEventKind::Freed)]
+    );
+    assert_eq!(renderer.managed_textures(), []);
+}
+
+#[test]
+fn invalid_updates_are_ignored() {
+    let (device, _) = create_device(&DeviceOptions::default()).unwrap();
+    let mut renderer = Renderer::new(&device).unwrap();
+    let options = TextureOptions::default();
+
+    let partial = ImageDelta::partial([0, 0], image([4, 4]), options);
+    renderer.update_textures(&device, set(1, partial)).unwrap();
+    let full = ImageDelta::full(image([8, 8]), options);
+    renderer.update_textures(&device, set(1, full)).unwrap();
+    let partial = ImageDelta::partial([6, 6], image([4, 4]), options);
+    renderer.update_textures(&device, set(1, partial)).unwrap();
+    assert_eq!(
+        renderer.take_texture_events(),
+        [
+            event(1, TextureEventKind::Ignored),
+            event(1, TextureEventKind::Created([8, 8])),
+            event(1, TextureEventKind::Ignored),
+        ]
+    );
+}
+
+#[test]
+fn evicted_textures_are_restored() {
+    let (device, _) = create_device(&DeviceOptions::default()).unwrap();
+    let mut renderer = Renderer::new(&device).unwrap();
+
+    let full = ImageDelta::full(image([32, 16]), TextureOptions::default());
+    renderer.update_textures(&device, set(1, full)).unwrap();
+    renderer.take_texture_events();
+
+    renderer.trim(true).unwrap();
+    assert_eq!(
+        renderer.take_texture_events(),
+        [event(1, TextureEventKind::Evicted)]
+    );
+    assert!(!renderer.managed_textures()[0].resident);
+
+    renderer
+        .update_textures(&device, TexturesDelta::default())
+        .unwrap();
+    assert_eq!(
+        renderer.take_texture_events(),
+        [event(1, TextureEventKind::Restored([32, 16]))]
+    );
+    assert!(renderer.managed_textures()[0].resident);
+}